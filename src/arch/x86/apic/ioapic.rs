@@ -0,0 +1,185 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Date: 2025-06-13
+// Author: Alexander Kuzin <alkuzindev@gmail.com>.
+
+//! I/O APIC (Advanced Programmable Interrupt Controller) driver.
+//!
+//! # Description
+//! I/O APIC receives device interrupts on its input pins (global system
+//! interrupts, GSIs) and routes them to local APICs according to its
+//! redirection table. Registers are accessed indirectly through register
+//! select & data window pair.
+
+use crate::kernel::mm::{
+    PhysAddr,
+    mmio::{self, CacheMode, MmioRegion},
+};
+use spin::{Mutex, Once};
+
+/// Default physical address of I/O APIC registers.
+pub const DEFAULT_BASE: PhysAddr = 0xFEC00000;
+
+/// Size of I/O APIC registers area.
+const REGISTERS_SIZE: usize = 0x20;
+
+/// Register select register.
+const IOREGSEL: usize = 0x00;
+
+/// Data window register.
+const IOWIN: usize = 0x10;
+
+/// I/O APIC version register index.
+const IOAPICVER: u8 = 0x01;
+
+/// First redirection table register index.
+const IOREDTBL: u8 = 0x10;
+
+/// Redirection entry mask bit.
+const ENTRY_MASKED: u32 = 1 << 16;
+
+/// I/O APIC registers.
+struct IoApic {
+    /// Mapped registers.
+    regs: MmioRegion,
+    /// Number of redirection entries.
+    entries: u8,
+}
+
+impl IoApic {
+    /// Read I/O APIC register.
+    ///
+    /// # Parameters
+    /// - `reg` - given register index.
+    ///
+    /// # Returns
+    /// - Register value.
+    fn read(&self, reg: u8) -> u32 {
+        self.regs.write::<u32>(IOREGSEL, reg as u32);
+        self.regs.read::<u32>(IOWIN)
+    }
+
+    /// Write I/O APIC register.
+    ///
+    /// # Parameters
+    /// - `reg`   - given register index.
+    /// - `value` - given value to write.
+    fn write(&self, reg: u8, value: u32) {
+        self.regs.write::<u32>(IOREGSEL, reg as u32);
+        self.regs.write::<u32>(IOWIN, value);
+    }
+}
+
+/// I/O APIC (register select & data window must be used atomically).
+static IOAPIC: Once<Mutex<IoApic>> = Once::new();
+
+/// Get redirection entry register index.
+///
+/// # Parameters
+/// - `gsi` - given global system interrupt number.
+///
+/// # Returns
+/// - Index of lower part of redirection entry.
+#[inline(always)]
+fn entry(gsi: u8) -> u8 {
+    IOREDTBL + gsi * 2
+}
+
+/// Get number of I/O APIC input pins.
+///
+/// # Returns
+/// - Number of redirection entries (zero if I/O APIC is not initialized).
+pub fn entries() -> u8 {
+    IOAPIC.get().map_or(0, |ioapic| ioapic.lock().entries)
+}
+
+/// Route global system interrupt to local APIC & unmask it. Interrupt is
+/// delivered as fixed, edge triggered & active high (ISA IRQ defaults).
+///
+/// # Parameters
+/// - `gsi`     - given global system interrupt number.
+/// - `vector`  - given interrupt vector.
+/// - `apic_id` - given local APIC ID of target CPU.
+pub fn route(gsi: u8, vector: u8, apic_id: u8) {
+    let Some(ioapic) = IOAPIC.get() else {
+        return;
+    };
+
+    let ioapic = ioapic.lock();
+
+    if gsi < ioapic.entries {
+        ioapic.write(entry(gsi) + 1, (apic_id as u32) << 24);
+        ioapic.write(entry(gsi), vector as u32);
+    }
+}
+
+/// Mask global system interrupt.
+///
+/// # Parameters
+/// - `gsi` - given global system interrupt number.
+pub fn mask(gsi: u8) {
+    let Some(ioapic) = IOAPIC.get() else {
+        return;
+    };
+
+    let ioapic = ioapic.lock();
+
+    if gsi < ioapic.entries {
+        let value = ioapic.read(entry(gsi));
+        ioapic.write(entry(gsi), value | ENTRY_MASKED);
+    }
+}
+
+/// Get redirection entry.
+///
+/// # Parameters
+/// - `gsi` - given global system interrupt number.
+///
+/// # Returns
+/// - Redirection entry - in case of success.
+/// - `None`            - otherwise.
+pub fn redirection(gsi: u8) -> Option<u64> {
+    let ioapic = IOAPIC.get()?.lock();
+
+    if gsi >= ioapic.entries {
+        return None;
+    }
+
+    let low = ioapic.read(entry(gsi)) as u64;
+    let high = ioapic.read(entry(gsi) + 1) as u64;
+
+    Some((high << 32) | low)
+}
+
+/// Map I/O APIC & mask all its inputs.
+///
+/// # Parameters
+/// - `paddr` - given physical address of I/O APIC registers.
+///
+/// # Returns
+/// - `true`  - in case of success.
+/// - `false` - otherwise.
+pub fn init(paddr: PhysAddr) -> bool {
+    let Some(regs) = mmio::ioremap(paddr, REGISTERS_SIZE, CacheMode::Uncached)
+    else {
+        return false;
+    };
+
+    let mut ioapic = IoApic { regs, entries: 0 };
+    let version = ioapic.read(IOAPICVER);
+
+    // Reads of missing device return all ones.
+    if version == u32::MAX {
+        mmio::iounmap(regs);
+        return false;
+    }
+
+    // Maximum redirection entry index is stored in bits 16-23.
+    ioapic.entries = ((version >> 16) & 0xFF) as u8 + 1;
+
+    for gsi in 0..ioapic.entries {
+        ioapic.write(entry(gsi), ENTRY_MASKED);
+    }
+
+    IOAPIC.call_once(|| Mutex::new(ioapic));
+    true
+}
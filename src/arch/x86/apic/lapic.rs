@@ -0,0 +1,273 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Date: 2025-06-13
+// Author: Alexander Kuzin <alkuzindev@gmail.com>.
+
+//! Local APIC (Advanced Programmable Interrupt Controller) driver.
+//!
+//! # Description
+//! Every CPU has its own local APIC. It accepts interrupts routed by I/O
+//! APIC & other CPUs, has its own timer and sends inter-processor
+//! interrupts (IPIs). Its registers are memory mapped at physical address
+//! stored in `IA32_APIC_BASE` MSR.
+
+use super::{ERROR_VECTOR, SPURIOUS_VECTOR, TIMER_VECTOR};
+use crate::{
+    arch::x86::cpu,
+    kernel::mm::mmio::{self, CacheMode, MmioRegion},
+};
+use spin::Once;
+
+/// `IA32_APIC_BASE` model specific register index.
+const IA32_APIC_BASE: u32 = 0x1B;
+
+/// APIC global enable bit of `IA32_APIC_BASE`.
+const APIC_BASE_ENABLE: u64 = 1 << 11;
+
+/// Physical address mask of `IA32_APIC_BASE`.
+const APIC_BASE_ADDR_MASK: u64 = 0xF_FFFF_F000;
+
+/// Size of local APIC registers area.
+const REGISTERS_SIZE: usize = 0x400;
+
+/// Local APIC ID register.
+const ID: usize = 0x20;
+
+/// Local APIC version register.
+const VERSION: usize = 0x30;
+
+/// Task priority register.
+const TPR: usize = 0x80;
+
+/// End of interrupt register.
+const EOI: usize = 0xB0;
+
+/// Spurious interrupt vector register.
+const SVR: usize = 0xF0;
+
+/// Error status register.
+const ESR: usize = 0x280;
+
+/// Interrupt command register (lower part).
+const ICR_LOW: usize = 0x300;
+
+/// Interrupt command register (higher part).
+const ICR_HIGH: usize = 0x310;
+
+/// LVT (Local Vector Table) timer register.
+const LVT_TIMER: usize = 0x320;
+
+/// LVT LINT0 pin register.
+const LVT_LINT0: usize = 0x350;
+
+/// LVT LINT1 pin register.
+const LVT_LINT1: usize = 0x360;
+
+/// LVT error register.
+const LVT_ERROR: usize = 0x370;
+
+/// Timer initial count register.
+const TIMER_INITIAL: usize = 0x380;
+
+/// Timer current count register.
+const TIMER_CURRENT: usize = 0x390;
+
+/// Timer divide configuration register.
+const TIMER_DIVIDE: usize = 0x3E0;
+
+/// APIC software enable bit of spurious interrupt vector register.
+const SVR_ENABLE: u32 = 1 << 8;
+
+/// LVT entry mask bit.
+const LVT_MASKED: u32 = 1 << 16;
+
+/// LVT NMI delivery mode.
+const LVT_NMI: u32 = 0b100 << 8;
+
+/// LVT timer periodic mode bit.
+const LVT_TIMER_PERIODIC: u32 = 1 << 17;
+
+/// Timer divide configuration: divide bus clock by 16.
+const TIMER_DIVIDE_16: u32 = 0b0011;
+
+/// ICR delivery status bit (IPI is not accepted yet).
+const ICR_PENDING: u32 = 1 << 12;
+
+/// ICR level bit (must be set for all IPIs except INIT de-assert).
+const ICR_ASSERT: u32 = 1 << 14;
+
+/// ICR destination shorthand: self.
+const ICR_SELF: u32 = 0b01 << 18;
+
+/// Mapped local APIC registers.
+static LAPIC: Once<MmioRegion> = Once::new();
+
+/// Local APIC timer mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimerMode {
+    /// Timer raises single interrupt & stops.
+    OneShot,
+    /// Timer reloads initial count after each interrupt.
+    Periodic,
+}
+
+/// Get mapped local APIC registers.
+///
+/// # Returns
+/// - Local APIC registers region.
+#[inline(always)]
+fn regs() -> &'static MmioRegion {
+    LAPIC.get().expect("Local APIC is not initialized")
+}
+
+/// Read local APIC register.
+///
+/// # Parameters
+/// - `reg` - given register offset.
+///
+/// # Returns
+/// - Register value.
+#[inline(always)]
+fn read(reg: usize) -> u32 {
+    regs().read::<u32>(reg)
+}
+
+/// Write local APIC register.
+///
+/// # Parameters
+/// - `reg`   - given register offset.
+/// - `value` - given value to write.
+#[inline(always)]
+fn write(reg: usize, value: u32) {
+    regs().write::<u32>(reg, value);
+}
+
+/// Get local APIC ID of current CPU.
+///
+/// # Returns
+/// - Local APIC ID.
+pub fn id() -> u8 {
+    (read(ID) >> 24) as u8
+}
+
+/// Get local APIC version.
+///
+/// # Returns
+/// - Local APIC version.
+pub fn version() -> u8 {
+    read(VERSION) as u8
+}
+
+/// Signal end of interrupt.
+#[inline(always)]
+pub fn eoi() {
+    write(EOI, 0);
+}
+
+/// Read & clear error status.
+///
+/// # Returns
+/// - Error status register value.
+pub fn error_status() -> u32 {
+    // Register is updated on write.
+    write(ESR, 0);
+    read(ESR)
+}
+
+/// Send IPI (inter-processor interrupt).
+///
+/// # Parameters
+/// - `apic_id` - given local APIC ID of target CPU.
+/// - `vector`  - given interrupt vector.
+pub fn send_ipi(apic_id: u8, vector: u8) {
+    write(ICR_HIGH, (apic_id as u32) << 24);
+    write(ICR_LOW, ICR_ASSERT | vector as u32);
+    wait_ipi();
+}
+
+/// Send IPI to current CPU.
+///
+/// # Parameters
+/// - `vector` - given interrupt vector.
+pub fn send_self_ipi(vector: u8) {
+    write(ICR_HIGH, 0);
+    write(ICR_LOW, ICR_SELF | ICR_ASSERT | vector as u32);
+    wait_ipi();
+}
+
+/// Wait until local APIC accepts IPI.
+fn wait_ipi() {
+    while (read(ICR_LOW) & ICR_PENDING) != 0 {
+        core::hint::spin_loop();
+    }
+}
+
+/// Start local APIC timer. Timer counts down at bus clock divided by 16.
+///
+/// # Parameters
+/// - `count` - given initial count.
+/// - `mode`  - given timer mode.
+pub fn start_timer(count: u32, mode: TimerMode) {
+    let mode = match mode {
+        TimerMode::OneShot => 0,
+        TimerMode::Periodic => LVT_TIMER_PERIODIC,
+    };
+
+    write(TIMER_DIVIDE, TIMER_DIVIDE_16);
+    write(LVT_TIMER, mode | TIMER_VECTOR as u32);
+    write(TIMER_INITIAL, count);
+}
+
+/// Stop local APIC timer.
+pub fn stop_timer() {
+    write(LVT_TIMER, LVT_MASKED | TIMER_VECTOR as u32);
+    write(TIMER_INITIAL, 0);
+}
+
+/// Get local APIC timer current count.
+///
+/// # Returns
+/// - Timer current count.
+pub fn timer_count() -> u32 {
+    read(TIMER_CURRENT)
+}
+
+/// Map & enable local APIC of current CPU.
+///
+/// # Returns
+/// - `true`  - in case of success.
+/// - `false` - otherwise.
+pub fn init() -> bool {
+    let base = cpu::rdmsr(IA32_APIC_BASE);
+    let paddr = base & APIC_BASE_ADDR_MASK;
+
+    let Some(region) =
+        mmio::ioremap(paddr, REGISTERS_SIZE, CacheMode::Uncached)
+    else {
+        return false;
+    };
+
+    LAPIC.call_once(|| region);
+
+    unsafe {
+        // Make sure APIC is not globally disabled.
+        cpu::wrmsr(IA32_APIC_BASE, base | APIC_BASE_ENABLE);
+    }
+
+    // Accept all interrupts.
+    write(TPR, 0);
+
+    // Legacy PIC is not used, LINT1 delivers NMI.
+    write(LVT_LINT0, LVT_MASKED);
+    write(LVT_LINT1, LVT_NMI);
+    write(LVT_TIMER, LVT_MASKED | TIMER_VECTOR as u32);
+    write(LVT_ERROR, ERROR_VECTOR as u32);
+
+    // Clear errors (back-to-back writes are required).
+    write(ESR, 0);
+    write(ESR, 0);
+
+    write(SVR, SVR_ENABLE | SPURIOUS_VECTOR as u32);
+    eoi();
+
+    true
+}
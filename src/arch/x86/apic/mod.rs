@@ -0,0 +1,294 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Date: 2025-06-13
+// Author: Alexander Kuzin <alkuzindev@gmail.com>.
+
+//! APIC (Advanced Programmable Interrupt Controller) main module.
+//!
+//! # Description
+//! If CPU has local APIC, legacy IRQs are routed through I/O APIC to local
+//! APIC of the boot CPU using the same vectors as remapped 8259 PIC, and
+//! the PIC is masked. Otherwise (or with `noapic` kernel parameter) 8259
+//! PIC stays in use. ACPI tables are not parsed yet, so the I/O APIC is
+//! expected at its default address and the only ISA interrupt override
+//! assumed is the timer one (IRQ 0 wired to GSI 2), which PC compatible
+//! machines (including QEMU) have.
+
+pub mod ioapic;
+pub mod lapic;
+
+use super::{cpu, pic::IRQ_BASE};
+use crate::{
+    hal::irq::InterruptController,
+    kernel::cmdline::{Param, ParamKind, kernel_param},
+    log,
+};
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+/// Local APIC timer interrupt vector.
+pub const TIMER_VECTOR: u8 = 0x30;
+
+/// Inter-processor interrupt vector.
+pub const IPI_VECTOR: u8 = 0x31;
+
+/// Local APIC error interrupt vector.
+pub const ERROR_VECTOR: u8 = 0xFE;
+
+/// Local APIC spurious interrupt vector.
+pub const SPURIOUS_VECTOR: u8 = 0xFF;
+
+/// Local APIC interrupt vectors (in order of `isr_apic_table`).
+pub const VECTORS: [u8; 4] =
+    [TIMER_VECTOR, IPI_VECTOR, ERROR_VECTOR, SPURIOUS_VECTOR];
+
+/// Whether to use 8259 PIC even if APIC is available.
+static NOAPIC: AtomicBool = AtomicBool::new(false);
+
+kernel_param! {
+    /// Kernel parameter disabling APIC (`noapic`).
+    static NOAPIC_PARAM = Param {
+        name: "noapic",
+        description: "Use 8259 PIC even if APIC is available",
+        kind: ParamKind::Flag(&NOAPIC),
+    };
+}
+
+/// Whether APIC is used as interrupt controller.
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Number of local APIC timer interrupts.
+static TIMER_TICKS: AtomicUsize = AtomicUsize::new(0);
+
+/// Number of received IPIs.
+static IPIS: AtomicUsize = AtomicUsize::new(0);
+
+/// Number of spurious local APIC interrupts.
+static SPURIOUS: AtomicUsize = AtomicUsize::new(0);
+
+/// APIC interrupt controller.
+pub struct Apic;
+
+/// Get global system interrupt of ISA IRQ.
+///
+/// # Parameters
+/// - `irq` - given IRQ number.
+///
+/// # Returns
+/// - I/O APIC input pin number.
+#[inline(always)]
+fn gsi(irq: u8) -> u8 {
+    match irq {
+        0 => 2,
+        irq => irq,
+    }
+}
+
+impl InterruptController for Apic {
+    /// Get interrupt controller name.
+    ///
+    /// # Returns
+    /// - Interrupt controller name.
+    fn name(&self) -> &'static str {
+        "APIC"
+    }
+
+    /// Mask IRQ line on I/O APIC.
+    ///
+    /// # Parameters
+    /// - `irq` - given IRQ number.
+    fn mask(&self, irq: u8) {
+        ioapic::mask(gsi(irq));
+    }
+
+    /// Route IRQ line to the boot CPU & unmask it on I/O APIC.
+    ///
+    /// # Parameters
+    /// - `irq` - given IRQ number.
+    fn unmask(&self, irq: u8) {
+        ioapic::route(gsi(irq), IRQ_BASE + irq, lapic::id());
+    }
+
+    /// Signal end of interrupt to local APIC.
+    ///
+    /// # Parameters
+    /// - `_irq` - given IRQ number.
+    fn eoi(&self, _irq: u8) {
+        lapic::eoi();
+    }
+
+    /// Check whether IRQ is spurious. Local APIC delivers spurious
+    /// interrupts on its own vector, so IRQs are never spurious.
+    ///
+    /// # Parameters
+    /// - `_irq` - given IRQ number.
+    ///
+    /// # Returns
+    /// - `false` - always.
+    fn is_spurious(&self, _irq: u8) -> bool {
+        false
+    }
+}
+
+/// Check whether APIC is used as interrupt controller.
+///
+/// # Returns
+/// - `true`  - if APIC is enabled.
+/// - `false` - otherwise.
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Acquire)
+}
+
+/// Get number of local APIC timer interrupts.
+///
+/// # Returns
+/// - Number of timer interrupts.
+pub fn timer_ticks() -> usize {
+    TIMER_TICKS.load(Ordering::Relaxed)
+}
+
+/// Get number of received IPIs.
+///
+/// # Returns
+/// - Number of IPIs.
+pub fn ipi_count() -> usize {
+    IPIS.load(Ordering::Relaxed)
+}
+
+/// Get number of spurious local APIC interrupts.
+///
+/// # Returns
+/// - Number of spurious interrupts.
+pub fn spurious_count() -> usize {
+    SPURIOUS.load(Ordering::Relaxed)
+}
+
+/// Handle local APIC interrupt. Called from `interrupt_dispatch`.
+///
+/// # Parameters
+/// - `vector` - given interrupt vector.
+pub fn handle(vector: u8) {
+    match vector {
+        TIMER_VECTOR => {
+            TIMER_TICKS.fetch_add(1, Ordering::Relaxed);
+        }
+        IPI_VECTOR => {
+            IPIS.fetch_add(1, Ordering::Relaxed);
+        }
+        ERROR_VECTOR => {
+            log::fail!("Local APIC error: {:#X}", lapic::error_status());
+        }
+        _ => {
+            // Spurious interrupt must not be acknowledged.
+            SPURIOUS.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+    }
+
+    lapic::eoi();
+}
+
+/// Switch from 8259 PIC to APIC if it is available. Must be called with
+/// interrupts disabled.
+///
+/// # Returns
+/// - `true`  - if APIC is enabled.
+/// - `false` - otherwise.
+pub fn init() -> bool {
+    if NOAPIC.load(Ordering::Relaxed) || !cpu::is_support_apic() {
+        return false;
+    }
+
+    // Local APIC blocks PIC interrupts, so it is enabled last.
+    if !ioapic::init(ioapic::DEFAULT_BASE) || !lapic::init() {
+        log::fail!("Failed to map APIC registers");
+        return false;
+    }
+
+    log::debug!(
+        "Local APIC: ID {}, version {:#X}, I/O APIC: {} inputs",
+        lapic::id(),
+        lapic::version(),
+        ioapic::entries()
+    );
+
+    super::pic::disable();
+    ENABLED.store(true, Ordering::Release);
+    true
+}
+
+#[cfg(feature = "ktest")]
+pub mod tests {
+    use super::{lapic::TimerMode, *};
+    use crate::{hal::irq, ktest::TestCase};
+
+    /// APIC unit tests.
+    pub const TESTS: &[TestCase] = &[
+        TestCase::new("self_ipi", self_ipi),
+        TestCase::new("irq_routing", irq_routing),
+        TestCase::new("timer_counts_down", timer_counts_down),
+    ];
+
+    /// Redirection entry mask bit.
+    const ENTRY_MASKED: u64 = 1 << 16;
+
+    /// Unused IRQ line (second parallel port).
+    const TEST_IRQ: u8 = 5;
+
+    fn test_handler(_irq: u8) {}
+
+    fn self_ipi() {
+        if !is_enabled() {
+            return;
+        }
+
+        let count = ipi_count();
+        lapic::send_self_ipi(IPI_VECTOR);
+
+        // Interrupts are enabled, give local APIC time to deliver IPI.
+        for _ in 0..1000 {
+            if ipi_count() != count {
+                break;
+            }
+
+            core::hint::spin_loop();
+        }
+
+        assert_eq!(ipi_count(), count + 1);
+    }
+
+    fn irq_routing() {
+        if !is_enabled() {
+            return;
+        }
+
+        assert_eq!(irq::register_irq_handler(TEST_IRQ, test_handler), Ok(()));
+
+        let entry = ioapic::redirection(gsi(TEST_IRQ)).unwrap();
+        assert_eq!(entry & 0xFF, (IRQ_BASE + TEST_IRQ) as u64);
+        assert_eq!(entry & ENTRY_MASKED, 0);
+        assert_eq!((entry >> 56) as u8, lapic::id());
+
+        assert_eq!(irq::unregister_irq_handler(TEST_IRQ), Ok(()));
+
+        let entry = ioapic::redirection(gsi(TEST_IRQ)).unwrap();
+        assert_ne!(entry & ENTRY_MASKED, 0);
+    }
+
+    fn timer_counts_down() {
+        if !is_enabled() {
+            return;
+        }
+
+        lapic::start_timer(u32::MAX, TimerMode::OneShot);
+
+        let first = lapic::timer_count();
+
+        while lapic::timer_count() == first {
+            core::hint::spin_loop();
+        }
+
+        assert!(lapic::timer_count() < first);
+
+        lapic::stop_timer();
+        assert_eq!(lapic::timer_count(), 0);
+    }
+}
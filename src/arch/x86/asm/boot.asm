@@ -31,12 +31,50 @@
     .long 768               # Screen height.
     .long 32                # Depth.
 
+# Multiboot2 header magic number.
+.set MBOOT2_MAGIC, 0xE85250D6
+
+# Protected mode i386 architecture.
+.set MBOOT2_ARCH, 0
+
+# Multiboot2 header tag types.
+.set MBOOT2_TAG_END,         0
+.set MBOOT2_TAG_FRAMEBUFFER, 5
+
+.set MBOOT2_HEADER_LENGTH, (mboot2_header_end - mboot2_header_start)
+.set MBOOT2_CHECKSUM, -(MBOOT2_MAGIC + MBOOT2_ARCH + MBOOT2_HEADER_LENGTH)
+
+.section .multiboot2
+.align 8
+mboot2_header_start:
+    .long MBOOT2_MAGIC          # Declare double word of magic number.
+    .long MBOOT2_ARCH           # Declare double word of architecture.
+    .long MBOOT2_HEADER_LENGTH  # Declare double word of header length.
+    .long MBOOT2_CHECKSUM       # Declare double word of header checksum.
+
+.align 8                        # Each tag must be 8-byte aligned.
+    .short MBOOT2_TAG_FRAMEBUFFER
+    .short 0                    # Tag flags (required tag).
+    .long 20                    # Tag size.
+    .long 1024                  # Screen width.
+    .long 768                   # Screen height.
+    .long 32                    # Depth.
+
+.align 8
+    .short MBOOT2_TAG_END
+    .short 0                    # Tag flags.
+    .long 8                     # Tag size.
+mboot2_header_end:
+
 # Higher-half kernel virtual base address (3 GB).
 .set base_address, 0xC0000000
 
 .section .bss               # Stores uninitialized global and static variables.
-.align 16                   # Reserving space for the stack.
+.align 0x1000               # Reserving space for the stack.
                             # Setup stack.
+.global stack_guard
+stack_guard:                # Guard page, unmapped to catch stack overflow.
+    .skip 0x1000
 .global stack_bottom
 .global stack_top
 stack_bottom:
@@ -72,7 +110,7 @@ higher_half:
     mov %ecx, %cr0          # Update the control register 0.
 
     mov $stack_top, %esp    # Set the stack pointer.
-    push %ebx               # Push multiboot info.
+    push %ebx               # Push multiboot (or multiboot2) info.
     push %eax               # Push magic number.
     xor %ebp, %ebp          # Reset ebp.
 
@@ -96,12 +134,11 @@ initial_page_dir:
     .set i, i + 0x00400000  # 4 MB.
 .endr
 
-# Map first 4 MB page to (0xC0000000-0xC0400000) from (0x00000000-0x00400000).
-.long 0x00000083            # 4 MB page is present & allowed to read/write.
-
-# Identity map addresses (0xC0400000-0xFFFFFFFF).
-.set i, 0xC0400083
-.rept 0xFF                  # 0xFF = (0xffc00000 - 0xc0400000)/0x00400000 + 1.
+# Map (0xC0000000-0xFFFFFFFF) to first 1 GB of physical memory.
+# Both mappings are temporary: kernel builds its own page directory
+# with 4 KB pages during memory management initialization.
+.set i, 0x00000083          # 4 MB page is present & allowed to read/write.
+.rept 0x100                 # 0x100 = 0x40000000/0x00400000.
     .long i
     .set i, i + 0x00400000  # 4 MB.
 .endr
@@ -0,0 +1,137 @@
+# SPDX-License-Identifier: GPL-3.0-or-later
+# Date: 2025-06-13
+# Author: Alexander Kuzin <alkuzindev@gmail.com>.
+
+# Interrupt service routine entry points. Each entry point makes the stack
+# look the same (error code & vector number on top of the CPU pushed frame)
+# and jumps to the common part, which saves registers & calls Rust code.
+
+# Entry point of exception that pushes error code itself.
+.macro ISR_ERROR_CODE vector
+.global isr\vector
+isr\vector:
+    push $\vector           # Push vector number.
+    jmp isr_common
+.endm
+
+# Entry point of exception (or interrupt) without error code.
+.macro ISR_NO_ERROR_CODE vector
+.global isr\vector
+isr\vector:
+    push $0                 # Push dummy error code.
+    push $\vector           # Push vector number.
+    jmp isr_common
+.endm
+
+.section .text
+
+ISR_NO_ERROR_CODE 0         # Divide error.
+ISR_NO_ERROR_CODE 1         # Debug.
+ISR_NO_ERROR_CODE 2         # Non-maskable interrupt.
+ISR_NO_ERROR_CODE 3         # Breakpoint.
+ISR_NO_ERROR_CODE 4         # Overflow.
+ISR_NO_ERROR_CODE 5         # Bound range exceeded.
+ISR_NO_ERROR_CODE 6         # Invalid opcode.
+ISR_NO_ERROR_CODE 7         # Device not available.
+ISR_ERROR_CODE 8            # Double fault (handled by task gate).
+ISR_NO_ERROR_CODE 9         # Coprocessor segment overrun.
+ISR_ERROR_CODE 10           # Invalid TSS.
+ISR_ERROR_CODE 11           # Segment not present.
+ISR_ERROR_CODE 12           # Stack-segment fault.
+ISR_ERROR_CODE 13           # General protection fault.
+ISR_ERROR_CODE 14           # Page fault.
+ISR_NO_ERROR_CODE 15        # Reserved.
+ISR_NO_ERROR_CODE 16        # x87 floating-point exception.
+ISR_ERROR_CODE 17           # Alignment check.
+ISR_NO_ERROR_CODE 18        # Machine check.
+ISR_NO_ERROR_CODE 19        # SIMD floating-point exception.
+ISR_NO_ERROR_CODE 20        # Virtualization exception.
+ISR_ERROR_CODE 21           # Control protection exception.
+ISR_NO_ERROR_CODE 22        # Reserved.
+ISR_NO_ERROR_CODE 23        # Reserved.
+ISR_NO_ERROR_CODE 24        # Reserved.
+ISR_NO_ERROR_CODE 25        # Reserved.
+ISR_NO_ERROR_CODE 26        # Reserved.
+ISR_NO_ERROR_CODE 27        # Reserved.
+ISR_NO_ERROR_CODE 28        # Hypervisor injection exception.
+ISR_ERROR_CODE 29           # VMM communication exception.
+ISR_ERROR_CODE 30           # Security exception.
+ISR_NO_ERROR_CODE 31        # Reserved.
+
+# Hardware interrupts of remapped 8259 PIC (IRQ 0-15).
+ISR_NO_ERROR_CODE 32
+ISR_NO_ERROR_CODE 33
+ISR_NO_ERROR_CODE 34
+ISR_NO_ERROR_CODE 35
+ISR_NO_ERROR_CODE 36
+ISR_NO_ERROR_CODE 37
+ISR_NO_ERROR_CODE 38
+ISR_NO_ERROR_CODE 39
+ISR_NO_ERROR_CODE 40
+ISR_NO_ERROR_CODE 41
+ISR_NO_ERROR_CODE 42
+ISR_NO_ERROR_CODE 43
+ISR_NO_ERROR_CODE 44
+ISR_NO_ERROR_CODE 45
+ISR_NO_ERROR_CODE 46
+ISR_NO_ERROR_CODE 47
+
+# Local APIC interrupts.
+ISR_NO_ERROR_CODE 48        # Local APIC timer.
+ISR_NO_ERROR_CODE 49        # Inter-processor interrupt.
+ISR_NO_ERROR_CODE 254       # Local APIC error.
+ISR_NO_ERROR_CODE 255       # Local APIC spurious interrupt.
+
+# System call gate (see `syscall.asm` for `sysenter` entry point).
+ISR_NO_ERROR_CODE 128
+
+isr_common:
+    pusha                   # Save general purpose registers.
+    push %ds                # Save segment registers.
+    push %es
+    push %fs
+    push %gs
+
+    mov $0x10, %ax          # Kernel data segment selector.
+    mov %ax, %ds
+    mov %ax, %es
+    mov %ax, %fs
+    mov %ax, %gs
+
+    push %esp               # Pass pointer to saved frame.
+    cld                     # Rust code expects direction flag clear.
+
+    .extern interrupt_dispatch
+    call interrupt_dispatch
+    add $4, %esp            # Drop frame pointer argument.
+
+    pop %gs                 # Restore segment registers.
+    pop %fs
+    pop %es
+    pop %ds
+    popa                    # Restore general purpose registers.
+
+    add $8, %esp            # Drop vector number & error code.
+    iret                    # Return from interrupt.
+
+.section .rodata
+
+# Addresses of CPU exception entry points indexed by vector number.
+.global isr_exception_table
+isr_exception_table:
+    .long isr0,  isr1,  isr2,  isr3,  isr4,  isr5,  isr6,  isr7
+    .long isr8,  isr9,  isr10, isr11, isr12, isr13, isr14, isr15
+    .long isr16, isr17, isr18, isr19, isr20, isr21, isr22, isr23
+    .long isr24, isr25, isr26, isr27, isr28, isr29, isr30, isr31
+
+# Addresses of hardware interrupt entry points indexed by IRQ number.
+.global isr_irq_table
+isr_irq_table:
+    .long isr32, isr33, isr34, isr35, isr36, isr37, isr38, isr39
+    .long isr40, isr41, isr42, isr43, isr44, isr45, isr46, isr47
+
+# Addresses of local APIC interrupt entry points (timer, IPI, error,
+# spurious).
+.global isr_apic_table
+isr_apic_table:
+    .long isr48, isr49, isr254, isr255
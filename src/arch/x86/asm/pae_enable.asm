@@ -0,0 +1,60 @@
+# SPDX-License-Identifier: GPL-3.0-or-later
+# Date: 2025-06-13
+# Author: Alexander Kuzin <alkuzindev@gmail.com>.
+
+# Higher-half kernel virtual base address (3 GB).
+.set base_address, 0xC0000000
+
+.set CR0_PG,   0x80000000   # Paging enable bit.
+.set CR4_PAE,  0x00000020   # Physical address extension bit.
+.set EFER,     0xC0000080   # Extended feature enable register MSR.
+.set EFER_NXE, 0x00000800   # No-execute enable bit.
+
+.section .text
+
+# Switch from legacy to PAE paging. Paging format can only be changed with
+# paging disabled, so the switch is done at the physical address of this
+# code, which must be identity mapped by both old & new paging structures.
+.global pae_enable
+pae_enable:
+    push %ebx
+    push %esi
+    mov 12(%esp), %esi      # Page directory pointer table address.
+    mov 16(%esp), %ebx      # Whether to enable no-execute pages.
+    pushf                   # Save interrupt flag.
+    cli
+
+    test %ebx, %ebx
+    jz pae_enable_low
+    mov $EFER, %ecx
+    rdmsr                   # Read EFER into EDX:EAX.
+    or $EFER_NXE, %eax
+    wrmsr                   # Enable no-execute bit in page entries.
+
+pae_enable_low:
+    mov $(pae_enable_switch - base_address), %ecx
+    jmp *%ecx               # Continue at physical address.
+
+pae_enable_switch:
+    mov %cr0, %ecx
+    and $~CR0_PG, %ecx
+    mov %ecx, %cr0          # Disable paging.
+
+    mov %cr4, %ecx
+    or $CR4_PAE, %ecx
+    mov %ecx, %cr4          # Enable PAE.
+
+    mov %esi, %cr3          # Load page directory pointer table.
+
+    mov %cr0, %ecx
+    or $CR0_PG, %ecx
+    mov %ecx, %cr0          # Enable paging.
+
+    mov $pae_enable_high, %ecx
+    jmp *%ecx               # Return to higher half.
+
+pae_enable_high:
+    popf                    # Restore interrupt flag.
+    pop %esi
+    pop %ebx
+    ret                     # Return back to Rust code.
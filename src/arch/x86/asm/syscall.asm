@@ -0,0 +1,64 @@
+# SPDX-License-Identifier: GPL-3.0-or-later
+# Date: 2025-06-13
+# Author: Alexander Kuzin <alkuzindev@gmail.com>.
+
+# Fast system call entry point. `sysenter` switches to kernel stack set in
+# SYSENTER_ESP MSR & disables interrupts, but saves neither user stack nor
+# return address: user code passes them in ECX & EDX. Entry point builds the
+# same frame as `int 0x80` does, so system call is dispatched the same way,
+# and returns to user mode by `sysexit`. `sysenter` clears only IF, VM & RF
+# in EFLAGS, so user flags are saved as is with IF set back.
+
+.set USER_CODE, 0x5B        # Sysexit code segment selector (RPL 3).
+.set USER_STACK, 0x63       # Sysexit stack segment selector (RPL 3).
+.set SYSCALL_VECTOR, 0x80   # System call vector number.
+.set USER_IF, 0x200         # Interrupt flag of user mode EFLAGS.
+
+.section .text
+
+.global sysenter_entry
+sysenter_entry:
+    push $USER_STACK        # Build interrupt return frame.
+    push %ecx               # User stack pointer.
+    pushf                   # User flags, untouched by code above.
+    orl $USER_IF, (%esp)    # Interrupts are enabled in user mode.
+    push $USER_CODE
+    push %edx               # User return address.
+    push $0                 # Push dummy error code.
+    push $SYSCALL_VECTOR    # Push vector number.
+
+    pusha                   # Save general purpose registers.
+    push %ds                # Save segment registers.
+    push %es
+    push %fs
+    push %gs
+
+    mov $0x10, %ax          # Kernel data segment selector.
+    mov %ax, %ds
+    mov %ax, %es
+    mov %ax, %fs
+    mov %ax, %gs
+
+    push %esp               # Pass pointer to saved frame.
+    cld                     # Rust code expects direction flag clear.
+    sti                     # System call may be interrupted.
+
+    .extern interrupt_dispatch
+    call interrupt_dispatch
+    cli                     # Frame is restored with interrupts disabled.
+    add $4, %esp            # Drop frame pointer argument.
+
+    pop %gs                 # Restore segment registers.
+    pop %fs
+    pop %es
+    pop %ds
+    popa                    # Restore general purpose registers.
+
+    add $8, %esp            # Drop vector number & error code.
+    pop %edx                # Return address for sysexit.
+    add $4, %esp            # Drop code segment selector.
+    popf                    # Restore user flags (enables interrupts).
+    pop %ecx                # User stack pointer for sysexit.
+    add $4, %esp            # Drop stack segment selector.
+
+    sysexit                 # Return to user mode.
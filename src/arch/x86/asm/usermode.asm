@@ -0,0 +1,69 @@
+# SPDX-License-Identifier: GPL-3.0-or-later
+# Date: 2025-06-13
+# Author: Alexander Kuzin <alkuzindev@gmail.com>.
+
+# Run user code until it exits. `user_mode_enter` saves kernel context on
+# the current stack & returns to user mode by `iret`. User code traps into
+# the kernel on a separate stack (set in TSS), so saved context stays intact
+# until `user_mode_exit` switches back to it & returns exit status.
+
+.set USER_CODE, 0x23        # User code segment selector (RPL 3).
+.set USER_DATA, 0x2B        # User data segment selector (RPL 3).
+.set USER_STACK, 0x33       # User stack segment selector (RPL 3).
+.set USER_EFLAGS, 0x202     # Interrupts enabled, IOPL 0.
+.set KERNEL_DATA, 0x10      # Kernel data segment selector.
+
+.section .data
+
+.align 4
+.global user_mode_context
+user_mode_context:
+    .long 0                 # Saved kernel stack pointer (0 if not in use).
+
+.section .text
+
+# u32 user_mode_enter(u32 entry, u32 stack)
+.global user_mode_enter
+user_mode_enter:
+    mov 4(%esp), %eax       # Get user entry point.
+    mov 8(%esp), %ecx       # Get user stack pointer.
+
+    push %ebp               # Save callee-saved registers.
+    push %ebx
+    push %esi
+    push %edi
+    pushf                   # Save kernel flags.
+    mov %esp, user_mode_context
+
+    mov $USER_DATA, %dx     # Load user data segments.
+    mov %dx, %ds
+    mov %dx, %es
+    mov %dx, %fs
+    mov %dx, %gs
+
+    push $USER_STACK        # Build interrupt return frame.
+    push %ecx               # User stack pointer.
+    push $USER_EFLAGS
+    push $USER_CODE
+    push %eax               # User entry point.
+    iret                    # Jump to user mode.
+
+# void user_mode_exit(u32 status)
+.global user_mode_exit
+user_mode_exit:
+    mov 4(%esp), %eax       # Get exit status.
+    mov user_mode_context, %esp
+    movl $0, user_mode_context
+
+    mov $KERNEL_DATA, %dx   # Load kernel data segments.
+    mov %dx, %ds
+    mov %dx, %es
+    mov %dx, %fs
+    mov %dx, %gs
+
+    popf                    # Restore kernel flags.
+    pop %edi                # Restore callee-saved registers.
+    pop %esi
+    pop %ebx
+    pop %ebp
+    ret                     # Return exit status from user_mode_enter.
@@ -43,6 +43,115 @@ pub fn cpuid(leaf: u32) -> CpuidResult {
     result
 }
 
+/// Read model specific register.
+///
+/// # Parameters
+/// - `msr` - given model specific register index.
+///
+/// # Returns
+/// - Model specific register value.
+pub fn rdmsr(msr: u32) -> u64 {
+    let (low, high): (u32, u32);
+
+    unsafe {
+        asm!(
+            "rdmsr",
+            in("ecx") msr,
+            out("eax") low,
+            out("edx") high,
+            options(nomem, nostack),
+        );
+    }
+
+    ((high as u64) << 32) | low as u64
+}
+
+/// Write model specific register.
+///
+/// # Parameters
+/// - `msr`   - given model specific register index.
+/// - `value` - given value to write.
+///
+/// # Safety
+/// - Register must exist & value must not break CPU state expected by the
+///   kernel.
+pub unsafe fn wrmsr(msr: u32, value: u64) {
+    unsafe {
+        asm!(
+            "wrmsr",
+            in("ecx") msr,
+            in("eax") value as u32,
+            in("edx") (value >> 32) as u32,
+            options(nostack),
+        );
+    }
+}
+
+/// Read time-stamp counter.
+///
+/// # Returns
+/// - Number of CPU cycles since reset.
+#[inline(always)]
+pub fn rdtsc() -> u64 {
+    let (low, high): (u32, u32);
+
+    unsafe {
+        asm!("rdtsc", out("eax") low, out("edx") high, options(nomem, nostack));
+    }
+
+    ((high as u64) << 32) | low as u64
+}
+
+/// Enable hardware interrupts.
+#[inline(always)]
+pub fn enable_interrupts() {
+    unsafe {
+        asm!("sti", options(nomem, nostack));
+    }
+}
+
+/// Disable hardware interrupts.
+#[inline(always)]
+pub fn disable_interrupts() {
+    unsafe {
+        asm!("cli", options(nomem, nostack));
+    }
+}
+
+/// Enable hardware interrupts & halt CPU until the next interrupt. Single
+/// instruction after `sti` is executed before interrupts are recognized,
+/// so no interrupt can arrive between enabling & halting.
+#[inline(always)]
+pub fn enable_interrupts_and_halt() {
+    unsafe {
+        asm!("sti", "hlt", options(nomem, nostack));
+    }
+}
+
+/// Halt CPU until the next interrupt.
+#[inline(always)]
+pub fn halt() {
+    unsafe {
+        asm!("hlt", options(nomem, nostack));
+    }
+}
+
+/// Check whether hardware interrupts are enabled.
+///
+/// # Returns
+/// - `true`  - if interrupt flag is set.
+/// - `false` - otherwise.
+#[inline(always)]
+pub fn are_interrupts_enabled() -> bool {
+    let eflags: u32;
+
+    unsafe {
+        asm!("pushfd", "pop {}", out(reg) eflags, options(nomem));
+    }
+
+    (eflags & (1 << 9)) != 0
+}
+
 /// Set basic CPU info.
 ///
 /// # Parameters
@@ -141,6 +250,122 @@ pub fn is_hypervisor_present() -> bool {
     (cpu_info.ecx & (1 << 31)) != 0x0
 }
 
+/// Check whether CPU supports PAE (Physical Address Extension) paging.
+///
+/// # Returns
+/// - `true`  - if CPU supports PAE.
+/// - `false` - otherwise.
+pub fn is_support_pae() -> bool {
+    // Get specific CPU info.
+    let cpu_info = cpuid(1);
+    (cpu_info.edx & (1 << 6)) != 0x0
+}
+
+/// Check whether CPU supports PAT (Page Attribute Table).
+///
+/// # Returns
+/// - `true`  - if CPU supports PAT.
+/// - `false` - otherwise.
+pub fn is_support_pat() -> bool {
+    // Get specific CPU info.
+    let cpu_info = cpuid(1);
+    (cpu_info.edx & (1 << 16)) != 0x0
+}
+
+/// Check whether CPU has time-stamp counter.
+///
+/// # Returns
+/// - `true`  - if CPU supports `rdtsc` instruction.
+/// - `false` - otherwise.
+pub fn is_support_tsc() -> bool {
+    // Get specific CPU info.
+    let cpu_info = cpuid(1);
+    (cpu_info.edx & (1 << 4)) != 0x0
+}
+
+/// Check whether CPU has on-chip local APIC.
+///
+/// # Returns
+/// - `true`  - if CPU has local APIC.
+/// - `false` - otherwise.
+pub fn is_support_apic() -> bool {
+    // Get specific CPU info.
+    let cpu_info = cpuid(1);
+    (cpu_info.edx & (1 << 9)) != 0x0
+}
+
+/// Check whether CPU supports `sysenter` & `sysexit` instructions.
+///
+/// # Returns
+/// - `true`  - if CPU supports fast system calls.
+/// - `false` - otherwise.
+pub fn is_support_sep() -> bool {
+    // Get specific CPU info.
+    let cpu_info = cpuid(1);
+    let family = (cpu_info.eax >> 8) & 0xF;
+    let model = (cpu_info.eax >> 4) & 0xF;
+    let stepping = cpu_info.eax & 0xF;
+
+    // Early Pentium Pro reports SEP, but does not support it.
+    let is_pentium_pro = family == 6 && model < 3 && stepping < 3;
+
+    (cpu_info.edx & (1 << 11)) != 0x0 && !is_pentium_pro
+}
+
+/// Get maximum extended CPUID leaf.
+///
+/// # Returns
+/// - Maximum supported extended CPUID leaf.
+fn max_extended_leaf() -> u32 {
+    cpuid(0x80000000).eax
+}
+
+/// Check whether CPU supports non-executable (NX) pages.
+///
+/// # Returns
+/// - `true`  - if CPU supports NX bit.
+/// - `false` - otherwise.
+pub fn is_support_nx() -> bool {
+    if max_extended_leaf() < 0x80000001 {
+        return false;
+    }
+
+    // Get specific CPU info.
+    let cpu_info = cpuid(0x80000001);
+    (cpu_info.edx & (1 << 20)) != 0x0
+}
+
+/// Check whether time-stamp counter runs at constant rate in all power
+/// states (invariant TSC).
+///
+/// # Returns
+/// - `true`  - if TSC is invariant.
+/// - `false` - otherwise.
+pub fn is_tsc_invariant() -> bool {
+    if max_extended_leaf() < 0x80000007 {
+        return false;
+    }
+
+    // Get specific CPU info.
+    let cpu_info = cpuid(0x80000007);
+    (cpu_info.edx & (1 << 8)) != 0x0
+}
+
+/// Get number of physical address bits supported by CPU.
+///
+/// # Returns
+/// - Physical address width in bits.
+pub fn phys_addr_bits() -> u32 {
+    if max_extended_leaf() < 0x80000008 {
+        // Default width of PAE capable CPUs.
+        return if is_support_pae() { 36 } else { 32 };
+    }
+
+    // Get specific CPU info.
+    let cpu_info = cpuid(0x80000008);
+    cpu_info.eax & 0xFF
+}
+
 /// Get hypervisor identifier.
 ///
 /// # Returns
@@ -0,0 +1,141 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Date: 2025-06-13
+// Author: Alexander Kuzin <alkuzindev@gmail.com>.
+
+//! Contains HPET (High Precision Event Timer) driver.
+//!
+//! # Description
+//! HPET main counter is used as clocksource only, its comparators are left
+//! disabled. ACPI tables are not parsed yet, so HPET is probed at its usual
+//! address (used by QEMU as well). Only 64-bit main counters are used,
+//! so that counter never wraps.
+
+use crate::{
+    hal::clocksource::Clocksource,
+    kernel::mm::{
+        PhysAddr,
+        mmio::{self, CacheMode, MmioRegion},
+    },
+};
+use spin::Once;
+
+/// Default physical address of HPET registers.
+const DEFAULT_BASE: PhysAddr = 0xFED00000;
+
+/// Size of HPET registers area.
+const REGISTERS_SIZE: usize = 0x400;
+
+/// General capabilities & ID register (lower part).
+const CAPABILITIES: usize = 0x000;
+
+/// Main counter tick period in femtoseconds (higher part of capabilities).
+const PERIOD: usize = 0x004;
+
+/// General configuration register.
+const CONFIG: usize = 0x010;
+
+/// Main counter value register (lower part).
+const COUNTER_LOW: usize = 0x0F0;
+
+/// Main counter value register (higher part).
+const COUNTER_HIGH: usize = 0x0F4;
+
+/// 64-bit main counter bit of capabilities register.
+const COUNT_SIZE_64: u32 = 1 << 13;
+
+/// Main counter enable bit of configuration register.
+const ENABLE: u32 = 1 << 0;
+
+/// Legacy replacement route bit of configuration register.
+const LEGACY_ROUTE: u32 = 1 << 1;
+
+/// Maximal valid main counter tick period (100 ns) in femtoseconds.
+const MAX_PERIOD: u32 = 100_000_000;
+
+/// Number of femtoseconds in second.
+const FEMTOS_PER_SEC: u64 = 1_000_000_000_000_000;
+
+/// Mapped HPET registers.
+static HPET: Once<MmioRegion> = Once::new();
+
+/// HPET clocksource.
+pub struct Hpet;
+
+impl Clocksource for Hpet {
+    /// Get clocksource name.
+    ///
+    /// # Returns
+    /// - Clocksource name.
+    fn name(&self) -> &'static str {
+        "HPET"
+    }
+
+    /// Get clocksource rating. HPET is stable, but slower to read than TSC.
+    ///
+    /// # Returns
+    /// - Clocksource rating (zero if HPET is not present).
+    fn rating(&self) -> u32 {
+        if HPET.is_completed() { 250 } else { 0 }
+    }
+
+    /// Get counter frequency.
+    ///
+    /// # Returns
+    /// - Main counter frequency (zero if HPET is not present).
+    fn frequency(&self) -> u64 {
+        HPET.get()
+            .map_or(0, |regs| FEMTOS_PER_SEC / regs.read::<u32>(PERIOD) as u64)
+    }
+
+    /// Read counter.
+    ///
+    /// # Returns
+    /// - Main counter value.
+    fn read(&self) -> u64 {
+        let Some(regs) = HPET.get() else {
+            return 0;
+        };
+
+        // Counter is read in two halves, so retry if lower one wrapped.
+        loop {
+            let high = regs.read::<u32>(COUNTER_HIGH);
+            let low = regs.read::<u32>(COUNTER_LOW);
+
+            if regs.read::<u32>(COUNTER_HIGH) == high {
+                return ((high as u64) << 32) | low as u64;
+            }
+        }
+    }
+}
+
+/// Probe HPET & enable its main counter.
+///
+/// # Returns
+/// - `true`  - if HPET is present.
+/// - `false` - otherwise.
+pub fn init() -> bool {
+    let Some(regs) =
+        mmio::ioremap(DEFAULT_BASE, REGISTERS_SIZE, CacheMode::Uncached)
+    else {
+        return false;
+    };
+
+    let capabilities = regs.read::<u32>(CAPABILITIES);
+    let period = regs.read::<u32>(PERIOD);
+
+    // Missing device reads as all ones, revision ID must not be zero.
+    let is_present = capabilities != u32::MAX
+        && (capabilities & 0xFF) != 0
+        && (1..=MAX_PERIOD).contains(&period);
+
+    if !is_present || (capabilities & COUNT_SIZE_64) == 0 {
+        mmio::iounmap(regs);
+        return false;
+    }
+
+    let config = regs.read::<u32>(CONFIG) & !LEGACY_ROUTE;
+    regs.write::<u32>(CONFIG, config | ENABLE);
+
+    HPET.call_once(|| regs);
+    true
+}
@@ -4,7 +4,14 @@
 
 //! Contains PS/2 keyboard driver.
 
-use crate::{hal::keyboard::Key, arch::x86::io::inb, print};
+use crate::{
+    arch::x86::io::inb,
+    hal::{
+        irq::{self, IrqQueue},
+        keyboard::{self, Key, Keymap},
+    },
+    print,
+};
 
 impl From<u8> for Key {
     /// Convert byte to keyboard key.
@@ -72,6 +79,35 @@ const UPPERCASE_KEYS: [char; 128] = [
     UNKNOWN, UNKNOWN, UNKNOWN, UNKNOWN, UNKNOWN, UNKNOWN, UNKNOWN, UNKNOWN,
 ];
 
+/// Array of UK layout lowercase characters to print.
+const UK_LOWERCASE_KEYS: [char; 128] = {
+    let mut keys = LOWERCASE_KEYS;
+    keys[0x2B] = '#';
+    keys[0x56] = '\\';
+    keys
+};
+
+/// Array of UK layout uppercase characters to print.
+const UK_UPPERCASE_KEYS: [char; 128] = {
+    let mut keys = UPPERCASE_KEYS;
+    keys[0x03] = '"';
+    keys[0x04] = '£';
+    keys[0x28] = '@';
+    keys[0x29] = '¬';
+    keys[0x2B] = '~';
+    keys[0x56] = '|';
+    keys
+};
+
+/// PS/2 controller data port.
+const DATA_PORT: u16 = 0x60;
+
+/// PS/2 keyboard IRQ line.
+const KEYBOARD_IRQ: u8 = 1;
+
+/// Scan codes received by keyboard IRQ handler.
+static SCAN_CODES: IrqQueue<64> = IrqQueue::new();
+
 /// Current keyboard key scan code.
 static mut SCAN_CODE: u8 = 0;
 
@@ -86,14 +122,12 @@ static mut IS_SHIFT: bool = false;
 /// # Returns
 /// - Pressed keyboard key info.
 pub fn read_key() -> Key {
-    unsafe {
-        // Wait until key is pressed.
-        while (inb(0x64) & 0x01) == 0 {
-            continue;
-        }
+    // Wait until key is pressed or released.
+    let raw = SCAN_CODES.pop_wait();
 
-        SCAN_CODE = inb(0x60) & 0x7F;
-        IS_PRESSED = (inb(0x60) & 0x80) != 0;
+    unsafe {
+        SCAN_CODE = raw & 0x7F;
+        IS_PRESSED = (raw & 0x80) != 0;
     }
 
     let scan_code = unsafe { SCAN_CODE };
@@ -112,12 +146,18 @@ pub fn read_key() -> Key {
             if !is_pressed {
                 let pos = scan_code as usize;
 
+                // Select character arrays of current keyboard layout.
+                let (lowercase, uppercase) = match keyboard::keymap() {
+                    Keymap::Us => (&LOWERCASE_KEYS, &UPPERCASE_KEYS),
+                    Keymap::Uk => (&UK_LOWERCASE_KEYS, &UK_UPPERCASE_KEYS),
+                };
+
                 // Check whether to return uppercase or lowercase character.
                 let ch = if is_shift_pressed {
-                    UPPERCASE_KEYS[pos]
+                    uppercase[pos]
                 }
                 else {
-                    LOWERCASE_KEYS[pos]
+                    lowercase[pos]
                 };
 
                 return Key::Char(ch);
@@ -195,8 +235,20 @@ pub fn get_line(buffer: &mut [char]) {
     }
 }
 
+/// Keyboard IRQ handler. Stores received scan code.
+///
+/// # Parameters
+/// - `_irq` - given IRQ number.
+fn keyboard_irq(_irq: u8) {
+    let scan_code = unsafe { inb(DATA_PORT) };
+
+    // Keys pressed while queue is full are dropped.
+    SCAN_CODES.push(scan_code);
+}
+
 /// Initialize keyboard driver.
 pub fn init() {
-    // Do nothing.
-    // TODO: use interrupts for keyboard driver.
+    if let Err(err) = irq::register_irq_handler(KEYBOARD_IRQ, keyboard_irq) {
+        panic!("Failed to register keyboard IRQ handler: {}", err);
+    }
 }
@@ -4,5 +4,8 @@
 
 //! x86 architecture-specific drivers main module.
 
+pub mod hpet;
 pub mod keyboard;
+pub mod pit;
+pub mod rtc;
 pub mod uart;
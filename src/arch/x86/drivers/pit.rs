@@ -0,0 +1,210 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Date: 2025-06-13
+// Author: Alexander Kuzin <alkuzindev@gmail.com>.
+
+//! Contains 8253/8254 PIT (Programmable Interval Timer) driver.
+//!
+//! # Description
+//! Channel 0 runs as rate generator & raises IRQ 0 on every tick. Time
+//! since boot is counted in PIT input clock cycles: full ticks plus cycles
+//! elapsed in the current tick, read from channel 0 counter, so that clock
+//! resolution is not limited by the tick rate.
+
+use crate::{
+    arch::x86::io::{inb, outb},
+    hal::{
+        clocksource::{self, Clocksource},
+        irq,
+    },
+};
+use spin::Mutex;
+
+/// Channel 0 data port.
+const CHANNEL0_DATA: u16 = 0x40;
+
+/// Mode/command register port.
+const COMMAND: u16 = 0x43;
+
+/// Command: channel 0, low byte then high byte, mode 2 (rate generator).
+const CHANNEL0_RATE_GENERATOR: u8 = 0x34;
+
+/// Command: latch channel 0 counter value.
+const CHANNEL0_LATCH: u8 = 0x00;
+
+/// PIT IRQ line.
+const PIT_IRQ: u8 = 0;
+
+/// PIT input clock frequency in Hz.
+pub const PIT_FREQUENCY: u32 = 1193182;
+
+/// Minimal tick rate in Hz (16-bit reload value limit).
+pub const MIN_FREQUENCY: u32 = PIT_FREQUENCY / 0xFFFF + 1;
+
+/// Maximal tick rate in Hz.
+pub const MAX_FREQUENCY: u32 = 10000;
+
+/// System clock state.
+struct Clock {
+    /// Channel 0 reload value (zero if timer is not initialized).
+    divisor: u32,
+    /// Number of ticks since boot.
+    ticks: u64,
+    /// PIT cycles elapsed before the first tick of current tick rate.
+    base_cycles: u64,
+    /// The latest returned number of PIT cycles since boot.
+    last_cycles: u64,
+}
+
+/// System clock. Locked with interrupts disabled only.
+static CLOCK: Mutex<Clock> = Mutex::new(Clock {
+    divisor: 0,
+    ticks: 0,
+    base_cycles: 0,
+    last_cycles: 0,
+});
+
+/// PIT clocksource.
+pub struct Pit;
+
+impl Clocksource for Pit {
+    /// Get clocksource name.
+    ///
+    /// # Returns
+    /// - Clocksource name.
+    fn name(&self) -> &'static str {
+        "PIT"
+    }
+
+    /// Get clocksource rating. PIT is always available, but it is slow to
+    /// read & has low resolution.
+    ///
+    /// # Returns
+    /// - Clocksource rating.
+    fn rating(&self) -> u32 {
+        100
+    }
+
+    /// Get counter frequency.
+    ///
+    /// # Returns
+    /// - PIT input clock frequency.
+    fn frequency(&self) -> u64 {
+        PIT_FREQUENCY as u64
+    }
+
+    /// Read counter.
+    ///
+    /// # Returns
+    /// - Number of PIT cycles since boot.
+    fn read(&self) -> u64 {
+        cycles()
+    }
+}
+
+/// Read channel 0 current counter.
+///
+/// # Returns
+/// - Channel 0 counter value.
+fn read_counter() -> u16 {
+    unsafe {
+        outb(COMMAND, CHANNEL0_LATCH);
+        let low = inb(CHANNEL0_DATA) as u16;
+        let high = inb(CHANNEL0_DATA) as u16;
+
+        (high << 8) | low
+    }
+}
+
+/// PIT IRQ handler. Counts ticks.
+///
+/// # Parameters
+/// - `_irq` - given IRQ number.
+fn pit_irq(_irq: u8) {
+    CLOCK.lock().ticks += 1;
+}
+
+/// Set tick rate.
+///
+/// # Parameters
+/// - `hz` - given tick rate in Hz (clamped to supported range).
+pub fn set_frequency(hz: u32) {
+    let hz = hz.clamp(MIN_FREQUENCY, MAX_FREQUENCY);
+    let divisor = PIT_FREQUENCY / hz;
+
+    irq::without_interrupts(|| {
+        let mut clock = CLOCK.lock();
+
+        // Keep time elapsed with previous tick rate.
+        clock.base_cycles += clock.ticks * clock.divisor as u64;
+        clock.ticks = 0;
+        clock.divisor = divisor;
+
+        unsafe {
+            outb(COMMAND, CHANNEL0_RATE_GENERATOR);
+            outb(CHANNEL0_DATA, divisor as u8);
+            outb(CHANNEL0_DATA, (divisor >> 8) as u8);
+        }
+    });
+}
+
+/// Get tick rate.
+///
+/// # Returns
+/// - Tick rate in Hz (zero if timer is not initialized).
+pub fn frequency() -> u32 {
+    let divisor = irq::without_interrupts(|| CLOCK.lock().divisor);
+    PIT_FREQUENCY.checked_div(divisor).unwrap_or(0)
+}
+
+/// Get number of ticks since tick rate was set.
+///
+/// # Returns
+/// - Number of timer interrupts.
+pub fn ticks() -> u64 {
+    irq::without_interrupts(|| CLOCK.lock().ticks)
+}
+
+/// Get number of PIT cycles since boot.
+///
+/// # Returns
+/// - Number of PIT cycles since timer initialization.
+pub fn cycles() -> u64 {
+    irq::without_interrupts(|| {
+        let mut clock = CLOCK.lock();
+
+        if clock.divisor == 0 {
+            return 0;
+        }
+
+        // Counter counts down from divisor to 1.
+        let counter = (read_counter() as u32).min(clock.divisor);
+        let cycles = clock.base_cycles
+            + clock.ticks * clock.divisor as u64
+            + (clock.divisor - counter) as u64;
+
+        // Counter reload may be not counted yet if IRQ is pending.
+        let cycles = cycles.max(clock.last_cycles);
+        clock.last_cycles = cycles;
+        cycles
+    })
+}
+
+/// Get time since boot.
+///
+/// # Returns
+/// - Number of nanoseconds since timer initialization.
+pub fn elapsed_ns() -> u64 {
+    clocksource::cycles_to_ns(cycles(), PIT_FREQUENCY as u64)
+}
+
+/// Initialize PIT driver.
+///
+/// # Parameters
+/// - `hz` - given tick rate in Hz.
+pub fn init(hz: u32) {
+    set_frequency(hz);
+
+    if let Err(err) = irq::register_irq_handler(PIT_IRQ, pit_irq) {
+        panic!("Failed to register PIT IRQ handler: {}", err);
+    }
+}
@@ -0,0 +1,225 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Date: 2025-06-13
+// Author: Alexander Kuzin <alkuzindev@gmail.com>.
+
+//! Contains CMOS RTC (Real-Time Clock) driver.
+//!
+//! # Description
+//! RTC keeps calendar time in CMOS registers, either in BCD or binary
+//! format and with either 12 or 24-hour clock (see status register B).
+//! Registers are updated once per second, so they are read only when no
+//! update is in progress & read again until two readings match.
+
+use crate::{
+    arch::x86::io::{inb, outb},
+    hal::irq,
+    kernel::time::DateTime,
+};
+
+/// CMOS register select port.
+const CMOS_ADDRESS: u16 = 0x70;
+
+/// CMOS data port.
+const CMOS_DATA: u16 = 0x71;
+
+/// Disable NMI bit of register select value.
+const NMI_DISABLE: u8 = 0x80;
+
+/// Status register D (read-only, selected when access is finished).
+const STATUS_D: u8 = 0x0D;
+
+/// Seconds register.
+const SECONDS: u8 = 0x00;
+
+/// Minutes register.
+const MINUTES: u8 = 0x02;
+
+/// Hours register.
+const HOURS: u8 = 0x04;
+
+/// Day of month register.
+const DAY: u8 = 0x07;
+
+/// Month register.
+const MONTH: u8 = 0x08;
+
+/// Year (last two digits) register.
+const YEAR: u8 = 0x09;
+
+/// Century register (the usual location, ACPI tables are not parsed yet).
+const CENTURY: u8 = 0x32;
+
+/// Status register A.
+const STATUS_A: u8 = 0x0A;
+
+/// Status register B.
+const STATUS_B: u8 = 0x0B;
+
+/// Update in progress bit of status register A.
+const UPDATE_IN_PROGRESS: u8 = 1 << 7;
+
+/// 24-hour format bit of status register B.
+const HOUR_24: u8 = 1 << 1;
+
+/// Binary format bit of status register B.
+const BINARY: u8 = 1 << 2;
+
+/// PM bit of hours register in 12-hour format.
+const HOUR_PM: u8 = 1 << 7;
+
+/// Raw RTC registers values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Registers {
+    /// Seconds register.
+    second: u8,
+    /// Minutes register.
+    minute: u8,
+    /// Hours register.
+    hour: u8,
+    /// Day of month register.
+    day: u8,
+    /// Month register.
+    month: u8,
+    /// Year register.
+    year: u8,
+    /// Century register.
+    century: u8,
+}
+
+/// Read CMOS register.
+///
+/// # Parameters
+/// - `reg` - given register index.
+///
+/// # Returns
+/// - Register value.
+fn read_register(reg: u8) -> u8 {
+    unsafe {
+        outb(CMOS_ADDRESS, NMI_DISABLE | reg);
+        inb(CMOS_DATA)
+    }
+}
+
+/// Select harmless register with NMI enabled again. NMI stays disabled
+/// while register index has disable bit set.
+fn enable_nmi() {
+    unsafe {
+        outb(CMOS_ADDRESS, STATUS_D);
+        inb(CMOS_DATA);
+    }
+}
+
+/// Check whether RTC is updating its registers.
+///
+/// # Returns
+/// - `true`  - if update is in progress.
+/// - `false` - otherwise.
+fn is_updating() -> bool {
+    (read_register(STATUS_A) & UPDATE_IN_PROGRESS) != 0
+}
+
+/// Read time registers when no update is in progress.
+///
+/// # Returns
+/// - Raw registers values.
+fn read_registers() -> Registers {
+    while is_updating() {
+        core::hint::spin_loop();
+    }
+
+    Registers {
+        second: read_register(SECONDS),
+        minute: read_register(MINUTES),
+        hour: read_register(HOURS),
+        day: read_register(DAY),
+        month: read_register(MONTH),
+        year: read_register(YEAR),
+        century: read_register(CENTURY),
+    }
+}
+
+/// Convert BCD value to binary.
+///
+/// # Parameters
+/// - `value` - given BCD value.
+///
+/// # Returns
+/// - Binary value.
+#[inline(always)]
+fn bcd_to_binary(value: u8) -> u8 {
+    (value >> 4) * 10 + (value & 0x0F)
+}
+
+/// Convert raw registers values to date & time.
+///
+/// # Parameters
+/// - `regs`   - given raw registers values.
+/// - `status` - given status register B value.
+///
+/// # Returns
+/// - Date & time stored in registers.
+fn decode(regs: Registers, status: u8) -> DateTime {
+    let pm = (regs.hour & HOUR_PM) != 0;
+    let mut regs = Registers {
+        hour: regs.hour & !HOUR_PM,
+        ..regs
+    };
+
+    if (status & BINARY) == 0 {
+        regs = Registers {
+            second: bcd_to_binary(regs.second),
+            minute: bcd_to_binary(regs.minute),
+            hour: bcd_to_binary(regs.hour),
+            day: bcd_to_binary(regs.day),
+            month: bcd_to_binary(regs.month),
+            year: bcd_to_binary(regs.year),
+            century: bcd_to_binary(regs.century),
+        };
+    }
+
+    // In 12-hour format midnight & noon are stored as 12.
+    if (status & HOUR_24) == 0 {
+        regs.hour = (regs.hour % 12) + if pm { 12 } else { 0 };
+    }
+
+    // Century register may be missing.
+    let century = match regs.century {
+        19..=99 => regs.century as u16,
+        _ => 20,
+    };
+
+    DateTime {
+        year: century * 100 + regs.year as u16,
+        month: regs.month,
+        day: regs.day,
+        hour: regs.hour,
+        minute: regs.minute,
+        second: regs.second,
+    }
+}
+
+/// Read current date & time.
+///
+/// # Returns
+/// - Date & time stored in RTC.
+pub fn read() -> DateTime {
+    irq::without_interrupts(|| {
+        let mut regs = read_registers();
+
+        // Update might start during reading, so repeat till values match.
+        loop {
+            let next = read_registers();
+
+            if next == regs {
+                break;
+            }
+
+            regs = next;
+        }
+
+        let status = read_register(STATUS_B);
+        enable_nmi();
+
+        decode(regs, status)
+    })
+}
@@ -6,12 +6,21 @@
 
 use crate::{
     arch::x86::io::{inb, outb},
-    hal::uart::UartInterface,
+    hal::{
+        irq::{self, IrqQueue},
+        uart::UartInterface,
+    },
 };
 
 /// Base address for COM1.
 const UART_BASE: u16 = 0x3f8;
 
+/// COM1 IRQ line.
+const UART_IRQ: u8 = 4;
+
+/// Bytes received by UART IRQ handler.
+static RECEIVED: IrqQueue<256> = IrqQueue::new();
+
 /// UART driver struct.
 pub struct Uart;
 
@@ -28,6 +37,42 @@ impl Uart {
             (inb(UART_BASE + 5) & 0x20) != 0
         }
     }
+
+    /// Checks if received data is available.
+    ///
+    /// # Returns
+    /// - `true`  - if there is received byte to read.
+    /// - `false` - otherwise.
+    fn is_data_ready() -> bool {
+        unsafe {
+            // Read the Line Status Register and check
+            // the data ready bit (bit 0).
+            (inb(UART_BASE + 5) & 0x01) != 0
+        }
+    }
+}
+
+/// UART IRQ handler. Stores all received bytes.
+///
+/// # Parameters
+/// - `_irq` - given IRQ number.
+fn uart_irq(_irq: u8) {
+    while Uart::is_data_ready() {
+        // Bytes received while queue is full are dropped.
+        RECEIVED.push(unsafe { inb(UART_BASE) });
+    }
+}
+
+/// Register UART IRQ handler & enable received data interrupt.
+pub fn init_irq() {
+    if let Err(err) = irq::register_irq_handler(UART_IRQ, uart_irq) {
+        panic!("Failed to register UART IRQ handler: {}", err);
+    }
+
+    unsafe {
+        // Enable received data available interrupt.
+        outb(UART_BASE + 1, 0x01);
+    }
 }
 
 impl UartInterface for Uart {
@@ -51,16 +96,13 @@ impl UartInterface for Uart {
         }
     }
 
-    /// Read byte from serial port.
+    /// Read byte from serial port. Waits for receive interrupt if no byte
+    /// is available.
     ///
     /// # Returns
     /// - Byte read from serial port.
     fn read(&self) -> u8 {
-        while !Self::is_transmit_empty() {
-            continue;
-        }
-
-        unsafe { inb(UART_BASE) }
+        RECEIVED.pop_wait()
     }
 
     /// Write byte to serial port.
@@ -0,0 +1,284 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Date: 2025-06-13
+// Author: Alexander Kuzin <alkuzindev@gmail.com>.
+
+//! CPU exception handlers.
+
+use super::{
+    idt::{EXCEPTIONS, InterruptFrame},
+    tss,
+};
+use crate::{
+    hal::mmu::PageFault,
+    kernel::mm::{kstack, vm},
+    log,
+};
+use core::{
+    arch::asm,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// Page fault error code: page was present.
+const PF_PRESENT: u32 = 1 << 0;
+
+/// Page fault error code: write access.
+const PF_WRITE: u32 = 1 << 1;
+
+/// Page fault error code: access from user mode.
+const PF_USER: u32 = 1 << 2;
+
+/// Page fault error code: reserved bit set in paging structure.
+const PF_RESERVED: u32 = 1 << 3;
+
+/// Page fault error code: instruction fetch.
+const PF_FETCH: u32 = 1 << 4;
+
+/// Number of breakpoint exceptions handled.
+static BREAKPOINTS: AtomicUsize = AtomicUsize::new(0);
+
+/// Address to resume at if write probe faults (zero if no probe is active).
+static PROBE_RECOVERY: AtomicUsize = AtomicUsize::new(0);
+
+/// Read faulting address from CR2 register.
+///
+/// # Returns
+/// - Faulting virtual address.
+fn read_cr2() -> usize {
+    let cr2: usize;
+
+    unsafe {
+        asm!("mov {}, cr2", out(reg) cr2, options(nomem, nostack));
+    }
+
+    cr2
+}
+
+/// CPU exception names indexed by vector number.
+const EXCEPTION_NAMES: [&str; EXCEPTIONS] = [
+    "Divide error (#DE)",
+    "Debug (#DB)",
+    "Non-maskable interrupt (NMI)",
+    "Breakpoint (#BP)",
+    "Overflow (#OF)",
+    "Bound range exceeded (#BR)",
+    "Invalid opcode (#UD)",
+    "Device not available (#NM)",
+    "Double fault (#DF)",
+    "Coprocessor segment overrun",
+    "Invalid TSS (#TS)",
+    "Segment not present (#NP)",
+    "Stack-segment fault (#SS)",
+    "General protection fault (#GP)",
+    "Page fault (#PF)",
+    "Reserved",
+    "x87 floating-point exception (#MF)",
+    "Alignment check (#AC)",
+    "Machine check (#MC)",
+    "SIMD floating-point exception (#XM)",
+    "Virtualization exception (#VE)",
+    "Control protection exception (#CP)",
+    "Reserved",
+    "Reserved",
+    "Reserved",
+    "Reserved",
+    "Reserved",
+    "Reserved",
+    "Hypervisor injection exception (#HV)",
+    "VMM communication exception (#VC)",
+    "Security exception (#SX)",
+    "Reserved",
+];
+
+/// Get CPU exception name.
+///
+/// # Parameters
+/// - `vector` - given exception vector number.
+///
+/// # Returns
+/// - Exception name.
+pub fn exception_name(vector: u32) -> &'static str {
+    EXCEPTION_NAMES
+        .get(vector as usize)
+        .copied()
+        .unwrap_or("Unknown exception")
+}
+
+/// Print saved CPU state.
+///
+/// # Parameters
+/// - `frame` - given CPU state saved on exception entry.
+fn dump_frame(frame: &InterruptFrame) {
+    // Stack pointer is not saved on exception without privilege change,
+    // interrupted stack continues right after the frame.
+    let esp =
+        frame as *const InterruptFrame as usize + size_of::<InterruptFrame>();
+
+    log::panic!(
+        "EIP={:#010X} CS={:#06X} EFLAGS={:#010X} ERROR={:#010X}",
+        frame.eip,
+        frame.cs,
+        frame.eflags,
+        frame.error_code
+    );
+    log::panic!(
+        "EAX={:#010X} EBX={:#010X} ECX={:#010X} EDX={:#010X}",
+        frame.eax,
+        frame.ebx,
+        frame.ecx,
+        frame.edx
+    );
+    log::panic!(
+        "ESI={:#010X} EDI={:#010X} EBP={:#010X} ESP={:#010X}",
+        frame.esi,
+        frame.edi,
+        frame.ebp,
+        esp
+    );
+    log::panic!(
+        "DS={:#06X} ES={:#06X} FS={:#06X} GS={:#06X}",
+        frame.ds,
+        frame.es,
+        frame.fs,
+        frame.gs
+    );
+}
+
+/// Breakpoint (#BP) handler. Reports CPU state & resumes execution.
+///
+/// # Parameters
+/// - `frame` - given CPU state saved on exception entry.
+pub fn breakpoint(frame: &InterruptFrame) {
+    log::debug!("Breakpoint at {:#010X}", frame.eip.wrapping_sub(1));
+    BREAKPOINTS.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Get number of breakpoints hit since boot.
+///
+/// # Returns
+/// - Number of handled breakpoint exceptions.
+pub fn breakpoint_count() -> usize {
+    BREAKPOINTS.load(Ordering::Relaxed)
+}
+
+/// Handler of CPU exceptions the kernel can not recover from. Reports
+/// exception & CPU state, then stops the kernel.
+///
+/// # Parameters
+/// - `frame` - given CPU state saved on exception entry.
+pub fn unhandled(frame: &mut InterruptFrame) -> ! {
+    let name = exception_name(frame.vector);
+
+    log::panic!("Exception {}: {}", frame.vector, name);
+    dump_frame(frame);
+
+    panic!("{} at {:#010X}", name, frame.eip);
+}
+
+/// Page fault (#PF) handler. Faults inside regions of the current address
+/// space are resolved by virtual memory subsystem, the rest are reported.
+///
+/// # Parameters
+/// - `frame` - given CPU state saved on exception entry.
+pub fn page_fault(frame: &mut InterruptFrame) {
+    let code = frame.error_code;
+    let fault = PageFault {
+        addr: read_cr2(),
+        ip: frame.eip as usize,
+        write: (code & PF_WRITE) != 0,
+        exec: (code & PF_FETCH) != 0,
+        user: (code & PF_USER) != 0,
+        present: (code & PF_PRESENT) != 0,
+    };
+
+    if kstack::is_guard_page(fault.addr) {
+        log::panic!("Kernel stack overflow: {}", fault);
+        dump_frame(frame);
+        panic!("Kernel stack overflow at {:#010X}", fault.ip);
+    }
+
+    if (code & PF_RESERVED) == 0 && vm::handle_page_fault(&fault) {
+        return;
+    }
+
+    // Faulting write probe reports failure instead of panic.
+    let recovery = PROBE_RECOVERY.swap(0, Ordering::Relaxed);
+
+    if recovery != 0 && !fault.user {
+        frame.eip = recovery as u32;
+        frame.eax = 1;
+        return;
+    }
+
+    log::panic!("Page fault: {}", fault);
+    log::panic!(
+        "Error code: {:#06X}{}",
+        code,
+        if (code & PF_RESERVED) != 0 {
+            " (reserved bit set)"
+        } else {
+            ""
+        }
+    );
+    dump_frame(frame);
+
+    panic!("Unhandled page fault at {:#010X}", fault.addr);
+}
+
+/// Check whether kernel is allowed to write to given address. Byte at the
+/// address is rewritten with its own value, page fault is caught instead of
+/// being reported.
+///
+/// # Parameters
+/// - `addr` - given virtual address to probe.
+///
+/// # Returns
+/// - `true`  - if write succeeded.
+/// - `false` - if write caused page fault.
+pub fn probe_write(addr: usize) -> bool {
+    let faulted: u32;
+
+    unsafe {
+        asm!(
+            "lea {tmp}, [2f]",
+            "mov [{recovery}], {tmp}",
+            "mov {tmp:l}, byte ptr [{addr}]",
+            "mov byte ptr [{addr}], {tmp:l}",
+            "2:",
+            "mov dword ptr [{recovery}], 0",
+            addr = in(reg) addr,
+            recovery = in(reg) PROBE_RECOVERY.as_ptr(),
+            tmp = out(reg_abcd) _,
+            inout("eax") 0u32 => faulted,
+            options(nostack),
+        );
+    }
+
+    faulted == 0
+}
+
+/// Double fault (#DF) handler. Runs as separate task on its own stack, so
+/// it works even if kernel stack is exhausted. State of the interrupted
+/// code is saved in kernel task TSS by the CPU.
+pub extern "C" fn double_fault() -> ! {
+    let task = tss::interrupted_task();
+    let (eip, esp) = (task.eip, task.esp);
+    let addr = read_cr2();
+
+    // Page fault on guard page could not push exception frame.
+    if kstack::is_guard_page(addr) || kstack::is_guard_page(esp as usize) {
+        log::panic!(
+            "Kernel stack overflow: ESP={:#010X}, faulting address {:#010X}",
+            esp,
+            addr
+        );
+        panic!("Kernel stack overflow at {:#010X}", eip);
+    }
+
+    log::panic!(
+        "Double fault: EIP={:#010X} ESP={:#010X} CR2={:#010X}",
+        eip,
+        esp,
+        addr
+    );
+    panic!("Double fault at {:#010X}", eip);
+}
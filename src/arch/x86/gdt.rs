@@ -9,8 +9,16 @@
 //! architecture for memory management and protection. It defines the
 //! characteristics of various memory segments, allowing the CPU to manage
 //! memory access and enforce protection mechanisms.
+//!
+//! Standard flat segments, task state segments & `sysenter` segments occupy
+//! fixed slots (see `Segment`), the rest of the table is allocated at
+//! runtime (per-CPU data segments, additional TSS, LDT). Every CPU may have
+//! its own `Gdt`, boot CPU uses the one initialized by `init`.
 
+use super::tss;
 use crate::log;
+use core::{arch::asm, fmt};
+use spin::Mutex;
 
 /// GDT segment structure.
 #[derive(Debug, Default, Clone, Copy)]
@@ -38,7 +46,7 @@ impl Entry {
     /// - `limit`  - given maximum addressable unit.
     /// - `access` - given segment access byte.
     /// - `flags`  - given segment flags.
-    pub fn new(base: u32, limit: u32, access: u8, flags: u8) -> Self {
+    pub const fn new(base: u32, limit: u32, access: u8, flags: u8) -> Self {
         let entry_base_low = (base & 0xFFFF) as u16;
         let entry_base_mid = ((base >> 0x10) & 0xFF) as u8;
         let entry_base_high = ((base >> 0x18) & 0xFF) as u8;
@@ -55,6 +63,42 @@ impl Entry {
             access,
         }
     }
+
+    /// Construct new 32-bit data segment with byte granularity.
+    ///
+    /// # Parameters
+    /// - `base`  - given linear address where the segment begins.
+    /// - `limit` - given maximum addressable byte (at most 1 MB).
+    pub fn data(base: u32, limit: u32) -> Self {
+        Self::new(base, limit, Access::KernelData as u8, SIZE_32)
+    }
+
+    /// Construct new task state segment.
+    ///
+    /// # Parameters
+    /// - `base` - given linear address of the TSS.
+    pub fn tss(base: u32) -> Self {
+        Self::new(base, TSS_LIMIT, Access::Tss as u8, 0)
+    }
+
+    /// Construct new local descriptor table segment.
+    ///
+    /// # Parameters
+    /// - `base`  - given linear address of the LDT.
+    /// - `limit` - given LDT size - 1.
+    pub fn ldt(base: u32, limit: u32) -> Self {
+        Self::new(base, limit, Access::Ldt as u8, 0)
+    }
+
+    /// Check whether entry describes present segment.
+    ///
+    /// # Returns
+    /// - `true`  - if segment is present.
+    /// - `false` - otherwise.
+    #[inline(always)]
+    pub fn is_present(&self) -> bool {
+        (self.access & PRESENT) != 0
+    }
 }
 
 /// GDT pointer.
@@ -78,8 +122,17 @@ pub enum Segment {
     UserCode = 0x20,
     UserData = 0x28,
     UserStack = 0x30,
+    Tss = 0x38,
+    DoubleFaultTss = 0x40,
+    SysenterCode = 0x48,
+    SysenterStack = 0x50,
+    SysexitCode = 0x58,
+    SysexitStack = 0x60,
 }
 
+/// Requested privilege level of user mode segment selectors.
+pub const USER_RPL: u32 = 3;
+
 /// Access bytes enumeration.
 #[derive(Debug)]
 #[repr(u8)]
@@ -90,125 +143,454 @@ pub enum Access {
     UserCode = 0xFA,
     UserData = 0xF2,
     UserStack = 0xF7,
+    Tss = 0x89,
+    Ldt = 0x82,
+}
+
+/// Present bit of access byte.
+const PRESENT: u8 = 1 << 7;
+
+/// Busy bit of TSS access byte.
+const TSS_BUSY: u8 = 1 << 1;
+
+/// 32-bit segment flag (byte granularity).
+const SIZE_32: u8 = 0x40;
+
+/// 32-bit segment flags (4 KB granularity).
+const FLAT_FLAGS: u8 = 0xCF;
+
+/// Task state segment limit.
+const TSS_LIMIT: u32 = size_of::<tss::TaskStateSegment>() as u32 - 1;
+
+/// Maximum number of GDT entries.
+pub const GDT_ENTRIES: usize = 32;
+
+/// Number of fixed GDT entries (see `Segment`).
+const FIXED_ENTRIES: usize = 13;
+
+/// GDT management errors enumeration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GdtError {
+    /// All GDT entries are in use.
+    Full,
+    /// Selector does not refer to allocated entry.
+    InvalidSelector,
+    /// Selector refers to fixed entry.
+    Reserved,
+}
+
+impl fmt::Display for GdtError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            Self::Full => "GDT is full",
+            Self::InvalidSelector => "GDT selector is not allocated",
+            Self::Reserved => "GDT selector refers to fixed entry",
+        };
+
+        f.write_str(msg)
+    }
 }
 
 fn access_to_str(access: u8) -> &'static str {
     match access {
         0x00 => "Null",
-        0x9A => "Kernel code",
-        0x92 => "Kernel data",
+        0x9A | 0x9B => "Kernel code",
+        0x92 | 0x93 => "Kernel data",
         0x97 => "Kernel stack",
-        0xFA => "User code",
-        0xF2 => "User data",
+        0xFA | 0xFB => "User code",
+        0xF2 | 0xF3 => "User data",
         0xF7 => "User stack",
+        0x89 | 0x8B => "Task state",
+        0x82 => "Local descriptor table",
         _ => "Unknown",
     }
 }
 
-/// GDT pointer physical address.
-const GDT_BASE: u32 = 0x800;
-
-/// Number of GDT entries.
-const GDT_ENTRIES: usize = 7;
-
 /// Empty entry.
-const NULL_ENTRY: Entry = Entry {
-    limit: 0,
-    base_low: 0,
-    base_mid: 0,
-    access: 0,
-    flags: 0,
-    base_high: 0,
-};
+const NULL_ENTRY: Entry = Entry::new(0, 0, 0, 0);
+
+/// Get GDT entry index of selector.
+///
+/// # Parameters
+/// - `selector` - given segment selector.
+///
+/// # Returns
+/// - Entry index (privilege level & table bits are ignored).
+#[inline(always)]
+fn index(selector: u16) -> usize {
+    (selector >> 3) as usize
+}
 
 /// Global Descriptor Table.
-static mut GDT: [Entry; GDT_ENTRIES] = [NULL_ENTRY; GDT_ENTRIES];
-
-/// Global Descriptor Table pointer.
-static mut GDT_PTR: *mut Pointer = GDT_BASE as *mut Pointer;
-
-/// Set GDT entries.
-fn set_entries() {
-    // 32-bit protected mode segment.
-    const FLAGS: u8 = 0xCF;
-    const BASE: u32 = 0x00000000;
-    const LIMIT: u32 = 0xFFFFFFFF;
-
-    let null = Entry::default();
-    let kern_code = Entry::new(BASE, LIMIT, Access::KernelCode as u8, FLAGS);
-    let kern_data = Entry::new(BASE, LIMIT, Access::KernelData as u8, FLAGS);
-    let kern_stack = Entry::new(BASE, LIMIT, Access::KernelStack as u8, FLAGS);
-    let user_code = Entry::new(BASE, LIMIT, Access::UserCode as u8, FLAGS);
-    let user_data = Entry::new(BASE, LIMIT, Access::UserData as u8, FLAGS);
-    let user_stack = Entry::new(BASE, LIMIT, Access::UserStack as u8, FLAGS);
+#[derive(Debug, Clone)]
+#[repr(C, align(8))]
+pub struct Gdt {
+    /// Table entries.
+    entries: [Entry; GDT_ENTRIES],
+    /// Bitmask of used entries.
+    used: u32,
+}
 
-    unsafe {
-        // (Null descriptor) should always contain no data.
-        GDT[0] = null;
+impl Gdt {
+    /// Construct empty GDT (only null descriptor is used).
+    pub const fn empty() -> Self {
+        Self {
+            entries: [NULL_ENTRY; GDT_ENTRIES],
+            used: 1,
+        }
+    }
+
+    /// Construct new GDT with standard flat segments. Task state segments
+    /// slots are reserved, but left empty.
+    pub fn new() -> Self {
+        const BASE: u32 = 0x00000000;
+        const LIMIT: u32 = 0xFFFFFFFF;
+
+        let mut gdt = Self::empty();
 
         // Kernel space segments.
-        GDT[1] = kern_code;
-        GDT[2] = kern_data;
-        GDT[3] = kern_stack;
+        gdt.entries[1] =
+            Entry::new(BASE, LIMIT, Access::KernelCode as u8, FLAT_FLAGS);
+        gdt.entries[2] =
+            Entry::new(BASE, LIMIT, Access::KernelData as u8, FLAT_FLAGS);
+        gdt.entries[3] =
+            Entry::new(BASE, LIMIT, Access::KernelStack as u8, FLAT_FLAGS);
 
         // User space segments.
-        GDT[4] = user_code;
-        GDT[5] = user_data;
-        GDT[6] = user_stack;
+        gdt.entries[4] =
+            Entry::new(BASE, LIMIT, Access::UserCode as u8, FLAT_FLAGS);
+        gdt.entries[5] =
+            Entry::new(BASE, LIMIT, Access::UserData as u8, FLAT_FLAGS);
+        gdt.entries[6] =
+            Entry::new(BASE, LIMIT, Access::UserStack as u8, FLAT_FLAGS);
+
+        // Segments loaded by `sysenter` & `sysexit` must follow each other.
+        gdt.entries[9] = gdt.entries[1];
+        gdt.entries[10] = gdt.entries[2];
+        gdt.entries[11] = gdt.entries[4];
+        gdt.entries[12] = gdt.entries[5];
+
+        gdt.used = (1 << FIXED_ENTRIES) - 1;
+        gdt
     }
-}
 
-// Set GDT pointer.
-fn set_pointer() {
-    unsafe {
-        let gdt_ptr = GDT_PTR.as_mut().unwrap();
-        gdt_ptr.size = (size_of::<Entry>() * GDT_ENTRIES - 1) as u16;
-        gdt_ptr.offset = (&raw const GDT as *const _) as u32;
+    /// Get entry.
+    ///
+    /// # Parameters
+    /// - `selector` - given segment selector.
+    ///
+    /// # Returns
+    /// - Entry  - if selector refers to used entry.
+    /// - `None` - otherwise.
+    pub fn get(&self, selector: u16) -> Option<Entry> {
+        let index = index(selector);
+
+        if index < GDT_ENTRIES && (self.used & (1 << index)) != 0 {
+            Some(self.entries[index])
+        } else {
+            None
+        }
+    }
+
+    /// Replace used entry (fixed one as well).
+    ///
+    /// # Parameters
+    /// - `selector` - given segment selector.
+    /// - `entry`    - given new entry.
+    ///
+    /// # Returns
+    /// - `Ok`                        - in case of success.
+    /// - `GdtError::InvalidSelector` - if entry is not used.
+    pub fn set(&mut self, selector: u16, entry: Entry) -> Result<(), GdtError> {
+        self.get(selector).ok_or(GdtError::InvalidSelector)?;
+        self.entries[index(selector)] = entry;
+        Ok(())
+    }
+
+    /// Allocate new entry.
+    ///
+    /// # Parameters
+    /// - `entry` - given entry to store.
+    ///
+    /// # Returns
+    /// - Selector of allocated entry - in case of success.
+    /// - `GdtError::Full`            - if there is no free entry.
+    pub fn alloc(&mut self, entry: Entry) -> Result<u16, GdtError> {
+        let index = (!self.used).trailing_zeros() as usize;
+
+        if index >= GDT_ENTRIES {
+            return Err(GdtError::Full);
+        }
+
+        self.used |= 1 << index;
+        self.entries[index] = entry;
+        Ok((index << 3) as u16)
+    }
+
+    /// Free allocated entry.
+    ///
+    /// # Parameters
+    /// - `selector` - given selector of allocated entry.
+    ///
+    /// # Returns
+    /// - `Ok`                        - in case of success.
+    /// - `GdtError::Reserved`        - if selector refers to fixed entry.
+    /// - `GdtError::InvalidSelector` - if entry is not allocated.
+    pub fn free(&mut self, selector: u16) -> Result<(), GdtError> {
+        if index(selector) < FIXED_ENTRIES {
+            return Err(GdtError::Reserved);
+        }
+
+        self.get(selector).ok_or(GdtError::InvalidSelector)?;
+        self.used &= !(1 << index(selector));
+        self.entries[index(selector)] = NULL_ENTRY;
+        Ok(())
+    }
+
+    /// Get number of used entries.
+    ///
+    /// # Returns
+    /// - Number of used entries (including null descriptor).
+    pub fn count(&self) -> usize {
+        self.used.count_ones() as usize
+    }
+
+    /// Load GDT & reload segment registers with kernel segments.
+    ///
+    /// # Safety
+    /// - GDT must not be moved or dropped while it is loaded.
+    /// - Standard flat segments must not be changed.
+    pub unsafe fn load(&self) {
+        let pointer = Pointer {
+            size: (size_of::<Entry>() * GDT_ENTRIES - 1) as u16,
+            offset: self.entries.as_ptr() as u32,
+        };
+
+        // Far return reloads CS, other registers are reloaded directly.
+        unsafe {
+            asm!(
+                "lgdt [{pointer}]",
+                "mov ds, {data:x}",
+                "mov es, {data:x}",
+                "mov fs, {data:x}",
+                "mov gs, {data:x}",
+                "mov ss, {stack:x}",
+                "push {code}",
+                "lea {tmp}, [2f]",
+                "push {tmp}",
+                "retf",
+                "2:",
+                pointer = in(reg) &raw const pointer,
+                data = in(reg) Segment::KernelData as u32,
+                stack = in(reg) Segment::KernelStack as u32,
+                code = const Segment::KernelCode as u32,
+                tmp = out(reg) _,
+            );
+        }
     }
-}
 
-unsafe extern "C" {
-    /// Flush out the old GDT and install the new changes.
+    /// Load task register. Busy bit of the TSS is cleared first, so that
+    /// TSS copied from another GDT can be loaded.
     ///
     /// # Parameters
-    /// - `ptr` - given new GDT pointer to update.
-    fn gdt_flush(ptr: u32);
-}
-
-/// Print GDT related info for debug.
-#[doc(hidden)]
-fn print_gdt() {
-    let gdt_ptr = unsafe { GDT_PTR.as_ref().unwrap() };
-    let size = gdt_ptr.size;
-    let offset = gdt_ptr.offset;
-
-    log::info!("Setting Global Descriptor Table (GDT):");
-    log::info!("GDT pointer: <{:#?}>", unsafe { GDT_PTR });
-    log::info!("GDT size: {} bytes", size);
-    log::info!("GDT offset: <{:#08x}>", offset);
-    log::info!("Set {} GDT entries:", GDT_ENTRIES);
-
-    let gdt = unsafe { GDT };
-
-    for entry in gdt.iter() {
-        log::info!(
-            "Access: {:#08X}  Flags: {:#08X}  Segment: {}",
-            entry.access,
-            entry.flags,
-            access_to_str(entry.access),
+    /// - `selector` - given selector of the TSS.
+    ///
+    /// # Returns
+    /// - `Ok`                        - in case of success.
+    /// - `GdtError::InvalidSelector` - if selector does not refer to TSS.
+    ///
+    /// # Safety
+    /// - GDT must be loaded.
+    pub unsafe fn load_task_register(
+        &mut self,
+        selector: u16,
+    ) -> Result<(), GdtError> {
+        let entry = self.get(selector).ok_or(GdtError::InvalidSelector)?;
+
+        if (entry.access & !TSS_BUSY) != Access::Tss as u8 {
+            return Err(GdtError::InvalidSelector);
+        }
+
+        self.entries[index(selector)].access = Access::Tss as u8;
+
+        unsafe {
+            asm!("ltr {:x}", in(reg) selector, options(nostack));
+        }
+
+        Ok(())
+    }
+
+    /// Print GDT entries for debug.
+    fn print(&self) {
+        log::debug!(
+            "GDT at <{:#010X}>: {} of {} entries used",
+            self.entries.as_ptr() as u32,
+            self.count(),
+            GDT_ENTRIES
         );
+
+        for (index, entry) in self.entries.iter().enumerate() {
+            if (self.used & (1 << index)) == 0 || !entry.is_present() {
+                continue;
+            }
+
+            log::debug!(
+                "Selector: {:#04X}  Access: {:#04X}  Flags: {:#04X}  \
+                 Segment: {}",
+                index << 3,
+                entry.access,
+                entry.flags,
+                access_to_str(entry.access),
+            );
+        }
+    }
+}
+
+impl Default for Gdt {
+    /// Construct new GDT with standard flat segments.
+    ///
+    /// # Returns
+    /// - New `Gdt` object.
+    fn default() -> Self {
+        Self::new()
     }
 }
 
-/// Initialize Global Descriptor Table.
+/// Boot CPU Global Descriptor Table.
+static GDT: Mutex<Gdt> = Mutex::new(Gdt::empty());
+
+/// Allocate new entry of boot CPU GDT.
+///
+/// # Parameters
+/// - `entry` - given entry to store.
+///
+/// # Returns
+/// - Selector of allocated entry - in case of success.
+/// - `GdtError::Full`            - if there is no free entry.
+pub fn alloc(entry: Entry) -> Result<u16, GdtError> {
+    GDT.lock().alloc(entry)
+}
+
+/// Free allocated entry of boot CPU GDT.
+///
+/// # Parameters
+/// - `selector` - given selector of allocated entry.
+///
+/// # Returns
+/// - `Ok`                        - in case of success.
+/// - `GdtError::Reserved`        - if selector refers to fixed entry.
+/// - `GdtError::InvalidSelector` - if entry is not allocated.
+pub fn free(selector: u16) -> Result<(), GdtError> {
+    GDT.lock().free(selector)
+}
+
+/// Get entry of boot CPU GDT.
+///
+/// # Parameters
+/// - `selector` - given segment selector.
+///
+/// # Returns
+/// - Entry  - if selector refers to used entry.
+/// - `None` - otherwise.
+pub fn get(selector: u16) -> Option<Entry> {
+    GDT.lock().get(selector)
+}
+
+/// Load boot CPU task register.
+///
+/// # Parameters
+/// - `selector` - given selector of the TSS.
+///
+/// # Returns
+/// - `Ok`                        - in case of success.
+/// - `GdtError::InvalidSelector` - if selector does not refer to TSS.
+pub fn load_task_register(selector: u16) -> Result<(), GdtError> {
+    unsafe { GDT.lock().load_task_register(selector) }
+}
+
+/// Initialize boot CPU Global Descriptor Table.
 pub fn init() {
-    set_entries();
-    set_pointer();
+    let mut gdt = GDT.lock();
+    *gdt = Gdt::new();
+
+    // Task state segments (byte granularity).
+    let tss = Entry::tss(tss::tss_base());
+    let df_tss = Entry::tss(tss::double_fault_tss_base());
+
+    gdt.entries[index(Segment::Tss as u16)] = tss;
+    gdt.entries[index(Segment::DoubleFaultTss as u16)] = df_tss;
 
     unsafe {
-        // Update GDT.
-        gdt_flush(GDT_BASE);
+        // Static table is never moved.
+        gdt.load();
+    }
+
+    gdt.print();
+}
+
+#[cfg(feature = "ktest")]
+pub mod tests {
+    use super::*;
+    use crate::ktest::TestCase;
+
+    /// Global Descriptor Table unit tests.
+    pub const TESTS: &[TestCase] = &[
+        TestCase::new("alloc_free", alloc_free),
+        TestCase::new("fixed_reserved", fixed_reserved),
+        TestCase::new("table_full", table_full),
+        TestCase::new("reload", reload),
+    ];
+
+    fn alloc_free() {
+        let entry = Entry::data(0x1000, 0xFFF);
+        let selector = alloc(entry).unwrap();
+
+        assert!(index(selector) >= FIXED_ENTRIES);
+        assert_eq!(get(selector).unwrap().access, Access::KernelData as u8);
+
+        // Allocated data segment is usable.
+        unsafe {
+            asm!("mov fs, {:x}", in(reg) selector, options(nostack));
+            asm!("mov fs, {:x}", in(reg) Segment::KernelData as u16);
+        }
+
+        assert_eq!(free(selector), Ok(()));
+        assert_eq!(free(selector), Err(GdtError::InvalidSelector));
+        assert!(get(selector).is_none());
+    }
+
+    fn fixed_reserved() {
+        assert_eq!(free(Segment::KernelCode as u16), Err(GdtError::Reserved));
+        assert_eq!(free(Segment::Tss as u16), Err(GdtError::Reserved));
+        assert!(get(Segment::Tss as u16).unwrap().is_present());
+    }
+
+    fn table_full() {
+        let mut gdt = Gdt::new();
+        let entry = Entry::data(0, 0);
+
+        for _ in FIXED_ENTRIES..GDT_ENTRIES {
+            gdt.alloc(entry).unwrap();
+        }
+
+        assert_eq!(gdt.count(), GDT_ENTRIES);
+        assert_eq!(gdt.alloc(entry), Err(GdtError::Full));
+
+        let selector = (FIXED_ENTRIES << 3) as u16;
+        gdt.free(selector).unwrap();
+        assert_eq!(gdt.alloc(entry), Ok(selector));
     }
 
-    print_gdt();
+    fn reload() {
+        let (cs, ds): (u16, u16);
+
+        unsafe {
+            GDT.lock().load();
+            asm!("mov {:x}, cs", out(reg) cs, options(nomem, nostack));
+            asm!("mov {:x}, ds", out(reg) ds, options(nomem, nostack));
+        }
+
+        assert_eq!(cs, Segment::KernelCode as u16);
+        assert_eq!(ds, Segment::KernelData as u16);
+    }
 }
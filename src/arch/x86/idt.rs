@@ -0,0 +1,294 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Date: 2025-06-13
+// Author: Alexander Kuzin <alkuzindev@gmail.com>.
+
+//! Interrupt Descriptor Table module.
+//!
+//! # Description
+//! The Interrupt Descriptor Table (IDT) tells the CPU where interrupt
+//! service routines are located. Every routine is an assembly entry point
+//! (see `isr.asm`) that saves CPU state as `InterruptFrame` & passes it
+//! to `interrupt_dispatch`. All CPU exceptions (vectors 0-31) have their
+//! routines, unhandled ones print CPU state & stop the kernel. Hardware
+//! interrupts (IRQs) follow CPU exceptions, local APIC interrupts use
+//! their own vectors. System call gate is the only one available from user
+//! mode.
+
+use super::{
+    apic, exceptions,
+    gdt::Segment,
+    pic::{IRQ_BASE, IRQ_COUNT},
+    syscall,
+};
+use crate::hal;
+use core::arch::asm;
+
+/// Number of IDT entries.
+const IDT_ENTRIES: usize = 256;
+
+/// Present 32-bit interrupt gate with kernel privilege level.
+const INTERRUPT_GATE: u8 = 0x8E;
+
+/// Present 32-bit trap gate with user privilege level.
+const USER_TRAP_GATE: u8 = 0xEF;
+
+/// Present task gate with kernel privilege level.
+const TASK_GATE: u8 = 0x85;
+
+/// Number of CPU exception vectors.
+pub const EXCEPTIONS: usize = 32;
+
+/// End of hardware interrupt vectors (exclusive).
+const IRQ_END: u8 = IRQ_BASE + IRQ_COUNT as u8;
+
+/// Breakpoint exception vector.
+pub const BREAKPOINT: u8 = 3;
+
+/// Double fault exception vector.
+pub const DOUBLE_FAULT: u8 = 8;
+
+/// Page fault exception vector.
+pub const PAGE_FAULT: u8 = 14;
+
+/// System call vector.
+pub const SYSCALL: u8 = 0x80;
+
+/// IDT gate structure.
+#[derive(Debug, Clone, Copy)]
+#[repr(C, packed)]
+pub struct Gate {
+    /// Lower part of interrupt service routine address.
+    pub offset_low: u16,
+    /// Code segment selector of interrupt service routine.
+    pub selector: u16,
+    /// Reserved, always zero.
+    pub zero: u8,
+    /// Gate type, privilege level & present bit.
+    pub attributes: u8,
+    /// Higher part of interrupt service routine address.
+    pub offset_high: u16,
+}
+
+impl Gate {
+    /// Empty (not present) gate.
+    const MISSING: Self = Self {
+        offset_low: 0,
+        selector: 0,
+        zero: 0,
+        attributes: 0,
+        offset_high: 0,
+    };
+
+    /// Construct new IDT gate.
+    ///
+    /// # Parameters
+    /// - `handler`    - given interrupt service routine address.
+    /// - `attributes` - given gate type, privilege level & present bit.
+    pub fn new(handler: u32, attributes: u8) -> Self {
+        Self {
+            offset_low: (handler & 0xFFFF) as u16,
+            selector: Segment::KernelCode as u16,
+            zero: 0,
+            attributes,
+            offset_high: ((handler >> 0x10) & 0xFFFF) as u16,
+        }
+    }
+
+    /// Construct new IDT task gate.
+    ///
+    /// # Parameters
+    /// - `selector` - given GDT selector of the task TSS.
+    pub fn task(selector: Segment) -> Self {
+        Self {
+            offset_low: 0,
+            selector: selector as u16,
+            zero: 0,
+            attributes: TASK_GATE,
+            offset_high: 0,
+        }
+    }
+}
+
+/// IDT pointer.
+#[derive(Debug)]
+#[repr(C, packed)]
+pub struct Pointer {
+    /// IDT size - 1.
+    pub size: u16,
+    /// Linear address of IDT.
+    pub offset: u32,
+}
+
+/// CPU state saved on interrupt entry.
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct InterruptFrame {
+    /// Saved GS segment register.
+    pub gs: u32,
+    /// Saved FS segment register.
+    pub fs: u32,
+    /// Saved ES segment register.
+    pub es: u32,
+    /// Saved DS segment register.
+    pub ds: u32,
+    /// Saved EDI register.
+    pub edi: u32,
+    /// Saved ESI register.
+    pub esi: u32,
+    /// Saved EBP register.
+    pub ebp: u32,
+    /// ESP register value pushed by `pusha` (ignored by `popa`).
+    pub esp_dummy: u32,
+    /// Saved EBX register.
+    pub ebx: u32,
+    /// Saved EDX register.
+    pub edx: u32,
+    /// Saved ECX register.
+    pub ecx: u32,
+    /// Saved EAX register.
+    pub eax: u32,
+    /// Interrupt vector number.
+    pub vector: u32,
+    /// Exception error code (zero if exception has no error code).
+    pub error_code: u32,
+    /// Interrupted instruction address.
+    pub eip: u32,
+    /// Interrupted code segment selector.
+    pub cs: u32,
+    /// Interrupted flags register.
+    pub eflags: u32,
+}
+
+/// Interrupt Descriptor Table.
+static mut IDT: [Gate; IDT_ENTRIES] = [Gate::MISSING; IDT_ENTRIES];
+
+unsafe extern "C" {
+    /// CPU exception service routines indexed by vector number.
+    #[link_name = "isr_exception_table"]
+    static ISR_EXCEPTION_TABLE: [unsafe extern "C" fn(); EXCEPTIONS];
+
+    /// Hardware interrupt service routines indexed by IRQ number.
+    #[link_name = "isr_irq_table"]
+    static ISR_IRQ_TABLE: [unsafe extern "C" fn(); IRQ_COUNT];
+
+    /// Local APIC interrupt service routines (see `apic::VECTORS`).
+    #[link_name = "isr_apic_table"]
+    static ISR_APIC_TABLE: [unsafe extern "C" fn(); apic::VECTORS.len()];
+
+    /// System call service routine.
+    #[link_name = "isr128"]
+    fn isr_syscall();
+}
+
+/// Set IDT gate.
+///
+/// # Parameters
+/// - `vector`     - given interrupt vector number.
+/// - `handler`    - given interrupt service routine.
+/// - `attributes` - given gate type, privilege level & present bit.
+fn set_gate(vector: u8, handler: unsafe extern "C" fn(), attributes: u8) {
+    let gate = Gate::new(handler as *const () as u32, attributes);
+
+    unsafe {
+        IDT[vector as usize] = gate;
+    }
+}
+
+/// Dispatch interrupt to its handler. Called from `isr_common`.
+///
+/// # Parameters
+/// - `frame` - given CPU state saved on interrupt entry.
+#[unsafe(no_mangle)]
+extern "C" fn interrupt_dispatch(frame: &mut InterruptFrame) {
+    match frame.vector as u8 {
+        BREAKPOINT => exceptions::breakpoint(frame),
+        PAGE_FAULT => exceptions::page_fault(frame),
+        vector if (vector as usize) < EXCEPTIONS => {
+            exceptions::unhandled(frame)
+        }
+        vector if (IRQ_BASE..IRQ_END).contains(&vector) => {
+            hal::irq::handle(vector - IRQ_BASE)
+        }
+        vector if apic::VECTORS.contains(&vector) => apic::handle(vector),
+        SYSCALL => syscall::handle(frame),
+        vector => panic!("Unexpected interrupt {}", vector),
+    }
+}
+
+/// Initialize Interrupt Descriptor Table.
+pub fn init() {
+    let handlers = unsafe { &ISR_EXCEPTION_TABLE };
+
+    for (vector, &handler) in handlers.iter().enumerate() {
+        set_gate(vector as u8, handler, INTERRUPT_GATE);
+    }
+
+    let handlers = unsafe { &ISR_IRQ_TABLE };
+
+    for (irq, &handler) in handlers.iter().enumerate() {
+        set_gate(IRQ_BASE + irq as u8, handler, INTERRUPT_GATE);
+    }
+
+    let handlers = unsafe { &ISR_APIC_TABLE };
+
+    for (&vector, &handler) in apic::VECTORS.iter().zip(handlers) {
+        set_gate(vector, handler, INTERRUPT_GATE);
+    }
+
+    // Trap gate keeps interrupts enabled during system call.
+    set_gate(SYSCALL, isr_syscall, USER_TRAP_GATE);
+
+    // Double fault switches to separate task with its own stack.
+    unsafe {
+        IDT[DOUBLE_FAULT as usize] = Gate::task(Segment::DoubleFaultTss);
+    }
+
+    let pointer = Pointer {
+        size: (size_of::<Gate>() * IDT_ENTRIES - 1) as u16,
+        offset: &raw const IDT as u32,
+    };
+
+    unsafe {
+        asm!("lidt [{}]", in(reg) &pointer, options(readonly, nostack));
+    }
+}
+
+#[cfg(feature = "ktest")]
+pub mod tests {
+    use super::*;
+    use crate::ktest::TestCase;
+
+    /// IDT unit tests.
+    pub const TESTS: &[TestCase] = &[
+        TestCase::new("exception_gates_present", exception_gates_present),
+        TestCase::new("breakpoint_resumes", breakpoint_resumes),
+    ];
+
+    fn exception_gates_present() {
+        let handlers = unsafe { &ISR_EXCEPTION_TABLE };
+
+        for (vector, &handler) in handlers.iter().enumerate() {
+            let gate = unsafe { IDT[vector] };
+            let offset =
+                gate.offset_low as u32 | ((gate.offset_high as u32) << 16);
+
+            if vector == DOUBLE_FAULT as usize {
+                assert_eq!(gate.attributes, TASK_GATE);
+                continue;
+            }
+
+            assert_eq!(gate.attributes, INTERRUPT_GATE);
+            assert_eq!(offset, handler as *const () as u32);
+        }
+    }
+
+    fn breakpoint_resumes() {
+        let count = exceptions::breakpoint_count();
+
+        unsafe {
+            asm!("int3", options(nomem, nostack));
+        }
+
+        assert_eq!(exceptions::breakpoint_count(), count + 1);
+    }
+}
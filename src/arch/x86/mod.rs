@@ -4,18 +4,118 @@
 
 //! x86 architecture-specific code main module.
 
-use crate::log;
+use crate::{
+    hal::{self, clocksource::Clocksource, irq::InterruptController},
+    log,
+};
 
+pub mod apic;
 pub mod cpu;
 pub mod drivers;
+pub mod exceptions;
 pub mod gdt;
+pub mod idt;
 pub mod io;
+pub mod paging;
+pub mod pic;
+pub mod syscall;
+pub mod tsc;
+pub mod tss;
+pub mod usermode;
 
 /// Initialize x86 architecture-specific part of the kernel.
 pub fn init() {
+    paging::select_mode();
+    log::success!("Selected paging mode");
+
     gdt::init();
     log::success!("Initialized Global Descriptor Table (GDT)");
 
+    tss::init();
+    log::success!("Initialized Task State Segment (TSS)");
+
+    idt::init();
+    log::success!("Initialized Interrupt Descriptor Table (IDT)");
+
+    if syscall::init() {
+        log::success!("Enabled sysenter system call entry");
+    }
+
+    pic::init();
+    log::success!("Remapped 8259 Programmable Interrupt Controller (PIC)");
+
+    drivers::pit::init(hal::timer::requested_tick_rate());
+    log::success!(
+        "Initialized Programmable Interval Timer (PIT) at {} Hz",
+        drivers::pit::frequency()
+    );
+
     drivers::keyboard::init();
     log::success!("Initialized PS/2 keyboard driver");
+
+    drivers::uart::init_irq();
+    log::success!("Enabled UART receive interrupts");
+
+    hal::irq::enable();
+    log::success!("Enabled hardware interrupts");
+}
+
+/// Initialize x86 architecture-specific part of the kernel that requires
+/// memory management.
+pub fn init_late() {
+    hal::irq::without_interrupts(|| {
+        if apic::init() {
+            // Move IRQ lines unmasked on PIC to I/O APIC.
+            hal::irq::unmask_registered();
+        }
+    });
+
+    let controller = interrupt_controller().name();
+    log::success!("Using {} interrupt controller", controller);
+
+    if drivers::hpet::init() {
+        log::debug!("Found High Precision Event Timer (HPET)");
+    }
+
+    if let Some(frequency) = tsc::calibrate() {
+        log::debug!("Calibrated TSC frequency: {} kHz", frequency / 1000);
+    }
+
+    if let Some(source) = hal::clocksource::select(drivers::pit::elapsed_ns) {
+        log::success!("Using {} clocksource", source.name());
+    }
+}
+
+/// Set kernel stack used on entry from user mode by interrupt or `sysenter`.
+///
+/// # Parameters
+/// - `top` - given top of the kernel stack of the running thread.
+pub fn set_kernel_stack(top: u32) {
+    tss::set_kernel_stack(top);
+    syscall::set_sysenter_stack(top);
+}
+
+/// All x86 clocksources.
+static CLOCKSOURCES: [&dyn Clocksource; 3] =
+    [&drivers::pit::Pit, &drivers::hpet::Hpet, &tsc::Tsc];
+
+/// Get all x86 clocksources.
+///
+/// # Returns
+/// - PIT, HPET & TSC clocksources.
+pub fn clocksources() -> &'static [&'static dyn Clocksource] {
+    &CLOCKSOURCES
+}
+
+/// Get interrupt controller in use.
+///
+/// # Returns
+/// - APIC     - if it is enabled.
+/// - 8259 PIC - otherwise.
+pub fn interrupt_controller() -> &'static dyn InterruptController {
+    if apic::is_enabled() {
+        &apic::Apic
+    } else {
+        &pic::Pic
+    }
 }
@@ -0,0 +1,224 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Date: 2025-06-13
+// Author: Alexander Kuzin <alkuzindev@gmail.com>.
+
+//! Legacy 32-bit paging.
+//!
+//! # Description
+//! Virtual address is translated through two levels of paging structures.
+//! Page directory holds 1024 entries, each pointing to a page table of 1024
+//! entries mapping 4 KiB pages. Physical addresses are limited to 4 GiB and
+//! pages can not be marked as non-executable.
+
+use super::{Format, alloc_table};
+use crate::{
+    hal::mmu::{MapError, MapFlags, PAGE_SIZE},
+    kernel::mm::{self, PhysAddr, frame},
+};
+
+/// Number of entries in single paging structure.
+const ENTRIES: usize = 1024;
+
+/// Entry maps page (or page table) that is present in memory.
+const PRESENT: u32 = 1 << 0;
+
+/// Entry allows writes.
+const WRITABLE: u32 = 1 << 1;
+
+/// Entry allows user mode access.
+const USER: u32 = 1 << 2;
+
+/// Entry uses write-through caching.
+const WRITE_THROUGH: u32 = 1 << 3;
+
+/// Entry disables caching.
+const CACHE_DISABLE: u32 = 1 << 4;
+
+/// Entry physical address mask.
+const ADDR_MASK: u32 = 0xFFFFF000;
+
+/// Paging structure (page directory or page table).
+type Table = [u32; ENTRIES];
+
+/// Get page directory index of virtual address.
+///
+/// # Parameters
+/// - `vaddr` - given virtual address.
+///
+/// # Returns
+/// - Page directory entry index.
+#[inline(always)]
+const fn pd_index(vaddr: usize) -> usize {
+    (vaddr >> 22) & (ENTRIES - 1)
+}
+
+/// Get page table index of virtual address.
+///
+/// # Parameters
+/// - `vaddr` - given virtual address.
+///
+/// # Returns
+/// - Page table entry index.
+#[inline(always)]
+const fn pt_index(vaddr: usize) -> usize {
+    (vaddr >> 12) & (ENTRIES - 1)
+}
+
+/// Get paging structure by its physical address.
+///
+/// # Parameters
+/// - `paddr` - given physical address of paging structure.
+///
+/// # Returns
+/// - Mutable reference to paging structure.
+#[inline(always)]
+fn table<'a>(paddr: PhysAddr) -> &'a mut Table {
+    unsafe { &mut *(mm::phys_to_virt(paddr) as *mut Table) }
+}
+
+/// Convert architecture-independent mapping flags to page entry flags.
+///
+/// # Parameters
+/// - `flags` - given page mapping flags.
+///
+/// # Returns
+/// - Page table entry flags.
+fn entry_flags(flags: MapFlags) -> u32 {
+    let mut entry = PRESENT;
+
+    if flags.contains(MapFlags::WRITABLE) {
+        entry |= WRITABLE;
+    }
+
+    if flags.contains(MapFlags::USER) {
+        entry |= USER;
+    }
+
+    if flags.contains(MapFlags::NO_CACHE) {
+        entry |= CACHE_DISABLE | WRITE_THROUGH;
+    } else if flags.contains(MapFlags::WRITE_COMBINE) {
+        // PAT entry selected by write-through bit alone is write-combining.
+        entry |= if super::is_write_combining_enabled() {
+            WRITE_THROUGH
+        } else {
+            CACHE_DISABLE | WRITE_THROUGH
+        };
+    }
+
+    entry
+}
+
+/// Get page table entry of virtual address.
+///
+/// # Parameters
+/// - `root`  - given physical address of page directory.
+/// - `vaddr` - given virtual address.
+///
+/// # Returns
+/// - Present page table entry - in case of success.
+/// - `None`                   - if address is not mapped.
+fn entry<'a>(root: PhysAddr, vaddr: usize) -> Option<&'a mut u32> {
+    let pde = table(root)[pd_index(vaddr)];
+
+    if (pde & PRESENT) == 0 {
+        return None;
+    }
+
+    let pte = &mut table((pde & ADDR_MASK) as PhysAddr)[pt_index(vaddr)];
+    ((*pte & PRESENT) != 0).then_some(pte)
+}
+
+/// Legacy 32-bit paging format.
+pub struct Legacy;
+
+impl Format for Legacy {
+    fn init_kernel(root: PhysAddr, kernel_base: usize) -> Result<(), MapError> {
+        for pde in &mut table(root)[pd_index(kernel_base)..] {
+            let pt = alloc_table().ok_or(MapError::NoMemory)?;
+            *pde = pt as u32 | PRESENT | WRITABLE;
+        }
+
+        Ok(())
+    }
+
+    fn map_kernel_tables(
+        root: PhysAddr,
+        kernel_base: usize,
+    ) -> Result<(), MapError> {
+        Self::map(root, mm::phys_to_virt(root), root, MapFlags::WRITABLE)?;
+
+        for index in pd_index(kernel_base)..ENTRIES {
+            let pt = (table(root)[index] & ADDR_MASK) as PhysAddr;
+            Self::map(root, mm::phys_to_virt(pt), pt, MapFlags::WRITABLE)?;
+        }
+
+        Ok(())
+    }
+
+    fn share_kernel(root: PhysAddr, kernel: PhysAddr, kernel_base: usize) {
+        let start = pd_index(kernel_base);
+        table(root)[start..].copy_from_slice(&table(kernel)[start..]);
+    }
+
+    fn map(
+        root: PhysAddr,
+        vaddr: usize,
+        paddr: PhysAddr,
+        flags: MapFlags,
+    ) -> Result<(), MapError> {
+        let pde = &mut table(root)[pd_index(vaddr)];
+
+        if (*pde & PRESENT) == 0 {
+            let pt = alloc_table().ok_or(MapError::NoMemory)?;
+            *pde = pt as u32 | PRESENT | WRITABLE;
+        }
+
+        // Access rights of page table entry are limited by directory entry.
+        if flags.contains(MapFlags::USER) {
+            *pde |= USER;
+        }
+
+        let pte = &mut table((*pde & ADDR_MASK) as PhysAddr)[pt_index(vaddr)];
+
+        if (*pte & PRESENT) != 0 {
+            return Err(MapError::AlreadyMapped);
+        }
+
+        *pte = paddr as u32 | entry_flags(flags);
+        Ok(())
+    }
+
+    fn unmap(root: PhysAddr, vaddr: usize) -> Option<PhysAddr> {
+        let pte = entry(root, vaddr)?;
+        let paddr = (*pte & ADDR_MASK) as PhysAddr;
+
+        *pte = 0;
+        Some(paddr)
+    }
+
+    fn protect(root: PhysAddr, vaddr: usize, flags: MapFlags) -> Option<()> {
+        if flags.contains(MapFlags::USER) {
+            table(root)[pd_index(vaddr)] |= USER;
+        }
+
+        let pte = entry(root, vaddr)?;
+        *pte = (*pte & ADDR_MASK) | entry_flags(flags);
+        Some(())
+    }
+
+    fn translate(root: PhysAddr, vaddr: usize) -> Option<PhysAddr> {
+        let pte = entry(root, vaddr)?;
+        let offset = (vaddr & (PAGE_SIZE - 1)) as PhysAddr;
+
+        Some((*pte & ADDR_MASK) as PhysAddr + offset)
+    }
+
+    fn release(root: PhysAddr, kernel_base: usize) {
+        for pde in &mut table(root)[..pd_index(kernel_base)] {
+            if (*pde & PRESENT) != 0 {
+                frame::free_direct((*pde & ADDR_MASK) as PhysAddr, 1);
+                *pde = 0;
+            }
+        }
+    }
+}
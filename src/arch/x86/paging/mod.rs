@@ -0,0 +1,732 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Date: 2025-06-13
+// Author: Alexander Kuzin <alkuzindev@gmail.com>.
+
+//! Paging module.
+//!
+//! # Description
+//! Two formats of paging structures are supported:
+//! - legacy 32-bit paging with two levels of 1024 entry tables,
+//! - PAE paging with three levels of 512 entry tables, which can address
+//!   physical memory above 4 GiB & mark pages as non-executable (NX).
+//!
+//! Format is selected once at boot: PAE is used when CPU supports it.
+//! Paging structures are mapped at their direct virtual addresses, so that
+//! the kernel can access them through `phys_to_virt`. All page tables of
+//! the kernel part are allocated in advance, therefore frames are mapped at
+//! their direct addresses without allocating paging structures & taking
+//! kernel page directory lock.
+
+mod legacy;
+mod pae;
+
+use super::{cpu, tss};
+use crate::{
+    hal::mmu::{MapError, MapFlags, PAGE_SIZE, PageDirectoryInterface},
+    kernel::mm::{self, PhysAddr, frame},
+    log,
+};
+use core::{
+    arch::asm,
+    ptr,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+};
+use legacy::Legacy;
+use pae::Pae;
+use spin::Mutex;
+
+/// CR0 bit forbidding supervisor writes to read-only pages.
+const CR0_WP: usize = 1 << 16;
+
+/// Page attribute table MSR.
+const IA32_PAT: u32 = 0x277;
+
+/// PAT memory type: write-combining.
+const PAT_WRITE_COMBINING: u64 = 0x01;
+
+/// Physical address limit of legacy 32-bit paging.
+const LEGACY_PHYS_LIMIT: PhysAddr = 1 << 32;
+
+/// Whether PAE paging format is used.
+static PAE_ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Whether non-executable pages are supported.
+static NX_ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Whether write-combining memory type is available through PAT.
+static PAT_ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Start of the kernel part of address space.
+static KERNEL_SPACE_BASE: AtomicUsize = AtomicUsize::new(usize::MAX);
+
+/// Physical address of kernel root table (0 until it is created). Root
+/// table is allocated below `DIRECT_MAP_LIMIT`, so it fits into `usize`.
+static KERNEL_ROOT: AtomicUsize = AtomicUsize::new(0);
+
+/// Paging structures format.
+trait Format {
+    /// Allocate paging structures of the kernel part in advance, so that
+    /// all page directories share them.
+    ///
+    /// # Parameters
+    /// - `root`        - given physical address of kernel root table.
+    /// - `kernel_base` - given start of the kernel part of address space.
+    ///
+    /// # Returns
+    /// - `Ok`  - in case of success.
+    /// - `Err` - otherwise.
+    fn init_kernel(root: PhysAddr, kernel_base: usize) -> Result<(), MapError>;
+
+    /// Map root table & paging structures of the kernel part at their
+    /// direct virtual addresses.
+    ///
+    /// # Parameters
+    /// - `root`        - given physical address of kernel root table.
+    /// - `kernel_base` - given start of the kernel part of address space.
+    ///
+    /// # Returns
+    /// - `Ok`  - in case of success.
+    /// - `Err` - otherwise.
+    fn map_kernel_tables(
+        root: PhysAddr,
+        kernel_base: usize,
+    ) -> Result<(), MapError>;
+
+    /// Share kernel part of address space with new root table.
+    ///
+    /// # Parameters
+    /// - `root`        - given physical address of new root table.
+    /// - `kernel`      - given physical address of kernel root table.
+    /// - `kernel_base` - given start of the kernel part of address space.
+    fn share_kernel(root: PhysAddr, kernel: PhysAddr, kernel_base: usize);
+
+    /// Map virtual page to physical frame.
+    ///
+    /// # Parameters
+    /// - `root`  - given physical address of root table.
+    /// - `vaddr` - given page aligned virtual address.
+    /// - `paddr` - given page aligned physical address.
+    /// - `flags` - given page mapping flags.
+    ///
+    /// # Returns
+    /// - `Ok`  - in case of success.
+    /// - `Err` - otherwise.
+    fn map(
+        root: PhysAddr,
+        vaddr: usize,
+        paddr: PhysAddr,
+        flags: MapFlags,
+    ) -> Result<(), MapError>;
+
+    /// Unmap virtual page. Paging structures are kept even if they become
+    /// empty.
+    ///
+    /// # Parameters
+    /// - `root`  - given physical address of root table.
+    /// - `vaddr` - given page aligned virtual address.
+    ///
+    /// # Returns
+    /// - Physical address the page was mapped to - in case of success.
+    /// - `None`                                  - if page was not mapped.
+    fn unmap(root: PhysAddr, vaddr: usize) -> Option<PhysAddr>;
+
+    /// Change mapping flags of virtual page.
+    ///
+    /// # Parameters
+    /// - `root`  - given physical address of root table.
+    /// - `vaddr` - given page aligned virtual address.
+    /// - `flags` - given new page mapping flags.
+    ///
+    /// # Returns
+    /// - `Some` - in case of success.
+    /// - `None` - if page was not mapped.
+    fn protect(root: PhysAddr, vaddr: usize, flags: MapFlags) -> Option<()>;
+
+    /// Translate virtual address.
+    ///
+    /// # Parameters
+    /// - `root`  - given physical address of root table.
+    /// - `vaddr` - given virtual address.
+    ///
+    /// # Returns
+    /// - Physical address - in case of success.
+    /// - `None`           - if address is not mapped.
+    fn translate(root: PhysAddr, vaddr: usize) -> Option<PhysAddr>;
+
+    /// Free paging structures of the user part of address space.
+    ///
+    /// # Parameters
+    /// - `root`        - given physical address of root table.
+    /// - `kernel_base` - given start of the kernel part of address space.
+    fn release(root: PhysAddr, kernel_base: usize);
+}
+
+/// Call function of the paging format selected at boot.
+macro_rules! dispatch {
+    ($func:ident($($arg:expr),* $(,)?)) => {
+        if PAE_ENABLED.load(Ordering::Relaxed) {
+            Pae::$func($($arg),*)
+        } else {
+            Legacy::$func($($arg),*)
+        }
+    };
+}
+
+/// Allocate zeroed paging structure mapped at its direct address.
+///
+/// # Returns
+/// - Physical address of paging structure - in case of success.
+/// - `None`                               - otherwise.
+fn alloc_table() -> Option<PhysAddr> {
+    let paddr = frame::alloc_direct(1, 1)?;
+
+    unsafe {
+        ptr::write_bytes(mm::phys_to_virt(paddr) as *mut u8, 0, PAGE_SIZE);
+    }
+
+    Some(paddr)
+}
+
+/// Get start of the kernel part of address space.
+///
+/// # Returns
+/// - Kernel part start virtual address.
+#[inline(always)]
+fn kernel_base() -> usize {
+    KERNEL_SPACE_BASE.load(Ordering::Relaxed)
+}
+
+/// Page directory (root of the paging structures).
+pub struct PageDirectory {
+    /// Physical address of root table (page directory or PAE page
+    /// directory pointer table).
+    paddr: PhysAddr,
+}
+
+impl PageDirectory {
+    /// Construct page directory placeholder without paging structures.
+    ///
+    /// # Returns
+    /// - New empty `PageDirectory` object.
+    pub const fn empty() -> Self {
+        Self { paddr: 0 }
+    }
+
+    /// Get root table physical address.
+    ///
+    /// # Returns
+    /// - Physical address of root table.
+    #[inline(always)]
+    pub fn paddr(&self) -> PhysAddr {
+        self.paddr
+    }
+}
+
+impl PageDirectoryInterface for PageDirectory {
+    /// Construct new page directory sharing kernel part with kernel page
+    /// directory.
+    ///
+    /// # Returns
+    /// - New `PageDirectory` object - in case of success.
+    /// - `None`                     - otherwise.
+    fn new() -> Option<Self> {
+        let directory = Self {
+            paddr: alloc_table()?,
+        };
+
+        let kernel = KERNEL_DIRECTORY.lock();
+        dispatch!(share_kernel(directory.paddr, kernel.paddr, kernel_base()));
+
+        Some(directory)
+    }
+
+    /// Map virtual page to physical frame.
+    ///
+    /// # Parameters
+    /// - `vaddr` - given page aligned virtual address.
+    /// - `paddr` - given page aligned physical address.
+    /// - `flags` - given page mapping flags.
+    ///
+    /// # Returns
+    /// - `Ok`  - in case of success.
+    /// - `Err` - otherwise.
+    fn map(
+        &mut self,
+        vaddr: usize,
+        paddr: PhysAddr,
+        flags: MapFlags,
+    ) -> Result<(), MapError> {
+        if (vaddr & (PAGE_SIZE - 1)) != 0
+            || (paddr & (PAGE_SIZE as PhysAddr - 1)) != 0
+        {
+            return Err(MapError::Unaligned);
+        }
+
+        if paddr >= phys_addr_limit() {
+            return Err(MapError::OutOfRange);
+        }
+
+        dispatch!(map(self.paddr, vaddr, paddr, flags))
+    }
+
+    /// Unmap virtual page. Paging structures are kept even if they become
+    /// empty.
+    ///
+    /// # Parameters
+    /// - `vaddr` - given page aligned virtual address.
+    ///
+    /// # Returns
+    /// - Physical address the page was mapped to - in case of success.
+    /// - `None`                                  - if page was not mapped.
+    fn unmap(&mut self, vaddr: usize) -> Option<PhysAddr> {
+        dispatch!(unmap(self.paddr, vaddr))
+    }
+
+    /// Change mapping flags of virtual page.
+    ///
+    /// # Parameters
+    /// - `vaddr` - given page aligned virtual address.
+    /// - `flags` - given new page mapping flags.
+    ///
+    /// # Returns
+    /// - `Some` - in case of success.
+    /// - `None` - if page was not mapped.
+    fn protect(&mut self, vaddr: usize, flags: MapFlags) -> Option<()> {
+        dispatch!(protect(self.paddr, vaddr, flags))
+    }
+
+    /// Translate virtual address.
+    ///
+    /// # Parameters
+    /// - `vaddr` - given virtual address.
+    ///
+    /// # Returns
+    /// - Physical address - in case of success.
+    /// - `None`           - if address is not mapped.
+    fn translate(&self, vaddr: usize) -> Option<PhysAddr> {
+        dispatch!(translate(self.paddr, vaddr))
+    }
+
+    /// Load root table into CR3 register.
+    ///
+    /// # Safety
+    /// - Currently executed code, stack & kernel data must be mapped.
+    unsafe fn activate(&self) {
+        unsafe {
+            asm!("mov cr3, {}", in(reg) self.paddr as usize);
+        }
+    }
+
+    /// Check whether root table is loaded into CR3 register.
+    ///
+    /// # Returns
+    /// - `true`  - if page directory is active.
+    /// - `false` - otherwise.
+    fn is_active(&self) -> bool {
+        let cr3: usize;
+
+        unsafe {
+            asm!("mov {}, cr3", out(reg) cr3, options(nomem, nostack));
+        }
+
+        cr3 as PhysAddr == self.paddr
+    }
+}
+
+impl Drop for PageDirectory {
+    /// Free root table & paging structures of its user part. Mapped frames
+    /// are owned by the address space and are not freed.
+    fn drop(&mut self) {
+        if self.paddr == 0 {
+            return;
+        }
+
+        dispatch!(release(self.paddr, kernel_base()));
+        frame::free_direct(self.paddr, 1);
+    }
+}
+
+/// Kernel page directory.
+static KERNEL_DIRECTORY: Mutex<PageDirectory> =
+    Mutex::new(PageDirectory::empty());
+
+/// Reprogram PAT entry selected by write-through bit alone (write-through
+/// by default) to write-combining memory type.
+fn init_pat() {
+    if !cpu::is_support_pat() {
+        log::info!("PAT is not supported, write-combining is unavailable");
+        return;
+    }
+
+    let pat = cpu::rdmsr(IA32_PAT);
+    let pat = (pat & !(0xFF << 8)) | (PAT_WRITE_COMBINING << 8);
+
+    unsafe {
+        cpu::wrmsr(IA32_PAT, pat);
+    }
+
+    flush_tlb_all();
+    PAT_ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Select paging format & memory types supported by CPU. Must be called
+/// before physical memory allocators are initialized.
+pub fn select_mode() {
+    init_pat();
+
+    if !cpu::is_support_pae() {
+        log::info!("PAE is not supported, using legacy 32-bit paging");
+        return;
+    }
+
+    let nx = cpu::is_support_nx();
+
+    PAE_ENABLED.store(true, Ordering::Relaxed);
+    NX_ENABLED.store(nx, Ordering::Relaxed);
+
+    log::info!(
+        "Using PAE paging ({}-bit physical addresses, NX {})",
+        cpu::phys_addr_bits(),
+        if nx { "enabled" } else { "not supported" }
+    );
+}
+
+/// Check whether PAE paging format is used.
+///
+/// # Returns
+/// - `true`  - if PAE paging is used.
+/// - `false` - otherwise.
+#[inline(always)]
+pub fn is_pae_enabled() -> bool {
+    PAE_ENABLED.load(Ordering::Relaxed)
+}
+
+/// Check whether non-executable pages are supported.
+///
+/// # Returns
+/// - `true`  - if NX bit is used.
+/// - `false` - otherwise.
+#[inline(always)]
+pub fn is_nx_enabled() -> bool {
+    NX_ENABLED.load(Ordering::Relaxed)
+}
+
+/// Check whether write-combining memory type is available.
+///
+/// # Returns
+/// - `true`  - if PAT provides write-combining memory type.
+/// - `false` - otherwise.
+#[inline(always)]
+pub fn is_write_combining_enabled() -> bool {
+    PAT_ENABLED.load(Ordering::Relaxed)
+}
+
+/// Get end of physical memory addressable by paging structures.
+///
+/// # Returns
+/// - Physical address limit (exclusive).
+pub fn phys_addr_limit() -> PhysAddr {
+    if is_pae_enabled() {
+        1 << cpu::phys_addr_bits()
+    } else {
+        LEGACY_PHYS_LIMIT
+    }
+}
+
+/// Map virtual page to physical frame in kernel page directory.
+///
+/// # Parameters
+/// - `vaddr` - given page aligned virtual address.
+/// - `paddr` - given page aligned physical address.
+/// - `flags` - given page mapping flags.
+///
+/// # Returns
+/// - `Ok`  - in case of success.
+/// - `Err` - otherwise.
+pub fn map(
+    vaddr: usize,
+    paddr: PhysAddr,
+    flags: MapFlags,
+) -> Result<(), MapError> {
+    KERNEL_DIRECTORY.lock().map(vaddr, paddr, flags)
+}
+
+/// Unmap virtual page from kernel page directory & invalidate its TLB entry.
+///
+/// # Parameters
+/// - `vaddr` - given page aligned virtual address.
+///
+/// # Returns
+/// - Physical address the page was mapped to - in case of success.
+/// - `None`                                  - if page was not mapped.
+pub fn unmap(vaddr: usize) -> Option<PhysAddr> {
+    let paddr = KERNEL_DIRECTORY.lock().unmap(vaddr)?;
+    flush_tlb(vaddr);
+    Some(paddr)
+}
+
+/// Map physical frame at its direct virtual address in kernel page
+/// directory. Boot page directory maps all directly accessible memory, so
+/// nothing is done until kernel page directory is created.
+///
+/// # Parameters
+/// - `paddr` - given physical address of the frame below
+///   `DIRECT_MAP_LIMIT`.
+///
+/// # Returns
+/// - `Ok`  - in case of success.
+/// - `Err` - otherwise.
+pub fn map_direct(paddr: PhysAddr) -> Result<(), MapError> {
+    let root = KERNEL_ROOT.load(Ordering::Relaxed) as PhysAddr;
+
+    if root == 0 {
+        return Ok(());
+    }
+
+    // Kernel page tables exist, so only the frame own entry is written.
+    let vaddr = mm::phys_to_virt(paddr);
+    dispatch!(map(root, vaddr, paddr, MapFlags::WRITABLE))
+}
+
+/// Unmap physical frame from its direct virtual address in kernel page
+/// directory & invalidate its TLB entry.
+///
+/// # Parameters
+/// - `paddr` - given physical address of the frame below
+///   `DIRECT_MAP_LIMIT`.
+pub fn unmap_direct(paddr: PhysAddr) {
+    let root = KERNEL_ROOT.load(Ordering::Relaxed) as PhysAddr;
+
+    if root == 0 {
+        return;
+    }
+
+    let vaddr = mm::phys_to_virt(paddr);
+
+    if dispatch!(unmap(root, vaddr)).is_some() {
+        flush_tlb(vaddr);
+    }
+}
+
+/// Translate virtual address using kernel page directory.
+///
+/// # Parameters
+/// - `vaddr` - given virtual address.
+///
+/// # Returns
+/// - Physical address - in case of success.
+/// - `None`           - if address is not mapped.
+pub fn translate(vaddr: usize) -> Option<PhysAddr> {
+    KERNEL_DIRECTORY.lock().translate(vaddr)
+}
+
+/// Invalidate TLB entry of single page.
+///
+/// # Parameters
+/// - `vaddr` - given virtual address of the page.
+#[inline(always)]
+pub fn flush_tlb(vaddr: usize) {
+    unsafe {
+        asm!("invlpg [{}]", in(reg) vaddr, options(nostack));
+    }
+}
+
+/// Invalidate all (non-global) TLB entries by reloading CR3 register.
+#[inline(always)]
+pub fn flush_tlb_all() {
+    unsafe {
+        asm!(
+            "mov {0}, cr3",
+            "mov cr3, {0}",
+            out(reg) _,
+            options(nostack),
+        );
+    }
+}
+
+/// Make supervisor writes to read-only pages fault.
+fn enable_write_protect() {
+    unsafe {
+        asm!(
+            "mov {0}, cr0",
+            "or {0}, {1}",
+            "mov cr0, {0}",
+            out(reg) _,
+            const CR0_WP,
+            options(nostack),
+        );
+    }
+}
+
+/// Create kernel page directory.
+///
+/// # Parameters
+/// - `kernel_base` - given start of the kernel part of address space.
+///
+/// # Returns
+/// - `Ok`  - in case of success.
+/// - `Err` - otherwise.
+pub fn init(kernel_base: usize) -> Result<(), MapError> {
+    let directory = PageDirectory {
+        paddr: alloc_table().ok_or(MapError::NoMemory)?,
+    };
+
+    dispatch!(init_kernel(directory.paddr, kernel_base))?;
+    dispatch!(map_kernel_tables(directory.paddr, kernel_base))?;
+
+    KERNEL_SPACE_BASE.store(kernel_base, Ordering::Relaxed);
+    KERNEL_ROOT.store(directory.paddr as usize, Ordering::Relaxed);
+    *KERNEL_DIRECTORY.lock() = directory;
+    Ok(())
+}
+
+/// Switch to kernel page directory & enable write protection of read-only
+/// kernel sections. Boot page directory identity mapping is not accessible
+/// after that.
+pub fn activate() {
+    let directory = KERNEL_DIRECTORY.lock();
+
+    if is_pae_enabled() {
+        // Paging format is changed with paging temporarily disabled.
+        pae::enable(directory.paddr, is_nx_enabled());
+    } else {
+        unsafe {
+            directory.activate();
+        }
+    }
+
+    // Double fault task must not depend on boot page directory.
+    tss::set_double_fault_cr3(directory.paddr as u32);
+    enable_write_protect();
+}
+
+/// Switch back to kernel page directory (after `activate` was done once at
+/// boot). Paging format is not changed.
+pub fn activate_kernel() {
+    let directory = KERNEL_DIRECTORY.lock();
+
+    unsafe {
+        directory.activate();
+    }
+
+    flush_tlb_all();
+}
+
+#[cfg(feature = "ktest")]
+pub mod tests {
+    use super::*;
+    use crate::{arch::x86::exceptions, hal::mmu, ktest::TestCase};
+
+    /// Paging unit tests.
+    pub const TESTS: &[TestCase] = &[
+        TestCase::new("null_is_unmapped", null_is_unmapped),
+        TestCase::new("map_translate_unmap", map_translate_unmap),
+        TestCase::new("map_rejects_unaligned", map_rejects_unaligned),
+        TestCase::new("map_range_rollback", map_range_rollback),
+        TestCase::new("high_frame_mapping", high_frame_mapping),
+        TestCase::new("data_is_not_executable", data_is_not_executable),
+        TestCase::new("rodata_write_faults", rodata_write_faults),
+        TestCase::new("text_write_faults", text_write_faults),
+    ];
+
+    /// Virtual address of the scratch test page.
+    const TEST_VADDR: usize = 0x40000000;
+
+    /// Physical address above 4 GiB.
+    const HIGH_PADDR: PhysAddr = 0x1_2345_6000;
+
+    /// Immutable data placed in `.rodata` section.
+    static RODATA: u32 = 0xC0FFEE;
+
+    /// Mutable data placed in `.data` section.
+    static DATA: Mutex<u32> = Mutex::new(0xC0FFEE);
+
+    fn null_is_unmapped() {
+        assert_eq!(translate(0), None);
+        assert_eq!(translate(PAGE_SIZE), None);
+    }
+
+    fn map_translate_unmap() {
+        let paddr = frame::alloc_direct(1, 1).unwrap();
+
+        assert_eq!(map(TEST_VADDR, paddr, MapFlags::WRITABLE), Ok(()));
+        assert_eq!(
+            map(TEST_VADDR, paddr, MapFlags::WRITABLE),
+            Err(MapError::AlreadyMapped)
+        );
+        assert_eq!(translate(TEST_VADDR + 0x123), Some(paddr + 0x123));
+
+        // Write through new mapping & read through direct mapping.
+        unsafe {
+            (TEST_VADDR as *mut u32).write_volatile(0xDEADBEEF);
+            let direct = mm::phys_to_virt(paddr) as *const u32;
+            assert_eq!(direct.read_volatile(), 0xDEADBEEF);
+        }
+
+        assert_eq!(unmap(TEST_VADDR), Some(paddr));
+        assert_eq!(translate(TEST_VADDR), None);
+        assert_eq!(unmap(TEST_VADDR), None);
+
+        frame::free_direct(paddr, 1);
+    }
+
+    fn map_rejects_unaligned() {
+        let flags = MapFlags::READ;
+
+        assert_eq!(map(TEST_VADDR + 1, 0, flags), Err(MapError::Unaligned));
+        assert_eq!(map(TEST_VADDR, 1, flags), Err(MapError::Unaligned));
+    }
+
+    fn map_range_rollback() {
+        let last = TEST_VADDR + 2 * PAGE_SIZE;
+        assert_eq!(map(last, 0, MapFlags::READ), Ok(()));
+
+        // Range overlapping mapped page is not mapped at all.
+        assert_eq!(
+            mmu::map_range(TEST_VADDR, 0, 3 * PAGE_SIZE, MapFlags::READ),
+            Err(MapError::AlreadyMapped)
+        );
+        assert_eq!(translate(TEST_VADDR), None);
+        assert_eq!(translate(TEST_VADDR + PAGE_SIZE), None);
+
+        assert_eq!(unmap(last), Some(0));
+    }
+
+    fn high_frame_mapping() {
+        let result = map(TEST_VADDR, HIGH_PADDR, MapFlags::READ);
+
+        if !is_pae_enabled() {
+            assert_eq!(result, Err(MapError::OutOfRange));
+            return;
+        }
+
+        // Mapping is never accessed, so the frame does not have to exist.
+        assert_eq!(result, Ok(()));
+        assert_eq!(translate(TEST_VADDR + 0x10), Some(HIGH_PADDR + 0x10));
+        assert_eq!(unmap(TEST_VADDR), Some(HIGH_PADDR));
+    }
+
+    fn data_is_not_executable() {
+        if !is_nx_enabled() {
+            return;
+        }
+
+        let root = KERNEL_DIRECTORY.lock().paddr;
+        let code = data_is_not_executable as *const () as usize;
+        let data = &raw const KERNEL_DIRECTORY as usize;
+
+        assert_eq!(pae::is_executable(root, code), Some(true));
+        assert_eq!(pae::is_executable(root, data), Some(false));
+    }
+
+    fn rodata_write_faults() {
+        assert!(!exceptions::probe_write(&raw const RODATA as usize));
+        assert!(exceptions::probe_write(&raw const DATA as usize));
+        assert_eq!(*DATA.lock(), 0xC0FFEE);
+    }
+
+    fn text_write_faults() {
+        let code = text_write_faults as *const () as usize;
+        assert!(!exceptions::probe_write(code));
+    }
+}
@@ -0,0 +1,385 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Date: 2025-06-13
+// Author: Alexander Kuzin <alkuzindev@gmail.com>.
+
+//! PAE (Physical Address Extension) paging.
+//!
+//! # Description
+//! Virtual address is translated through three levels of paging structures
+//! with 64-bit entries. Page directory pointer table holds 4 entries, each
+//! covering 1 GiB by page directory of 512 entries, each pointing to page
+//! table of 512 entries mapping 4 KiB pages. Entries can address physical
+//! memory above 4 GiB & mark pages as non-executable (NX).
+//!
+//! Kernel part of address space is covered by its own page directories,
+//! which are shared by all page directory pointer tables.
+
+use super::{Format, alloc_table, flush_tlb, flush_tlb_all, kernel_base};
+use crate::{
+    hal::mmu::{MapError, MapFlags, PAGE_SIZE},
+    kernel::mm::{self, KERNEL_BASE, PhysAddr, frame},
+};
+use core::arch::asm;
+
+/// Number of entries in page directory or page table.
+const ENTRIES: usize = 512;
+
+/// Number of entries in page directory pointer table.
+const PDPT_ENTRIES: usize = 4;
+
+/// Entry maps page (or paging structure) that is present in memory.
+const PRESENT: u64 = 1 << 0;
+
+/// Entry allows writes.
+const WRITABLE: u64 = 1 << 1;
+
+/// Entry allows user mode access.
+const USER: u64 = 1 << 2;
+
+/// Entry uses write-through caching.
+const WRITE_THROUGH: u64 = 1 << 3;
+
+/// Entry disables caching.
+const CACHE_DISABLE: u64 = 1 << 4;
+
+/// Entry forbids instruction fetches (requires NX support).
+const NO_EXECUTE: u64 = 1 << 63;
+
+/// Entry physical address mask.
+const ADDR_MASK: u64 = 0x000FFFFFFFFFF000;
+
+/// Paging structure (page directory pointer table, page directory or page
+/// table).
+type Table = [u64; ENTRIES];
+
+/// Number of trampoline pages identity mapped while switching to PAE.
+const TRAMPOLINE_PAGES: usize = 2;
+
+unsafe extern "C" {
+    /// Switch CPU from legacy to PAE paging.
+    ///
+    /// # Parameters
+    /// - `pdpt` - given physical address of page directory pointer table.
+    /// - `nx`   - given non-zero value to enable non-executable pages.
+    fn pae_enable(pdpt: u32, nx: u32);
+}
+
+/// Get page directory pointer table index of virtual address.
+///
+/// # Parameters
+/// - `vaddr` - given virtual address.
+///
+/// # Returns
+/// - Page directory pointer table entry index.
+#[inline(always)]
+const fn pdpt_index(vaddr: usize) -> usize {
+    (vaddr >> 30) & (PDPT_ENTRIES - 1)
+}
+
+/// Get page directory index of virtual address.
+///
+/// # Parameters
+/// - `vaddr` - given virtual address.
+///
+/// # Returns
+/// - Page directory entry index.
+#[inline(always)]
+const fn pd_index(vaddr: usize) -> usize {
+    (vaddr >> 21) & (ENTRIES - 1)
+}
+
+/// Get page table index of virtual address.
+///
+/// # Parameters
+/// - `vaddr` - given virtual address.
+///
+/// # Returns
+/// - Page table entry index.
+#[inline(always)]
+const fn pt_index(vaddr: usize) -> usize {
+    (vaddr >> 12) & (ENTRIES - 1)
+}
+
+/// Get paging structure by its physical address.
+///
+/// # Parameters
+/// - `paddr` - given physical address of paging structure.
+///
+/// # Returns
+/// - Mutable reference to paging structure.
+#[inline(always)]
+fn table<'a>(paddr: PhysAddr) -> &'a mut Table {
+    unsafe { &mut *(mm::phys_to_virt(paddr) as *mut Table) }
+}
+
+/// Convert architecture-independent mapping flags to page entry flags.
+///
+/// # Parameters
+/// - `flags` - given page mapping flags.
+///
+/// # Returns
+/// - Page table entry flags.
+fn entry_flags(flags: MapFlags) -> u64 {
+    let mut entry = PRESENT;
+
+    if flags.contains(MapFlags::WRITABLE) {
+        entry |= WRITABLE;
+    }
+
+    if flags.contains(MapFlags::USER) {
+        entry |= USER;
+    }
+
+    if flags.contains(MapFlags::NO_CACHE) {
+        entry |= CACHE_DISABLE | WRITE_THROUGH;
+    } else if flags.contains(MapFlags::WRITE_COMBINE) {
+        // PAT entry selected by write-through bit alone is write-combining.
+        entry |= if super::is_write_combining_enabled() {
+            WRITE_THROUGH
+        } else {
+            CACHE_DISABLE | WRITE_THROUGH
+        };
+    }
+
+    if !flags.contains(MapFlags::EXECUTABLE) && super::is_nx_enabled() {
+        entry |= NO_EXECUTE;
+    }
+
+    entry
+}
+
+/// Get page directory entry of virtual address.
+///
+/// # Parameters
+/// - `root`  - given physical address of page directory pointer table.
+/// - `vaddr` - given virtual address.
+///
+/// # Returns
+/// - Page directory entry - in case of success.
+/// - `None`               - if page directory is not present.
+fn pd_entry<'a>(root: PhysAddr, vaddr: usize) -> Option<&'a mut u64> {
+    let pdpte = table(root)[pdpt_index(vaddr)];
+
+    if (pdpte & PRESENT) == 0 {
+        return None;
+    }
+
+    Some(&mut table(pdpte & ADDR_MASK)[pd_index(vaddr)])
+}
+
+/// Get page table entry of virtual address.
+///
+/// # Parameters
+/// - `root`  - given physical address of page directory pointer table.
+/// - `vaddr` - given virtual address.
+///
+/// # Returns
+/// - Present page table entry - in case of success.
+/// - `None`                   - if address is not mapped.
+fn entry<'a>(root: PhysAddr, vaddr: usize) -> Option<&'a mut u64> {
+    let pde = *pd_entry(root, vaddr)?;
+
+    if (pde & PRESENT) == 0 {
+        return None;
+    }
+
+    let pte = &mut table(pde & ADDR_MASK)[pt_index(vaddr)];
+    ((*pte & PRESENT) != 0).then_some(pte)
+}
+
+/// Check whether page directory pointer table is loaded into CR3 register.
+///
+/// # Parameters
+/// - `root` - given physical address of page directory pointer table.
+///
+/// # Returns
+/// - `true`  - if page directory pointer table is active.
+/// - `false` - otherwise.
+fn is_active(root: PhysAddr) -> bool {
+    let cr3: usize;
+
+    unsafe {
+        asm!("mov {}, cr3", out(reg) cr3, options(nomem, nostack));
+    }
+
+    cr3 as PhysAddr == root
+}
+
+/// Check whether mapped page is executable.
+///
+/// # Parameters
+/// - `root`  - given physical address of page directory pointer table.
+/// - `vaddr` - given virtual address.
+///
+/// # Returns
+/// - Whether page is executable - in case of success.
+/// - `None`                     - if address is not mapped.
+#[cfg(feature = "ktest")]
+pub fn is_executable(root: PhysAddr, vaddr: usize) -> Option<bool> {
+    entry(root, vaddr).map(|pte| (*pte & NO_EXECUTE) == 0)
+}
+
+/// Switch CPU to PAE paging with given page directory pointer table.
+///
+/// # Parameters
+/// - `root` - given physical address of kernel page directory pointer table.
+/// - `nx`   - given whether to enable non-executable pages.
+pub fn enable(root: PhysAddr, nx: bool) {
+    // Paging is re-enabled while executing at physical address of the
+    // trampoline, so it must be identity mapped by the new tables.
+    let start =
+        (pae_enable as *const () as usize - KERNEL_BASE) & !(PAGE_SIZE - 1);
+    let trampoline = start..start + TRAMPOLINE_PAGES * PAGE_SIZE;
+
+    for vaddr in trampoline.clone().step_by(PAGE_SIZE) {
+        let flags = MapFlags::READ | MapFlags::EXECUTABLE;
+
+        if let Err(err) = Pae::map(root, vaddr, vaddr as PhysAddr, flags) {
+            panic!("Failed to map PAE trampoline: {}", err);
+        }
+    }
+
+    unsafe {
+        pae_enable(root as u32, nx as u32);
+    }
+
+    for vaddr in trampoline.step_by(PAGE_SIZE) {
+        Pae::unmap(root, vaddr);
+        flush_tlb(vaddr);
+    }
+
+    // Page directory pointers are cached by CPU until CR3 is reloaded.
+    Pae::release(root, kernel_base());
+    flush_tlb_all();
+}
+
+/// PAE paging format.
+pub struct Pae;
+
+impl Format for Pae {
+    fn init_kernel(root: PhysAddr, kernel_base: usize) -> Result<(), MapError> {
+        for pdpte in &mut table(root)[pdpt_index(kernel_base)..PDPT_ENTRIES] {
+            let pd = alloc_table().ok_or(MapError::NoMemory)?;
+            *pdpte = pd | PRESENT;
+
+            for pde in table(pd).iter_mut() {
+                let pt = alloc_table().ok_or(MapError::NoMemory)?;
+                *pde = pt | PRESENT | WRITABLE;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn map_kernel_tables(
+        root: PhysAddr,
+        kernel_base: usize,
+    ) -> Result<(), MapError> {
+        let flags = MapFlags::WRITABLE;
+        Self::map(root, mm::phys_to_virt(root), root, flags)?;
+
+        for index in pdpt_index(kernel_base)..PDPT_ENTRIES {
+            let pd = table(root)[index] & ADDR_MASK;
+            Self::map(root, mm::phys_to_virt(pd), pd, flags)?;
+
+            for entry in 0..ENTRIES {
+                let pt = table(pd)[entry] & ADDR_MASK;
+                Self::map(root, mm::phys_to_virt(pt), pt, flags)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn share_kernel(root: PhysAddr, kernel: PhysAddr, kernel_base: usize) {
+        let range = pdpt_index(kernel_base)..PDPT_ENTRIES;
+        table(root)[range.clone()].copy_from_slice(&table(kernel)[range]);
+    }
+
+    fn map(
+        root: PhysAddr,
+        vaddr: usize,
+        paddr: PhysAddr,
+        flags: MapFlags,
+    ) -> Result<(), MapError> {
+        let pdpte = &mut table(root)[pdpt_index(vaddr)];
+
+        // Only present bit & caching bits are allowed in PDPT entries.
+        if (*pdpte & PRESENT) == 0 {
+            let pd = alloc_table().ok_or(MapError::NoMemory)?;
+            *pdpte = pd | PRESENT;
+
+            // Page directory pointers are cached by CPU until CR3 is
+            // reloaded.
+            if is_active(root) {
+                flush_tlb_all();
+            }
+        }
+
+        let pde = &mut table(*pdpte & ADDR_MASK)[pd_index(vaddr)];
+
+        if (*pde & PRESENT) == 0 {
+            let pt = alloc_table().ok_or(MapError::NoMemory)?;
+            *pde = pt | PRESENT | WRITABLE;
+        }
+
+        // Access rights of page table entry are limited by directory entry.
+        if flags.contains(MapFlags::USER) {
+            *pde |= USER;
+        }
+
+        let pte = &mut table(*pde & ADDR_MASK)[pt_index(vaddr)];
+
+        if (*pte & PRESENT) != 0 {
+            return Err(MapError::AlreadyMapped);
+        }
+
+        *pte = paddr | entry_flags(flags);
+        Ok(())
+    }
+
+    fn unmap(root: PhysAddr, vaddr: usize) -> Option<PhysAddr> {
+        let pte = entry(root, vaddr)?;
+        let paddr = *pte & ADDR_MASK;
+
+        *pte = 0;
+        Some(paddr)
+    }
+
+    fn protect(root: PhysAddr, vaddr: usize, flags: MapFlags) -> Option<()> {
+        let pte = entry(root, vaddr)?;
+        *pte = (*pte & ADDR_MASK) | entry_flags(flags);
+
+        if flags.contains(MapFlags::USER) {
+            *pd_entry(root, vaddr)? |= USER;
+        }
+
+        Some(())
+    }
+
+    fn translate(root: PhysAddr, vaddr: usize) -> Option<PhysAddr> {
+        let pte = entry(root, vaddr)?;
+        let offset = (vaddr & (PAGE_SIZE - 1)) as PhysAddr;
+
+        Some((*pte & ADDR_MASK) + offset)
+    }
+
+    fn release(root: PhysAddr, kernel_base: usize) {
+        for pdpte in &mut table(root)[..pdpt_index(kernel_base)] {
+            if (*pdpte & PRESENT) == 0 {
+                continue;
+            }
+
+            let pd = *pdpte & ADDR_MASK;
+
+            for &pde in table(pd).iter() {
+                if (pde & PRESENT) != 0 {
+                    frame::free_direct(pde & ADDR_MASK, 1);
+                }
+            }
+
+            frame::free_direct(pd, 1);
+            *pdpte = 0;
+        }
+    }
+}
@@ -0,0 +1,241 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Date: 2025-06-13
+// Author: Alexander Kuzin <alkuzindev@gmail.com>.
+
+//! 8259 PIC (Programmable Interrupt Controller) driver.
+//!
+//! # Description
+//! Two cascaded controllers deliver 16 legacy IRQs. By default they use
+//! vectors 0x08-0x0F & 0x70-0x77, the first range collides with CPU
+//! exceptions, so both controllers are remapped right after CPU exception
+//! vectors. All IRQs are masked until handler is registered. PIC is used
+//! only if APIC is not available, otherwise all its lines stay masked.
+
+use super::io::{inb, outb};
+use crate::hal::irq::InterruptController;
+
+/// Master PIC command port.
+const MASTER_COMMAND: u16 = 0x20;
+
+/// Master PIC data port.
+const MASTER_DATA: u16 = 0x21;
+
+/// Slave PIC command port.
+const SLAVE_COMMAND: u16 = 0xA0;
+
+/// Slave PIC data port.
+const SLAVE_DATA: u16 = 0xA1;
+
+/// Initialization command word 1: initialization, ICW4 is present.
+const ICW1_INIT: u8 = 0x11;
+
+/// Initialization command word 4: 8086 mode.
+const ICW4_8086: u8 = 0x01;
+
+/// Operation command word 3: read in-service register.
+const OCW3_READ_ISR: u8 = 0x0B;
+
+/// End of interrupt command.
+const EOI: u8 = 0x20;
+
+/// IRQ line of slave PIC on master PIC.
+const CASCADE_IRQ: u8 = 2;
+
+/// Port used for short I/O delays.
+const DELAY_PORT: u16 = 0x80;
+
+/// First interrupt vector of remapped IRQs.
+pub const IRQ_BASE: u8 = 0x20;
+
+/// Number of IRQ lines.
+pub const IRQ_COUNT: usize = 16;
+
+/// Wait for PIC to process previous command.
+#[inline(always)]
+fn io_wait() {
+    unsafe {
+        outb(DELAY_PORT, 0);
+    }
+}
+
+/// Get data port of PIC handling IRQ & IRQ line on that PIC.
+///
+/// # Parameters
+/// - `irq` - given IRQ number.
+///
+/// # Returns
+/// - Data port & IRQ line on the PIC.
+#[inline(always)]
+fn data_port(irq: u8) -> (u16, u8) {
+    if irq < 8 {
+        (MASTER_DATA, irq)
+    } else {
+        (SLAVE_DATA, irq - 8)
+    }
+}
+
+/// Read in-service register of both controllers.
+///
+/// # Returns
+/// - In-service register (slave in the high byte).
+fn in_service() -> u16 {
+    unsafe {
+        outb(MASTER_COMMAND, OCW3_READ_ISR);
+        outb(SLAVE_COMMAND, OCW3_READ_ISR);
+        ((inb(SLAVE_COMMAND) as u16) << 8) | inb(MASTER_COMMAND) as u16
+    }
+}
+
+/// Mask IRQ line.
+///
+/// # Parameters
+/// - `irq` - given IRQ number.
+pub fn mask(irq: u8) {
+    let (port, line) = data_port(irq);
+
+    unsafe {
+        outb(port, inb(port) | (1 << line));
+    }
+}
+
+/// Unmask IRQ line.
+///
+/// # Parameters
+/// - `irq` - given IRQ number.
+pub fn unmask(irq: u8) {
+    let (port, line) = data_port(irq);
+
+    unsafe {
+        outb(port, inb(port) & !(1 << line));
+    }
+}
+
+/// Send end of interrupt command.
+///
+/// # Parameters
+/// - `irq` - given IRQ number.
+pub fn eoi(irq: u8) {
+    unsafe {
+        if irq >= 8 {
+            outb(SLAVE_COMMAND, EOI);
+        }
+
+        outb(MASTER_COMMAND, EOI);
+    }
+}
+
+/// Check whether IRQ is spurious. Spurious IRQ is raised on the lowest
+/// priority line of controller when interrupt request disappears before
+/// it is acknowledged, it must not be acknowledged by EOI (except for the
+/// cascade line on master PIC).
+///
+/// # Parameters
+/// - `irq` - given IRQ number.
+///
+/// # Returns
+/// - `true`  - if IRQ is spurious.
+/// - `false` - otherwise.
+fn is_spurious(irq: u8) -> bool {
+    if irq != 7 && irq != 15 {
+        return false;
+    }
+
+    if (in_service() & (1 << irq)) != 0 {
+        return false;
+    }
+
+    if irq == 15 {
+        eoi(CASCADE_IRQ);
+    }
+
+    true
+}
+
+/// 8259 PIC interrupt controller.
+pub struct Pic;
+
+impl InterruptController for Pic {
+    /// Get interrupt controller name.
+    ///
+    /// # Returns
+    /// - Interrupt controller name.
+    fn name(&self) -> &'static str {
+        "8259 PIC"
+    }
+
+    /// Mask IRQ line.
+    ///
+    /// # Parameters
+    /// - `irq` - given IRQ number.
+    fn mask(&self, irq: u8) {
+        mask(irq);
+    }
+
+    /// Unmask IRQ line.
+    ///
+    /// # Parameters
+    /// - `irq` - given IRQ number.
+    fn unmask(&self, irq: u8) {
+        unmask(irq);
+    }
+
+    /// Send end of interrupt command.
+    ///
+    /// # Parameters
+    /// - `irq` - given IRQ number.
+    fn eoi(&self, irq: u8) {
+        eoi(irq);
+    }
+
+    /// Check whether IRQ is spurious.
+    ///
+    /// # Parameters
+    /// - `irq` - given IRQ number.
+    ///
+    /// # Returns
+    /// - `true`  - if IRQ is spurious.
+    /// - `false` - otherwise.
+    fn is_spurious(&self, irq: u8) -> bool {
+        is_spurious(irq)
+    }
+}
+
+/// Mask all IRQ lines of both controllers.
+pub fn disable() {
+    unsafe {
+        outb(MASTER_DATA, 0xFF);
+        outb(SLAVE_DATA, 0xFF);
+    }
+}
+
+/// Remap both controllers after CPU exception vectors & mask all IRQs.
+pub fn init() {
+    unsafe {
+        // Start initialization sequence.
+        outb(MASTER_COMMAND, ICW1_INIT);
+        io_wait();
+        outb(SLAVE_COMMAND, ICW1_INIT);
+        io_wait();
+
+        // Set vector offsets.
+        outb(MASTER_DATA, IRQ_BASE);
+        io_wait();
+        outb(SLAVE_DATA, IRQ_BASE + 8);
+        io_wait();
+
+        // Tell master about slave on IRQ2 & slave about its cascade identity.
+        outb(MASTER_DATA, 1 << CASCADE_IRQ);
+        io_wait();
+        outb(SLAVE_DATA, CASCADE_IRQ);
+        io_wait();
+
+        outb(MASTER_DATA, ICW4_8086);
+        io_wait();
+        outb(SLAVE_DATA, ICW4_8086);
+        io_wait();
+
+        // Mask everything except cascade line.
+        outb(MASTER_DATA, !(1 << CASCADE_IRQ));
+        outb(SLAVE_DATA, 0xFF);
+    }
+}
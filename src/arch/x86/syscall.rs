@@ -0,0 +1,224 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Date: 2025-06-13
+// Author: Alexander Kuzin <alkuzindev@gmail.com>.
+
+//! x86 system call entry.
+//!
+//! # Description
+//! System call enters the kernel by `int 0x80` or, if CPU supports it, by
+//! `sysenter` (see `syscall.asm`). Both entry points save CPU state as
+//! `InterruptFrame`, so registers are the same:
+//! - `eax`                      - system call number & result.
+//! - `ebx`, `esi`, `edi`, `ebp` - arguments.
+//! - `ecx`, `edx`               - user stack & return address for
+//!   `sysenter`, preserved by `int 0x80`.
+//!
+//! User EFLAGS are preserved by both entry points.
+
+use super::{cpu, gdt::Segment, idt::InterruptFrame, tss};
+use crate::kernel::syscall;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+/// `sysenter` code segment selector MSR.
+const IA32_SYSENTER_CS: u32 = 0x174;
+
+/// `sysenter` stack pointer MSR.
+const IA32_SYSENTER_ESP: u32 = 0x175;
+
+/// `sysenter` entry point MSR.
+const IA32_SYSENTER_EIP: u32 = 0x176;
+
+/// Whether `sysenter` is enabled.
+static SYSENTER: AtomicBool = AtomicBool::new(false);
+
+unsafe extern "C" {
+    /// `sysenter` entry point.
+    fn sysenter_entry();
+}
+
+/// Handle system call. Called from `interrupt_dispatch` for both entry
+/// points.
+///
+/// # Parameters
+/// - `frame` - given CPU state saved on system call entry.
+pub fn handle(frame: &mut InterruptFrame) {
+    let args = [frame.ebx, frame.esi, frame.edi, frame.ebp];
+    frame.eax = syscall::dispatch(frame.eax, &args);
+}
+
+/// Check whether `sysenter` system call entry is enabled.
+///
+/// # Returns
+/// - `true`  - if `sysenter` is enabled.
+/// - `false` - otherwise.
+pub fn is_sysenter_enabled() -> bool {
+    SYSENTER.load(Ordering::Relaxed)
+}
+
+/// Set stack used on `sysenter`.
+///
+/// # Parameters
+/// - `esp` - given top of the kernel stack of the running thread.
+pub fn set_sysenter_stack(esp: u32) {
+    if is_sysenter_enabled() {
+        unsafe { cpu::wrmsr(IA32_SYSENTER_ESP, esp as u64) };
+    }
+}
+
+/// Initialize `sysenter` entry point. System call gate is set by IDT.
+///
+/// # Returns
+/// - `true`  - if `sysenter` is enabled.
+/// - `false` - if CPU does not support `sysenter`.
+pub fn init() -> bool {
+    if !cpu::is_support_sep() {
+        return false;
+    }
+
+    // Stack segment & user segments follow `sysenter` code segment.
+    unsafe {
+        cpu::wrmsr(IA32_SYSENTER_CS, Segment::SysenterCode as u64);
+        cpu::wrmsr(IA32_SYSENTER_ESP, tss::kernel_stack() as u64);
+        cpu::wrmsr(IA32_SYSENTER_EIP, sysenter_entry as *const () as u64);
+    }
+
+    SYSENTER.store(true, Ordering::Relaxed);
+    true
+}
+
+#[cfg(feature = "ktest")]
+pub mod tests {
+    use super::*;
+    use crate::{
+        arch::x86::{
+            idt::SYSCALL,
+            usermode::tests::{CODE, DATA, push_exit, run_user_code},
+        },
+        kernel::syscall::{SYS_UPTIME, SyscallError},
+        ktest::TestCase,
+    };
+    use alloc::vec::Vec;
+    use core::arch::asm;
+
+    /// x86 system call entry unit tests.
+    pub const TESTS: &[TestCase] = &[
+        TestCase::new("int80_dispatch", int80_dispatch),
+        TestCase::new("sysenter_msrs", sysenter_msrs),
+        TestCase::new("int80_from_user", int80_from_user),
+        TestCase::new("sysenter_from_user", sysenter_from_user),
+    ];
+
+    /// EFLAGS carry flag.
+    const FLAG_CF: u32 = 1 << 0;
+
+    /// EFLAGS interrupt flag.
+    const FLAG_IF: u32 = 1 << 9;
+
+    /// EFLAGS direction flag.
+    const FLAG_DF: u32 = 1 << 10;
+
+    /// Build user code calling `uptime` system call with CF & DF set. Call
+    /// result & uptime are stored to `DATA`, EFLAGS after the call are
+    /// returned as exit status.
+    ///
+    /// # Parameters
+    /// - `fast` - given whether to use `sysenter` instead of `int 0x80`.
+    ///
+    /// # Returns
+    /// - Machine code.
+    fn uptime_code(fast: bool) -> Vec<u8> {
+        let mut code = Vec::new();
+
+        code.push(0xB8); // mov eax, SYS_UPTIME
+        code.extend(SYS_UPTIME.to_le_bytes());
+        code.push(0xBB); // mov ebx, DATA + 8
+        code.extend((DATA + 8).to_le_bytes());
+        code.extend([0xFD, 0xF9]); // std; stc
+
+        if fast {
+            code.extend([0x89, 0xE1]); // mov ecx, esp
+
+            // Return address follows `mov edx, imm32` & `sysenter`.
+            let ret = CODE + code.len() as u32 + 7;
+
+            code.push(0xBA); // mov edx, ret
+            code.extend(ret.to_le_bytes());
+            code.extend([0x0F, 0x34]); // sysenter
+        } else {
+            code.extend([0xCD, SYSCALL]); // int 0x80
+        }
+
+        code.extend([0x9C, 0x5B, 0xFC]); // pushfd; pop ebx; cld
+        code.push(0xA3); // mov [DATA], eax
+        code.extend(DATA.to_le_bytes());
+
+        push_exit(&mut code);
+        code
+    }
+
+    /// Run `uptime_code` in user mode & check results.
+    ///
+    /// # Parameters
+    /// - `fast` - given whether to use `sysenter` instead of `int 0x80`.
+    fn check_user_call(fast: bool) {
+        let (flags, data) = run_user_code(&uptime_code(fast));
+        let result = u32::from_le_bytes(data[..4].try_into().unwrap());
+        let uptime = u64::from_le_bytes(data[8..16].try_into().unwrap());
+
+        assert_eq!(result, 0);
+        assert!(uptime > 0);
+        assert_eq!(flags & (FLAG_CF | FLAG_DF), FLAG_CF | FLAG_DF);
+        assert_ne!(flags & FLAG_IF, 0);
+    }
+
+    fn int80_dispatch() {
+        let mut result: u32;
+
+        // Gate is available from kernel mode as well.
+        unsafe {
+            asm!(
+                "int {vector}",
+                vector = const SYSCALL,
+                inout("eax") SYS_UPTIME => result,
+                in("ebx") 0u32,
+            );
+        }
+
+        assert_eq!(result, syscall::encode(Err(SyscallError::Fault)));
+
+        unsafe {
+            asm!(
+                "int {vector}",
+                vector = const SYSCALL,
+                inout("eax") u32::MAX => result,
+            );
+        }
+
+        assert_eq!(result, syscall::encode(Err(SyscallError::NoSys)));
+    }
+
+    fn sysenter_msrs() {
+        if !is_sysenter_enabled() {
+            return;
+        }
+
+        let code = cpu::rdmsr(IA32_SYSENTER_CS);
+        let entry = cpu::rdmsr(IA32_SYSENTER_EIP);
+
+        assert_eq!(code, Segment::SysenterCode as u64);
+        assert_eq!(entry, sysenter_entry as *const () as u64);
+        assert_eq!(cpu::rdmsr(IA32_SYSENTER_ESP), tss::kernel_stack() as u64);
+    }
+
+    fn int80_from_user() {
+        check_user_call(false);
+    }
+
+    fn sysenter_from_user() {
+        if !is_sysenter_enabled() {
+            return;
+        }
+
+        check_user_call(true);
+    }
+}
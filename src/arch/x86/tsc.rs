@@ -0,0 +1,94 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Date: 2025-06-13
+// Author: Alexander Kuzin <alkuzindev@gmail.com>.
+
+//! TSC (Time-Stamp Counter) clocksource.
+//!
+//! # Description
+//! TSC counts CPU clock cycles & is the cheapest clock to read, but its
+//! frequency is not reported reliably, so it is calibrated against PIT
+//! during boot. Only invariant TSC (running at constant rate regardless of
+//! power states) is preferred over other clocksources.
+
+use super::{cpu, drivers::pit};
+use crate::hal::clocksource::Clocksource;
+use spin::Once;
+
+/// Calibration period in nanoseconds.
+const CALIBRATION_NS: u64 = 50_000_000;
+
+/// Number of nanoseconds in second.
+const NANOS_PER_SEC: u64 = 1_000_000_000;
+
+/// Calibrated TSC frequency in Hz.
+static FREQUENCY: Once<u64> = Once::new();
+
+/// TSC clocksource.
+pub struct Tsc;
+
+impl Clocksource for Tsc {
+    /// Get clocksource name.
+    ///
+    /// # Returns
+    /// - Clocksource name.
+    fn name(&self) -> &'static str {
+        "TSC"
+    }
+
+    /// Get clocksource rating. Rate of not invariant TSC may change, so it
+    /// is rated lower than PIT.
+    ///
+    /// # Returns
+    /// - Clocksource rating (zero if TSC is not calibrated).
+    fn rating(&self) -> u32 {
+        match FREQUENCY.get() {
+            None => 0,
+            Some(_) if cpu::is_tsc_invariant() => 300,
+            Some(_) => 50,
+        }
+    }
+
+    /// Get counter frequency.
+    ///
+    /// # Returns
+    /// - Calibrated TSC frequency (zero if TSC is not calibrated).
+    fn frequency(&self) -> u64 {
+        FREQUENCY.get().copied().unwrap_or(0)
+    }
+
+    /// Read counter.
+    ///
+    /// # Returns
+    /// - Current time-stamp counter value.
+    fn read(&self) -> u64 {
+        cpu::rdtsc()
+    }
+}
+
+/// Measure TSC frequency against PIT. Requires running system timer with
+/// interrupts enabled.
+///
+/// # Returns
+/// - TSC frequency in Hz - in case of success.
+/// - `None`              - if CPU has no TSC.
+pub fn calibrate() -> Option<u64> {
+    if !cpu::is_support_tsc() {
+        return None;
+    }
+
+    let frequency = *FREQUENCY.call_once(|| {
+        let start_ns = pit::elapsed_ns();
+        let start = cpu::rdtsc();
+        let mut elapsed = 0;
+
+        while elapsed < CALIBRATION_NS {
+            core::hint::spin_loop();
+            elapsed = pit::elapsed_ns() - start_ns;
+        }
+
+        let cycles = cpu::rdtsc() - start;
+        cycles * NANOS_PER_SEC / elapsed
+    });
+
+    Some(frequency)
+}
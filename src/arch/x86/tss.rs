@@ -0,0 +1,269 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Date: 2025-06-13
+// Author: Alexander Kuzin <alkuzindev@gmail.com>.
+
+//! Task State Segment module.
+//!
+//! # Description
+//! The Task State Segment (TSS) holds CPU state of hardware task. Kernel
+//! does not use hardware task switching except for double fault: it is
+//! handled by separate task with its own stack, so that kernel stack
+//! overflow is reported instead of causing triple fault.
+//!
+//! Kernel task TSS also holds ring 0 stack (`SS0:ESP0`) which CPU switches
+//! to when interrupt arrives in user mode. It must point to kernel stack
+//! of the running thread, so it is updated on every context switch.
+
+use super::{
+    exceptions,
+    gdt::{self, Segment},
+};
+use crate::kernel::mm::kstack;
+use core::arch::asm;
+
+/// Double fault task stack size.
+const DOUBLE_FAULT_STACK_SIZE: usize = 0x4000;
+
+/// EFLAGS register value with interrupts disabled.
+const EFLAGS_DEFAULT: u32 = 0x2;
+
+/// Task State Segment structure.
+#[derive(Debug, Clone, Copy)]
+#[repr(C, packed)]
+pub struct TaskStateSegment {
+    /// Selector of previous task TSS.
+    pub link: u32,
+    /// Privilege level 0 stack pointer.
+    pub esp0: u32,
+    /// Privilege level 0 stack segment.
+    pub ss0: u32,
+    /// Privilege level 1 stack pointer.
+    pub esp1: u32,
+    /// Privilege level 1 stack segment.
+    pub ss1: u32,
+    /// Privilege level 2 stack pointer.
+    pub esp2: u32,
+    /// Privilege level 2 stack segment.
+    pub ss2: u32,
+    /// Page directory physical address.
+    pub cr3: u32,
+    /// Saved EIP register.
+    pub eip: u32,
+    /// Saved EFLAGS register.
+    pub eflags: u32,
+    /// Saved EAX register.
+    pub eax: u32,
+    /// Saved ECX register.
+    pub ecx: u32,
+    /// Saved EDX register.
+    pub edx: u32,
+    /// Saved EBX register.
+    pub ebx: u32,
+    /// Saved ESP register.
+    pub esp: u32,
+    /// Saved EBP register.
+    pub ebp: u32,
+    /// Saved ESI register.
+    pub esi: u32,
+    /// Saved EDI register.
+    pub edi: u32,
+    /// Saved ES segment register.
+    pub es: u32,
+    /// Saved CS segment register.
+    pub cs: u32,
+    /// Saved SS segment register.
+    pub ss: u32,
+    /// Saved DS segment register.
+    pub ds: u32,
+    /// Saved FS segment register.
+    pub fs: u32,
+    /// Saved GS segment register.
+    pub gs: u32,
+    /// LDT segment selector.
+    pub ldt: u32,
+    /// Debug trap flag.
+    pub trap: u16,
+    /// Offset of I/O permission bitmap.
+    pub iomap_base: u16,
+}
+
+impl TaskStateSegment {
+    /// Construct empty TSS without I/O permission bitmap.
+    const fn empty() -> Self {
+        Self {
+            link: 0,
+            esp0: 0,
+            ss0: 0,
+            esp1: 0,
+            ss1: 0,
+            esp2: 0,
+            ss2: 0,
+            cr3: 0,
+            eip: 0,
+            eflags: 0,
+            eax: 0,
+            ecx: 0,
+            edx: 0,
+            ebx: 0,
+            esp: 0,
+            ebp: 0,
+            esi: 0,
+            edi: 0,
+            es: 0,
+            cs: 0,
+            ss: 0,
+            ds: 0,
+            fs: 0,
+            gs: 0,
+            ldt: 0,
+            trap: 0,
+            iomap_base: size_of::<Self>() as u16,
+        }
+    }
+}
+
+/// Double fault task stack.
+#[repr(C, align(16))]
+struct Stack([u8; DOUBLE_FAULT_STACK_SIZE]);
+
+/// TSS of the kernel task (state is saved here on double fault).
+static mut TSS: TaskStateSegment = TaskStateSegment::empty();
+
+/// TSS of the double fault task.
+static mut DOUBLE_FAULT_TSS: TaskStateSegment = TaskStateSegment::empty();
+
+/// Double fault task stack.
+static mut DOUBLE_FAULT_STACK: Stack = Stack([0; DOUBLE_FAULT_STACK_SIZE]);
+
+/// Get kernel task TSS address.
+///
+/// # Returns
+/// - Linear address of kernel task TSS.
+#[inline(always)]
+pub fn tss_base() -> u32 {
+    &raw const TSS as u32
+}
+
+/// Get double fault task TSS address.
+///
+/// # Returns
+/// - Linear address of double fault task TSS.
+#[inline(always)]
+pub fn double_fault_tss_base() -> u32 {
+    &raw const DOUBLE_FAULT_TSS as u32
+}
+
+/// Get kernel task state saved on switch to double fault task.
+///
+/// # Returns
+/// - Copy of kernel task TSS.
+pub fn interrupted_task() -> TaskStateSegment {
+    unsafe { TSS }
+}
+
+/// Set stack used on entry to kernel from user mode.
+///
+/// # Parameters
+/// - `esp0` - given top of the kernel stack of the running thread.
+pub fn set_kernel_stack(esp0: u32) {
+    unsafe {
+        TSS.esp0 = esp0;
+    }
+}
+
+/// Get stack used on entry to kernel from user mode.
+///
+/// # Returns
+/// - Top of the kernel stack of the running thread.
+pub fn kernel_stack() -> u32 {
+    unsafe { TSS.esp0 }
+}
+
+/// Set page directory used by double fault task.
+///
+/// # Parameters
+/// - `cr3` - given physical address of kernel page directory.
+pub fn set_double_fault_cr3(cr3: u32) {
+    unsafe {
+        DOUBLE_FAULT_TSS.cr3 = cr3;
+    }
+}
+
+/// Initialize double fault task, ring 0 stack & load kernel task register.
+pub fn init() {
+    let cr3: usize;
+
+    unsafe {
+        asm!("mov {}, cr3", out(reg) cr3, options(nomem, nostack));
+    }
+
+    let stack_top =
+        &raw const DOUBLE_FAULT_STACK as u32 + DOUBLE_FAULT_STACK_SIZE as u32;
+    let code = Segment::KernelCode as u32;
+    let data = Segment::KernelData as u32;
+    let stack = Segment::KernelStack as u32;
+
+    unsafe {
+        // Boot stack is used until the first context switch.
+        TSS.ss0 = stack;
+        TSS.esp0 = kstack::boot_stack_top() as u32;
+
+        DOUBLE_FAULT_TSS = TaskStateSegment {
+            cr3: cr3 as u32,
+            eip: exceptions::double_fault as *const () as u32,
+            eflags: EFLAGS_DEFAULT,
+            esp: stack_top,
+            es: data,
+            cs: code,
+            ss: stack,
+            ds: data,
+            fs: data,
+            gs: data,
+            ..TaskStateSegment::empty()
+        };
+    }
+
+    if let Err(err) = gdt::load_task_register(Segment::Tss as u16) {
+        panic!("Failed to load task register: {}", err);
+    }
+}
+
+#[cfg(feature = "ktest")]
+pub mod tests {
+    use super::*;
+    use crate::ktest::TestCase;
+
+    /// Task State Segment unit tests.
+    pub const TESTS: &[TestCase] = &[
+        TestCase::new("task_register", task_register),
+        TestCase::new("ring0_stack", ring0_stack),
+        TestCase::new("kernel_stack_switch", kernel_stack_switch),
+    ];
+
+    fn task_register() {
+        let selector: u16;
+
+        unsafe {
+            asm!("str {:x}", out(reg) selector, options(nomem, nostack));
+        }
+
+        assert_eq!(selector, Segment::Tss as u16);
+    }
+
+    fn ring0_stack() {
+        let ss0 = unsafe { TSS.ss0 };
+
+        assert_eq!(ss0, Segment::KernelStack as u32);
+        assert_ne!(kernel_stack(), 0);
+    }
+
+    fn kernel_stack_switch() {
+        let esp0 = kernel_stack();
+
+        set_kernel_stack(0xC0FFEE00);
+        assert_eq!(kernel_stack(), 0xC0FFEE00);
+
+        set_kernel_stack(esp0);
+        assert_eq!(kernel_stack(), esp0);
+    }
+}
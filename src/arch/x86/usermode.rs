@@ -0,0 +1,238 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Date: 2025-06-13
+// Author: Alexander Kuzin <alkuzindev@gmail.com>.
+
+//! Ring 3 (user mode) transition.
+//!
+//! # Description
+//! CPU enters user mode only by returning to less privileged code, so
+//! `iret` frame with user mode selectors is built by hand. Interrupts &
+//! exceptions raised in user mode switch to ring 0 stack from kernel task
+//! TSS (see `tss::set_kernel_stack`).
+//!
+//! `run_user_mode` runs user code on behalf of the caller & returns when
+//! user code finishes by `exit` system call (see `usermode.asm`).
+
+use super::gdt::{Segment, USER_RPL};
+use crate::kernel::mm::kstack::KernelStack;
+use core::arch::asm;
+
+unsafe extern "C" {
+    /// Save kernel context & jump to user mode.
+    fn user_mode_enter(entry: u32, stack: u32) -> u32;
+    /// Restore kernel context saved by `user_mode_enter`.
+    fn user_mode_exit(status: u32) -> !;
+    /// Saved kernel stack pointer (zero if no user code is run).
+    static mut user_mode_context: u32;
+}
+
+/// EFLAGS register value of user mode code (interrupts enabled, IOPL 0).
+const USER_EFLAGS: u32 = 0x202;
+
+/// User mode code segment selector.
+const USER_CODE: u32 = Segment::UserCode as u32 | USER_RPL;
+
+/// User mode data segment selector.
+const USER_DATA: u32 = Segment::UserData as u32 | USER_RPL;
+
+/// User mode stack segment selector.
+const USER_STACK: u32 = Segment::UserStack as u32 | USER_RPL;
+
+/// Switch CPU to user mode & jump to user code.
+///
+/// # Parameters
+/// - `entry` - given user code entry point.
+/// - `stack` - given user stack top.
+///
+/// # Safety
+/// - `entry` & `stack` must be mapped with user access in current address
+///   space.
+/// - Kernel stack set by `tss::set_kernel_stack` must be valid, it is used
+///   on the next interrupt.
+pub unsafe fn enter_user_mode(entry: u32, stack: u32) -> ! {
+    unsafe {
+        asm!(
+            "mov ds, {data:x}",
+            "mov es, {data:x}",
+            "mov fs, {data:x}",
+            "mov gs, {data:x}",
+            "push {ss}",
+            "push {stack}",
+            "push {eflags}",
+            "push {cs}",
+            "push {entry}",
+            "iretd",
+            data = in(reg) USER_DATA,
+            ss = const USER_STACK,
+            eflags = const USER_EFLAGS,
+            cs = const USER_CODE,
+            stack = in(reg) stack,
+            entry = in(reg) entry,
+            options(noreturn),
+        );
+    }
+}
+
+/// Run user code until it finishes by `exit` system call. Nested runs are
+/// not supported.
+///
+/// # Parameters
+/// - `entry` - given user code entry point.
+/// - `stack` - given user stack top.
+///
+/// # Returns
+/// - Exit status of user code - in case of success.
+/// - `None`                   - if kernel stack can not be allocated.
+///
+/// # Safety
+/// - `entry` & `stack` must be mapped with user access in current address
+///   space.
+pub unsafe fn run_user_mode(entry: u32, stack: u32) -> Option<u32> {
+    // Entries from user mode must not overwrite saved kernel context.
+    let kstack = KernelStack::new()?;
+    let previous = super::tss::kernel_stack();
+
+    super::set_kernel_stack(kstack.top() as u32);
+    let status = unsafe { user_mode_enter(entry, stack) };
+    super::set_kernel_stack(previous);
+
+    Some(status)
+}
+
+/// Finish user code run by `run_user_mode` & return to its caller.
+///
+/// # Parameters
+/// - `status` - given exit status of user code.
+///
+/// # Returns
+/// - Returns only if no user code is run by `run_user_mode`.
+pub fn exit_user_mode(status: u32) {
+    let context = unsafe { (&raw const user_mode_context).read_volatile() };
+
+    if context != 0 {
+        unsafe { user_mode_exit(status) };
+    }
+}
+
+#[cfg(feature = "ktest")]
+pub mod tests {
+    use super::*;
+    use crate::{
+        arch::x86::{idt::SYSCALL, tss},
+        kernel::{
+            mm::{
+                PAGE_SIZE,
+                vm::{self, AddressSpace, Backing, Prot, RegionFlags},
+            },
+            syscall::SYS_EXIT,
+        },
+        ktest::TestCase,
+    };
+    use alloc::{sync::Arc, vec::Vec};
+    use spin::Mutex;
+
+    /// User mode unit tests.
+    pub const TESTS: &[TestCase] = &[
+        TestCase::new("ring3_roundtrip", ring3_roundtrip),
+        TestCase::new("exit_outside_user_mode", exit_outside_user_mode),
+    ];
+
+    /// User code page address.
+    pub const CODE: u32 = 0x10000000;
+
+    /// User data & stack page address.
+    pub const DATA: u32 = CODE + PAGE_SIZE as u32;
+
+    /// Size of user data returned by `run_user_code`.
+    pub const DATA_SIZE: usize = 32;
+
+    /// Run machine code in ring 3 in temporary address space. Code is
+    /// placed at `CODE`, stack grows down from the end of `DATA` page.
+    ///
+    /// # Parameters
+    /// - `code` - given machine code finished by `exit` system call.
+    ///
+    /// # Returns
+    /// - Exit status & first `DATA_SIZE` bytes of `DATA` page.
+    pub fn run_user_code(code: &[u8]) -> (u32, [u8; DATA_SIZE]) {
+        let page = PAGE_SIZE as usize;
+        let space = Arc::new(Mutex::new(AddressSpace::new().unwrap()));
+
+        {
+            let mut space = space.lock();
+            let code_prot = Prot::READ | Prot::WRITE | Prot::EXEC;
+            let data_prot = Prot::READ | Prot::WRITE;
+
+            space
+                .map(
+                    CODE as usize,
+                    page,
+                    code_prot,
+                    RegionFlags::USER,
+                    Backing::Anonymous,
+                )
+                .unwrap();
+
+            space
+                .map(
+                    DATA as usize,
+                    page,
+                    data_prot,
+                    RegionFlags::USER,
+                    Backing::Anonymous,
+                )
+                .unwrap();
+        }
+
+        vm::switch_to(space.clone());
+
+        let mut data = [0; DATA_SIZE];
+        let status;
+
+        unsafe {
+            let ptr = CODE as *mut u8;
+            ptr.copy_from_nonoverlapping(code.as_ptr(), code.len());
+
+            status = run_user_mode(CODE, DATA + PAGE_SIZE as u32).unwrap();
+
+            let ptr = DATA as *const u8;
+            ptr.copy_to_nonoverlapping(data.as_mut_ptr(), DATA_SIZE);
+        }
+
+        vm::switch_to_kernel();
+        (status, data)
+    }
+
+    /// Append machine code exiting with value of `ebx`.
+    ///
+    /// # Parameters
+    /// - `code` - given code to append exit to.
+    pub fn push_exit(code: &mut Vec<u8>) {
+        code.push(0xB8); // mov eax, SYS_EXIT
+        code.extend(SYS_EXIT.to_le_bytes());
+        code.extend([0xCD, SYSCALL]); // int 0x80
+        code.extend([0xEB, 0xFE]); // jmp $
+    }
+
+    fn ring3_roundtrip() {
+        let previous = tss::kernel_stack();
+
+        // Exit with selector of user code segment.
+        let mut code = Vec::from([
+            0x31, 0xDB, // xor ebx, ebx
+            0x8C, 0xCB, // mov bx, cs
+        ]);
+
+        push_exit(&mut code);
+
+        let (status, _) = run_user_code(&code);
+
+        assert_eq!(status, USER_CODE);
+        assert_eq!(tss::kernel_stack(), previous);
+    }
+
+    fn exit_outside_user_mode() {
+        // Must return, since no user code is run.
+        exit_user_mode(0);
+    }
+}
@@ -0,0 +1,697 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Date: 2025-06-13
+// Author: Alexander Kuzin <alkuzindev@gmail.com>.
+
+//! Bootloader independent boot information.
+//!
+//! # Description
+//! The kernel entry path converts bootloader specific structures into
+//! `BootInfo`. All data is copied, so it stays valid after bootloader
+//! memory is reclaimed or unmapped.
+
+use crate::{
+    multiboot::{
+        MULTIBOOT_INFO_APM_TABLE, MULTIBOOT_INFO_CONFIG_TABLE,
+        MULTIBOOT_INFO_FRAMEBUFFER_INFO, MULTIBOOT_INFO_MEMORY, MultibootInfo,
+        MultibootMemoryType,
+    },
+    multiboot2::{AcpiRsdp, Multiboot2Info},
+};
+
+/// Maximum number of memory regions.
+pub const MAX_MEMORY_REGIONS: usize = 64;
+
+/// Maximum number of boot modules.
+pub const MAX_MODULES: usize = 16;
+
+/// Maximum size of kernel command line in bytes.
+pub const CMDLINE_SIZE: usize = 256;
+
+/// Maximum size of boot module command line in bytes.
+pub const MODULE_CMDLINE_SIZE: usize = 64;
+
+/// Maximum size of bootloader name in bytes.
+pub const BOOT_LOADER_NAME_SIZE: usize = 64;
+
+/// Maximum size of ACPI RSDP in bytes (ACPI 2.0+ XSDP).
+pub const RSDP_SIZE: usize = 36;
+
+/// Fixed capacity string.
+#[derive(Clone, Copy)]
+pub struct FixedString<const N: usize> {
+    /// String bytes.
+    buf: [u8; N],
+    /// String length in bytes.
+    len: usize,
+}
+
+impl<const N: usize> FixedString<N> {
+    /// Construct new empty fixed capacity string.
+    ///
+    /// # Returns
+    /// - New empty `FixedString` object.
+    pub const fn new() -> Self {
+        Self {
+            buf: [0; N],
+            len: 0,
+        }
+    }
+
+    /// Construct fixed capacity string from string slice.
+    /// Strings that do not fit are truncated at character boundary.
+    ///
+    /// # Parameters
+    /// - `s` - given string slice to copy.
+    ///
+    /// # Returns
+    /// - New `FixedString` object.
+    pub fn from_str(s: &str) -> Self {
+        let mut len = s.len().min(N);
+
+        while !s.is_char_boundary(len) {
+            len -= 1;
+        }
+
+        let mut string = Self::new();
+        string.buf[..len].copy_from_slice(&s.as_bytes()[..len]);
+        string.len = len;
+        string
+    }
+
+    /// Get string slice.
+    ///
+    /// # Returns
+    /// - String slice of stored characters.
+    pub fn as_str(&self) -> &str {
+        // Only whole characters of valid string slices are stored.
+        unsafe { str::from_utf8_unchecked(&self.buf[..self.len]) }
+    }
+
+    /// Check whether string is empty.
+    ///
+    /// # Returns
+    /// - `true`  - if string is empty.
+    /// - `false` - otherwise.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl<const N: usize> Default for FixedString<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> core::fmt::Debug for FixedString<N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{:?}", self.as_str())
+    }
+}
+
+/// Physical memory region type.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum MemoryRegionType {
+    /// Usable RAM.
+    Available,
+    /// Reserved memory.
+    #[default]
+    Reserved,
+    /// Memory holding ACPI tables that can be reclaimed after parsing.
+    AcpiReclaimable,
+    /// ACPI non-volatile storage.
+    AcpiNvs,
+    /// Defective RAM.
+    BadRam,
+    /// Type value unknown to the kernel.
+    Unknown(u32),
+}
+
+impl From<u32> for MemoryRegionType {
+    /// Convert raw memory map entry type.
+    ///
+    /// # Parameters
+    /// - `value` - given raw memory region type.
+    ///
+    /// # Returns
+    /// - `MemoryRegionType` enumeration member associated with `value`.
+    fn from(value: u32) -> Self {
+        // Multiboot & multiboot2 share memory type values.
+        match MultibootMemoryType::try_from(value) {
+            Ok(MultibootMemoryType::Available) => Self::Available,
+            Ok(MultibootMemoryType::Reserved) => Self::Reserved,
+            Ok(MultibootMemoryType::AcpiReclaimable) => Self::AcpiReclaimable,
+            Ok(MultibootMemoryType::Nvs) => Self::AcpiNvs,
+            Ok(MultibootMemoryType::BadRam) => Self::BadRam,
+            Err(value) => Self::Unknown(value),
+        }
+    }
+}
+
+/// Physical memory region.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MemoryRegion {
+    /// Region physical start address.
+    pub start: u64,
+    /// Region size in bytes.
+    pub len: u64,
+    /// Region type.
+    pub rtype: MemoryRegionType,
+}
+
+impl MemoryRegion {
+    /// Get region end.
+    ///
+    /// # Returns
+    /// - Physical address right after the last byte of the region.
+    #[inline(always)]
+    pub fn end(&self) -> u64 {
+        self.start.saturating_add(self.len)
+    }
+}
+
+/// Bootloader provided framebuffer description.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FramebufferInfo {
+    /// Framebuffer physical address.
+    pub addr: u64,
+    /// Number of bytes in a single row of the framebuffer.
+    pub pitch: u32,
+    /// X-resolution.
+    pub width: u32,
+    /// Y-resolution.
+    pub height: u32,
+    /// Bits per pixel.
+    pub bpp: u8,
+}
+
+impl FramebufferInfo {
+    /// Get framebuffer size.
+    ///
+    /// # Returns
+    /// - Framebuffer size in bytes.
+    #[inline(always)]
+    pub fn size(&self) -> u64 {
+        self.pitch as u64 * self.height as u64
+    }
+}
+
+/// Boot module loaded by the bootloader.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct BootModule {
+    /// Module physical start address.
+    pub start: u64,
+    /// Module physical end address (exclusive).
+    pub end: u64,
+    /// Module command line.
+    pub cmdline: FixedString<MODULE_CMDLINE_SIZE>,
+}
+
+/// Copy of the ACPI RSDP.
+#[derive(Debug, Clone, Copy)]
+pub struct RsdpCopy {
+    /// ACPI RSDP bytes.
+    bytes: [u8; RSDP_SIZE],
+    /// ACPI RSDP size in bytes.
+    len: usize,
+}
+
+impl RsdpCopy {
+    /// Construct ACPI RSDP copy.
+    ///
+    /// # Parameters
+    /// - `data` - given ACPI RSDP bytes.
+    ///
+    /// # Returns
+    /// - New `RsdpCopy` object.
+    pub fn new(data: &[u8]) -> Self {
+        let len = data.len().min(RSDP_SIZE);
+        let mut bytes = [0u8; RSDP_SIZE];
+        bytes[..len].copy_from_slice(&data[..len]);
+
+        Self { bytes, len }
+    }
+
+    /// Get ACPI RSDP bytes.
+    ///
+    /// # Returns
+    /// - ACPI RSDP as byte slice.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+}
+
+/// Firmware tables passed by the bootloader.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FirmwareTables {
+    /// Copy of the ACPI RSDP.
+    pub acpi_rsdp: Option<RsdpCopy>,
+    /// APM table physical address.
+    pub apm_table: Option<u64>,
+    /// ROM configuration table physical address.
+    pub rom_config_table: Option<u64>,
+}
+
+/// Bootloader independent boot information.
+#[derive(Debug, Clone)]
+pub struct BootInfo {
+    /// Physical memory regions.
+    memory_regions: [MemoryRegion; MAX_MEMORY_REGIONS],
+    /// Number of physical memory regions.
+    memory_region_count: usize,
+    /// Boot modules.
+    modules: [BootModule; MAX_MODULES],
+    /// Number of boot modules.
+    module_count: usize,
+    /// Framebuffer description.
+    pub framebuffer: Option<FramebufferInfo>,
+    /// Kernel command line.
+    pub cmdline: FixedString<CMDLINE_SIZE>,
+    /// Bootloader name.
+    pub boot_loader_name: FixedString<BOOT_LOADER_NAME_SIZE>,
+    /// Firmware tables.
+    pub firmware: FirmwareTables,
+}
+
+impl Default for BootInfo {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BootInfo {
+    /// Construct new empty boot information.
+    ///
+    /// # Returns
+    /// - New empty `BootInfo` object.
+    pub const fn new() -> Self {
+        const EMPTY_REGION: MemoryRegion = MemoryRegion {
+            start: 0,
+            len: 0,
+            rtype: MemoryRegionType::Reserved,
+        };
+
+        const EMPTY_MODULE: BootModule = BootModule {
+            start: 0,
+            end: 0,
+            cmdline: FixedString::new(),
+        };
+
+        Self {
+            memory_regions: [EMPTY_REGION; MAX_MEMORY_REGIONS],
+            memory_region_count: 0,
+            modules: [EMPTY_MODULE; MAX_MODULES],
+            module_count: 0,
+            framebuffer: None,
+            cmdline: FixedString::new(),
+            boot_loader_name: FixedString::new(),
+            firmware: FirmwareTables {
+                acpi_rsdp: None,
+                apm_table: None,
+                rom_config_table: None,
+            },
+        }
+    }
+
+    /// Get physical memory regions.
+    ///
+    /// # Returns
+    /// - Physical memory regions slice.
+    pub fn memory_regions(&self) -> &[MemoryRegion] {
+        &self.memory_regions[..self.memory_region_count]
+    }
+
+    /// Get boot modules.
+    ///
+    /// # Returns
+    /// - Boot modules slice.
+    pub fn modules(&self) -> &[BootModule] {
+        &self.modules[..self.module_count]
+    }
+
+    /// Add physical memory region. Regions that do not fit are dropped.
+    ///
+    /// # Parameters
+    /// - `region` - given physical memory region to add.
+    ///
+    /// # Returns
+    /// - `true`  - if region was added.
+    /// - `false` - otherwise.
+    pub fn push_memory_region(&mut self, region: MemoryRegion) -> bool {
+        if self.memory_region_count >= MAX_MEMORY_REGIONS {
+            return false;
+        }
+
+        self.memory_regions[self.memory_region_count] = region;
+        self.memory_region_count += 1;
+        true
+    }
+
+    /// Add boot module. Modules that do not fit are dropped.
+    ///
+    /// # Parameters
+    /// - `module` - given boot module to add.
+    ///
+    /// # Returns
+    /// - `true`  - if module was added.
+    /// - `false` - otherwise.
+    pub fn push_module(&mut self, module: BootModule) -> bool {
+        if self.module_count >= MAX_MODULES {
+            return false;
+        }
+
+        self.modules[self.module_count] = module;
+        self.module_count += 1;
+        true
+    }
+
+    /// Construct boot information from multiboot info structure.
+    ///
+    /// # Parameters
+    /// - `info` - given multiboot info structure.
+    ///
+    /// # Returns
+    /// - New `BootInfo` object.
+    pub fn from_multiboot(info: &MultibootInfo) -> Self {
+        let mut boot_info = Self::new();
+
+        if let Some(mmap) = info.memory_map() {
+            for entry in mmap {
+                boot_info.push_memory_region(MemoryRegion {
+                    start: entry.addr,
+                    len: entry.len,
+                    rtype: MemoryRegionType::from(entry.mtype),
+                });
+            }
+        } else if info.has_flag(MULTIBOOT_INFO_MEMORY) {
+            // Fall back to basic lower/upper memory info (in KiB).
+            boot_info.push_memory_region(MemoryRegion {
+                start: 0,
+                len: info.mem_lower as u64 * 1024,
+                rtype: MemoryRegionType::Available,
+            });
+            boot_info.push_memory_region(MemoryRegion {
+                start: 0x100000,
+                len: info.mem_upper as u64 * 1024,
+                rtype: MemoryRegionType::Available,
+            });
+        }
+
+        if let Some(modules) = info.modules() {
+            for module in modules {
+                let cmdline = crate::multiboot::read_c_str(module.cmdline);
+
+                boot_info.push_module(BootModule {
+                    start: module.mod_start as u64,
+                    end: module.mod_end as u64,
+                    cmdline: FixedString::from_str(cmdline.unwrap_or("")),
+                });
+            }
+        }
+
+        if info.has_flag(MULTIBOOT_INFO_FRAMEBUFFER_INFO) {
+            boot_info.framebuffer = Some(FramebufferInfo {
+                addr: info.framebuffer_addr,
+                pitch: info.framebuffer_pitch,
+                width: info.framebuffer_width,
+                height: info.framebuffer_height,
+                bpp: info.framebuffer_bpp,
+            });
+        }
+
+        if let Some(cmdline) = info.cmdline() {
+            boot_info.cmdline = FixedString::from_str(cmdline);
+        }
+
+        if let Some(name) = info.boot_loader_name() {
+            boot_info.boot_loader_name = FixedString::from_str(name);
+        }
+
+        if info.has_flag(MULTIBOOT_INFO_APM_TABLE) {
+            boot_info.firmware.apm_table = Some(info.apm_table as u64);
+        }
+
+        if info.has_flag(MULTIBOOT_INFO_CONFIG_TABLE) {
+            boot_info.firmware.rom_config_table =
+                Some(info.config_table as u64);
+        }
+
+        boot_info
+    }
+
+    /// Construct boot information from multiboot2 boot information.
+    ///
+    /// # Parameters
+    /// - `info` - given multiboot2 boot information.
+    ///
+    /// # Returns
+    /// - New `BootInfo` object.
+    pub fn from_multiboot2(info: &Multiboot2Info) -> Self {
+        let mut boot_info = Self::new();
+
+        if let Some(mmap) = info.memory_map() {
+            for entry in mmap {
+                boot_info.push_memory_region(MemoryRegion {
+                    start: entry.addr,
+                    len: entry.len,
+                    rtype: MemoryRegionType::from(entry.mtype),
+                });
+            }
+        }
+
+        for module in info.modules() {
+            boot_info.push_module(BootModule {
+                start: module.start as u64,
+                end: module.end as u64,
+                cmdline: FixedString::from_str(module.cmdline.unwrap_or("")),
+            });
+        }
+
+        if let Some(tag) = info.framebuffer() {
+            boot_info.framebuffer = Some(FramebufferInfo {
+                addr: tag.framebuffer_addr,
+                pitch: tag.framebuffer_pitch,
+                width: tag.framebuffer_width,
+                height: tag.framebuffer_height,
+                bpp: tag.framebuffer_bpp,
+            });
+        }
+
+        if let Some(cmdline) = info.cmdline() {
+            boot_info.cmdline = FixedString::from_str(cmdline);
+        }
+
+        if let Some(name) = info.boot_loader_name() {
+            boot_info.boot_loader_name = FixedString::from_str(name);
+        }
+
+        boot_info.firmware.acpi_rsdp =
+            info.acpi_rsdp().map(|rsdp| match rsdp {
+                AcpiRsdp::V1(data) | AcpiRsdp::V2(data) => RsdpCopy::new(data),
+            });
+
+        boot_info
+    }
+}
+
+#[cfg(feature = "ktest")]
+pub mod tests {
+    use super::*;
+    use crate::{
+        ktest::TestCase,
+        multiboot::{
+            MULTIBOOT_INFO_CMDLINE, MULTIBOOT_INFO_MEM_MAP,
+            MULTIBOOT_INFO_MODS, MultibootModList,
+        },
+        multiboot2::{
+            MULTIBOOT2_TAG_TYPE_ACPI_NEW, MULTIBOOT2_TAG_TYPE_CMDLINE,
+            MULTIBOOT2_TAG_TYPE_END, MULTIBOOT2_TAG_TYPE_FRAMEBUFFER,
+            MULTIBOOT2_TAG_TYPE_MMAP,
+        },
+    };
+
+    /// Boot information unit tests.
+    pub const TESTS: &[TestCase] = &[
+        TestCase::new("fixed_string_truncates", fixed_string_truncates),
+        TestCase::new("memory_region_type", memory_region_type),
+        TestCase::new("from_multiboot", from_multiboot),
+        TestCase::new("from_multiboot_meminfo", from_multiboot_meminfo),
+        TestCase::new("from_multiboot2", from_multiboot2),
+    ];
+
+    /// Synthetic multiboot memory map entry (24 bytes).
+    #[repr(C, packed)]
+    struct RawMmapEntry {
+        size: u32,
+        addr: u64,
+        len: u64,
+        mtype: u32,
+    }
+
+    /// Synthetic 8-byte aligned multiboot2 boot information buffer.
+    #[repr(C, align(8))]
+    struct Multiboot2Buffer([u8; 256]);
+
+    /// Write little endian 32-bit value into buffer.
+    fn put_u32(buf: &mut [u8], offset: usize, value: u32) {
+        buf[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+    }
+
+    /// Write little endian 64-bit value into buffer.
+    fn put_u64(buf: &mut [u8], offset: usize, value: u64) {
+        buf[offset..offset + 8].copy_from_slice(&value.to_le_bytes());
+    }
+
+    fn fixed_string_truncates() {
+        let s: FixedString<4> = FixedString::from_str("abcdef");
+        assert_eq!(s.as_str(), "abcd");
+
+        // Multi-byte character must not be split.
+        let s: FixedString<4> = FixedString::from_str("abcé");
+        assert_eq!(s.as_str(), "abc");
+    }
+
+    fn memory_region_type() {
+        assert_eq!(MemoryRegionType::from(1), MemoryRegionType::Available);
+        assert_eq!(MemoryRegionType::from(5), MemoryRegionType::BadRam);
+        assert_eq!(MemoryRegionType::from(42), MemoryRegionType::Unknown(42));
+    }
+
+    fn from_multiboot() {
+        let mmap = [
+            RawMmapEntry {
+                size: 20,
+                addr: 0,
+                len: 0x9FC00,
+                mtype: 1,
+            },
+            RawMmapEntry {
+                size: 20,
+                addr: 0x100000,
+                len: 0x3EE0000,
+                mtype: 1,
+            },
+            RawMmapEntry {
+                size: 20,
+                addr: 0xFFFC0000,
+                len: 0x40000,
+                mtype: 2,
+            },
+        ];
+        let modules = [MultibootModList {
+            mod_start: 0x200000,
+            mod_end: 0x201000,
+            cmdline: c"init".as_ptr() as u32,
+            pad: 0,
+        }];
+
+        let info = MultibootInfo {
+            flags: MULTIBOOT_INFO_MEM_MAP
+                | MULTIBOOT_INFO_MODS
+                | MULTIBOOT_INFO_CMDLINE,
+            cmdline: c"loglevel=3 noterm".as_ptr() as u32,
+            mmap_addr: mmap.as_ptr() as u32,
+            mmap_length: size_of_val(&mmap) as u32,
+            mods_addr: modules.as_ptr() as u32,
+            mods_count: modules.len() as u32,
+            ..Default::default()
+        };
+
+        let boot_info = BootInfo::from_multiboot(&info);
+        let regions = boot_info.memory_regions();
+
+        assert_eq!(regions.len(), 3);
+        assert_eq!(regions[1].start, 0x100000);
+        assert_eq!(regions[1].end(), 0x3FE0000);
+        assert_eq!(regions[2].rtype, MemoryRegionType::Reserved);
+        assert_eq!(boot_info.modules().len(), 1);
+        assert_eq!(boot_info.modules()[0].end, 0x201000);
+        assert_eq!(boot_info.modules()[0].cmdline.as_str(), "init");
+        assert_eq!(boot_info.cmdline.as_str(), "loglevel=3 noterm");
+        assert!(boot_info.boot_loader_name.is_empty());
+        assert!(boot_info.framebuffer.is_none());
+    }
+
+    fn from_multiboot_meminfo() {
+        let info = MultibootInfo {
+            flags: MULTIBOOT_INFO_MEMORY,
+            mem_lower: 639,
+            mem_upper: 64512,
+            ..Default::default()
+        };
+
+        let boot_info = BootInfo::from_multiboot(&info);
+        let regions = boot_info.memory_regions();
+
+        assert_eq!(regions.len(), 2);
+        assert_eq!(regions[0].len, 639 * 1024);
+        assert_eq!(regions[1].start, 0x100000);
+        assert_eq!(regions[1].len, 64512 * 1024);
+    }
+
+    fn from_multiboot2() {
+        let mut buffer = Multiboot2Buffer([0; 256]);
+        let buf = &mut buffer.0;
+        let mut offset = 8;
+
+        // Command line tag.
+        put_u32(buf, offset, MULTIBOOT2_TAG_TYPE_CMDLINE);
+        put_u32(buf, offset + 4, 8 + 9);
+        buf[offset + 8..offset + 17].copy_from_slice(b"console=\0");
+        offset += 24;
+
+        // Memory map tag with two entries.
+        put_u32(buf, offset, MULTIBOOT2_TAG_TYPE_MMAP);
+        put_u32(buf, offset + 4, 16 + 2 * 24);
+        put_u32(buf, offset + 8, 24);
+        put_u64(buf, offset + 16, 0x100000);
+        put_u64(buf, offset + 24, 0x1000000);
+        put_u32(buf, offset + 32, 1);
+        put_u64(buf, offset + 40, 0xE0000);
+        put_u64(buf, offset + 48, 0x20000);
+        put_u32(buf, offset + 56, 7);
+        offset += 64;
+
+        // Framebuffer tag.
+        put_u32(buf, offset, MULTIBOOT2_TAG_TYPE_FRAMEBUFFER);
+        put_u32(buf, offset + 4, 32);
+        put_u64(buf, offset + 8, 0xFD000000);
+        put_u32(buf, offset + 16, 4096);
+        put_u32(buf, offset + 20, 1024);
+        put_u32(buf, offset + 24, 768);
+        buf[offset + 28] = 32;
+        offset += 32;
+
+        // ACPI 2.0+ RSDP tag.
+        put_u32(buf, offset, MULTIBOOT2_TAG_TYPE_ACPI_NEW);
+        put_u32(buf, offset + 4, 8 + RSDP_SIZE as u32);
+        buf[offset + 8..offset + 16].copy_from_slice(b"RSD PTR ");
+        offset += 48;
+
+        // Terminating tag.
+        put_u32(buf, offset, MULTIBOOT2_TAG_TYPE_END);
+        put_u32(buf, offset + 4, 8);
+        offset += 8;
+
+        put_u32(buf, 0, offset as u32);
+
+        let addr = buffer.0.as_ptr() as u32;
+        let info = unsafe { Multiboot2Info::from_addr(addr) }.unwrap();
+        let boot_info = BootInfo::from_multiboot2(&info);
+        let regions = boot_info.memory_regions();
+
+        assert_eq!(boot_info.cmdline.as_str(), "console=");
+        assert_eq!(regions.len(), 2);
+        assert_eq!(regions[0].rtype, MemoryRegionType::Available);
+        assert_eq!(regions[1].rtype, MemoryRegionType::Unknown(7));
+
+        let fb = boot_info.framebuffer.unwrap();
+        assert_eq!(fb.addr, 0xFD000000);
+        assert_eq!((fb.width, fb.height, fb.bpp), (1024, 768, 32));
+        assert_eq!(fb.size(), 4096 * 768);
+
+        let rsdp = boot_info.firmware.acpi_rsdp.unwrap();
+        assert_eq!(&rsdp.as_bytes()[..8], b"RSD PTR ");
+        assert!(boot_info.modules().is_empty());
+    }
+}
@@ -4,11 +4,13 @@
 
 //! VBE (VESA BIOS Extensions) driver.
 
+use crate::kernel::mm::mmio::MmioRegion;
+
 /// VESA framebuffer struct.
 #[derive(Debug, Default, Clone)]
 pub struct Framebuffer {
-    /// Framebuffer physical address.
-    pub addr: u32,
+    /// Framebuffer memory mapped with write-combining.
+    pub mem: MmioRegion,
     /// Number of bytes in a single row of the framebuffer.
     pub pitch: u32,
     /// Y-resolution.
@@ -26,6 +28,6 @@ impl Framebuffer {
     /// - Framebuffer address as mutable raw pointer.
     #[inline(always)]
     pub fn addr(&self) -> *mut u32 {
-        self.addr as *mut u32
+        self.mem.as_ptr()
     }
 }
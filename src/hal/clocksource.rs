@@ -0,0 +1,183 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Date: 2025-06-13
+// Author: Alexander Kuzin <alkuzindev@gmail.com>.
+
+//! Clocksource architecture-independent interface.
+//!
+//! # Description
+//! Clocksource is free running counter of known frequency. Architecture
+//! code provides all its clocksources, the one with the highest rating is
+//! selected once during boot & used for monotonic time since then. Time
+//! counted before selection (by system timer) is kept, so clock does not
+//! jump on switching.
+
+use crate::arch;
+use spin::Once;
+
+/// Number of nanoseconds in second.
+const NANOS_PER_SEC: u64 = 1_000_000_000;
+
+/// Clocksource architecture-independent interface.
+pub trait Clocksource: Sync {
+    /// Get clocksource name.
+    ///
+    /// # Returns
+    /// - Clocksource name.
+    fn name(&self) -> &'static str;
+
+    /// Get clocksource rating. Higher rating means better clocksource.
+    ///
+    /// # Returns
+    /// - Clocksource rating (zero if clocksource is not available).
+    fn rating(&self) -> u32;
+
+    /// Get counter frequency.
+    ///
+    /// # Returns
+    /// - Counter frequency in Hz.
+    fn frequency(&self) -> u64;
+
+    /// Read counter.
+    ///
+    /// # Returns
+    /// - Current counter value.
+    fn read(&self) -> u64;
+}
+
+/// Selected clocksource.
+struct Current {
+    /// Clocksource.
+    source: &'static dyn Clocksource,
+    /// Counter frequency in Hz.
+    frequency: u64,
+    /// Counter value at selection.
+    base_cycles: u64,
+    /// Time since boot at selection in nanoseconds.
+    base_ns: u64,
+}
+
+/// Selected clocksource.
+static CURRENT: Once<Current> = Once::new();
+
+/// Convert counter cycles to nanoseconds.
+///
+/// # Parameters
+/// - `cycles`    - given number of counter cycles.
+/// - `frequency` - given counter frequency in Hz.
+///
+/// # Returns
+/// - Number of nanoseconds.
+pub fn cycles_to_ns(cycles: u64, frequency: u64) -> u64 {
+    let secs = cycles / frequency;
+    let rest = cycles % frequency;
+
+    secs * NANOS_PER_SEC + rest * NANOS_PER_SEC / frequency
+}
+
+/// Get all clocksources of current architecture.
+///
+/// # Returns
+/// - Architecture clocksources.
+pub fn clocksources() -> &'static [&'static dyn Clocksource] {
+    #[cfg(target_arch = "x86")]
+    arch::x86::clocksources()
+}
+
+/// Select clocksource with the highest rating.
+///
+/// # Parameters
+/// - `uptime_ns` - given function to get time since boot before selection.
+///
+/// # Returns
+/// - Selected clocksource - in case of success.
+/// - `None`               - if there is no available clocksource.
+pub fn select(uptime_ns: fn() -> u64) -> Option<&'static dyn Clocksource> {
+    let source = clocksources()
+        .iter()
+        .filter(|source| source.rating() > 0)
+        .max_by_key(|source| source.rating())
+        .copied()?;
+
+    let current = CURRENT.call_once(|| Current {
+        source,
+        frequency: source.frequency(),
+        base_cycles: source.read(),
+        base_ns: uptime_ns(),
+    });
+
+    Some(current.source)
+}
+
+/// Get selected clocksource.
+///
+/// # Returns
+/// - Selected clocksource - in case of success.
+/// - `None`               - if clocksource is not selected yet.
+pub fn current() -> Option<&'static dyn Clocksource> {
+    CURRENT.get().map(|current| current.source)
+}
+
+/// Get time since boot measured by selected clocksource.
+///
+/// # Returns
+/// - Number of nanoseconds since boot - in case of success.
+/// - `None`                           - if clocksource is not selected yet.
+pub fn uptime_ns() -> Option<u64> {
+    let current = CURRENT.get()?;
+    let cycles = current.source.read().wrapping_sub(current.base_cycles);
+
+    Some(current.base_ns + cycles_to_ns(cycles, current.frequency))
+}
+
+#[cfg(feature = "ktest")]
+pub mod tests {
+    use super::*;
+    use crate::{
+        kernel::time::{self, Instant},
+        ktest::TestCase,
+    };
+
+    /// Clocksource unit tests.
+    pub const TESTS: &[TestCase] = &[
+        TestCase::new("cycles_conversion", cycles_conversion),
+        TestCase::new("best_is_selected", best_is_selected),
+        TestCase::new("clocksources_agree", clocksources_agree),
+    ];
+
+    fn cycles_conversion() {
+        assert_eq!(cycles_to_ns(0, 1000), 0);
+        assert_eq!(cycles_to_ns(1, 1000), 1_000_000);
+        assert_eq!(cycles_to_ns(1193182, 1193182), NANOS_PER_SEC);
+        assert_eq!(cycles_to_ns(3, 3_000_000_000), 1);
+
+        // A day of 4 GHz counter does not overflow.
+        let cycles = 4_000_000_000 * 3600 * 24;
+        assert_eq!(cycles_to_ns(cycles, 4_000_000_000), 86400 * NANOS_PER_SEC);
+    }
+
+    fn best_is_selected() {
+        let current = current().unwrap();
+
+        for source in clocksources() {
+            assert!(source.rating() <= current.rating());
+        }
+    }
+
+    fn clocksources_agree() {
+        let available = clocksources().iter().filter(|s| s.rating() > 0);
+
+        for source in available {
+            let cycles = source.read();
+            let start = Instant::now();
+
+            time::sleep_ms(20);
+
+            let cycles = source.read().wrapping_sub(cycles);
+            let elapsed = start.elapsed().as_nanos() as u64;
+            let measured = cycles_to_ns(cycles, source.frequency());
+
+            // Allow 5% difference (calibration error & read latency).
+            assert!(measured.abs_diff(elapsed) < elapsed / 20);
+        }
+    }
+}
@@ -87,3 +87,48 @@ pub fn get_cpu_info() -> CPUInfo {
     #[cfg(target_arch = "x86")]
     arch::x86::cpu::get_cpu_info()
 }
+
+/// Halt CPU until the next interrupt.
+pub fn halt() {
+    #[cfg(target_arch = "x86")]
+    arch::x86::cpu::halt();
+}
+
+/// Set kernel stack used on entry from user mode. Must be called on every
+/// context switch.
+///
+/// # Parameters
+/// - `top` - given top of the kernel stack of the running thread.
+pub fn set_kernel_stack(top: usize) {
+    #[cfg(target_arch = "x86")]
+    arch::x86::set_kernel_stack(top as u32);
+}
+
+/// Switch CPU to user mode & jump to user code.
+///
+/// # Parameters
+/// - `entry` - given user code entry point.
+/// - `stack` - given user stack top.
+///
+/// # Safety
+/// - `entry` & `stack` must be mapped with user access in current address
+///   space.
+/// - Kernel stack set by `set_kernel_stack` must be valid.
+pub unsafe fn enter_user_mode(entry: usize, stack: usize) -> ! {
+    #[cfg(target_arch = "x86")]
+    unsafe {
+        arch::x86::usermode::enter_user_mode(entry as u32, stack as u32)
+    }
+}
+
+/// Finish user code run by the kernel & return exit status to the kernel.
+///
+/// # Parameters
+/// - `status` - given exit status of user code.
+///
+/// # Returns
+/// - Returns only if no user code is run by the kernel.
+pub fn exit_user_mode(status: u32) {
+    #[cfg(target_arch = "x86")]
+    arch::x86::usermode::exit_user_mode(status);
+}
@@ -0,0 +1,449 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Date: 2025-06-13
+// Author: Alexander Kuzin <alkuzindev@gmail.com>.
+
+//! Hardware interrupts architecture-independent interface.
+//!
+//! # Description
+//! Drivers register handler of their IRQ line instead of busy-polling
+//! devices. IRQ lines are masked, unmasked & acknowledged through
+//! `InterruptController` selected by architecture-specific code, handlers
+//! only service the device. Handlers run with interrupts disabled and must
+//! not block or take locks that are held with interrupts enabled.
+
+use crate::arch;
+use core::{
+    cell::UnsafeCell,
+    fmt, mem,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// Number of IRQ lines.
+#[cfg(target_arch = "x86")]
+pub const IRQ_COUNT: usize = arch::x86::pic::IRQ_COUNT;
+
+/// IRQ handler. Receives number of IRQ being handled.
+pub type IrqHandler = fn(irq: u8);
+
+/// Interrupt controller architecture-independent interface.
+pub trait InterruptController: Sync {
+    /// Get interrupt controller name.
+    ///
+    /// # Returns
+    /// - Interrupt controller name.
+    fn name(&self) -> &'static str;
+
+    /// Mask IRQ line.
+    ///
+    /// # Parameters
+    /// - `irq` - given IRQ number.
+    fn mask(&self, irq: u8);
+
+    /// Unmask IRQ line.
+    ///
+    /// # Parameters
+    /// - `irq` - given IRQ number.
+    fn unmask(&self, irq: u8);
+
+    /// Acknowledge end of interrupt.
+    ///
+    /// # Parameters
+    /// - `irq` - given IRQ number.
+    fn eoi(&self, irq: u8);
+
+    /// Check whether IRQ is spurious (must not be dispatched & acknowledged).
+    ///
+    /// # Parameters
+    /// - `irq` - given IRQ number.
+    ///
+    /// # Returns
+    /// - `true`  - if IRQ is spurious.
+    /// - `false` - otherwise.
+    fn is_spurious(&self, irq: u8) -> bool;
+}
+
+/// IRQ registration errors enumeration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IrqError {
+    /// IRQ number is out of range.
+    InvalidIrq,
+    /// IRQ already has handler.
+    AlreadyRegistered,
+}
+
+impl fmt::Display for IrqError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            Self::InvalidIrq => "IRQ number is out of range",
+            Self::AlreadyRegistered => "IRQ handler is already registered",
+        };
+
+        f.write_str(msg)
+    }
+}
+
+/// Registered IRQ handlers (function addresses, zero if not registered).
+static HANDLERS: [AtomicUsize; IRQ_COUNT] =
+    [const { AtomicUsize::new(0) }; IRQ_COUNT];
+
+/// Number of handled interrupts of each IRQ line.
+static COUNTERS: [AtomicUsize; IRQ_COUNT] =
+    [const { AtomicUsize::new(0) }; IRQ_COUNT];
+
+/// Number of spurious IRQs detected.
+static SPURIOUS: AtomicUsize = AtomicUsize::new(0);
+
+/// Get interrupt controller in use.
+///
+/// # Returns
+/// - Interrupt controller selected by architecture-specific code.
+#[inline(always)]
+pub fn controller() -> &'static dyn InterruptController {
+    #[cfg(target_arch = "x86")]
+    arch::x86::interrupt_controller()
+}
+
+/// Register IRQ handler & unmask IRQ line.
+///
+/// # Parameters
+/// - `irq`     - given IRQ number.
+/// - `handler` - given IRQ handler.
+///
+/// # Returns
+/// - `Ok`  - in case of success.
+/// - `Err` - otherwise.
+pub fn register_irq_handler(
+    irq: u8,
+    handler: IrqHandler,
+) -> Result<(), IrqError> {
+    let slot = HANDLERS.get(irq as usize).ok_or(IrqError::InvalidIrq)?;
+
+    slot.compare_exchange(
+        0,
+        handler as usize,
+        Ordering::AcqRel,
+        Ordering::Relaxed,
+    )
+    .map_err(|_| IrqError::AlreadyRegistered)?;
+
+    controller().unmask(irq);
+    Ok(())
+}
+
+/// Mask IRQ line & unregister its handler.
+///
+/// # Parameters
+/// - `irq` - given IRQ number.
+///
+/// # Returns
+/// - `Ok`  - in case of success.
+/// - `Err` - otherwise.
+pub fn unregister_irq_handler(irq: u8) -> Result<(), IrqError> {
+    let slot = HANDLERS.get(irq as usize).ok_or(IrqError::InvalidIrq)?;
+
+    controller().mask(irq);
+    slot.store(0, Ordering::Release);
+    Ok(())
+}
+
+/// Unmask IRQ lines that have handlers. Called by architecture-specific
+/// code after switching interrupt controller.
+pub fn unmask_registered() {
+    let controller = controller();
+
+    for (irq, slot) in HANDLERS.iter().enumerate() {
+        if slot.load(Ordering::Acquire) != 0 {
+            controller.unmask(irq as u8);
+        }
+    }
+}
+
+/// Call handler of IRQ.
+///
+/// # Parameters
+/// - `irq` - given IRQ number.
+///
+/// # Returns
+/// - `true`  - if IRQ has handler.
+/// - `false` - otherwise.
+pub fn dispatch(irq: u8) -> bool {
+    let Some(slot) = HANDLERS.get(irq as usize) else {
+        return false;
+    };
+
+    let handler = slot.load(Ordering::Acquire);
+
+    if handler == 0 {
+        return false;
+    }
+
+    COUNTERS[irq as usize].fetch_add(1, Ordering::Relaxed);
+
+    // Only valid `IrqHandler` addresses are stored in the table.
+    let handler = unsafe { mem::transmute::<usize, IrqHandler>(handler) };
+    handler(irq);
+
+    true
+}
+
+/// Handle IRQ: filter spurious one, call its handler & acknowledge it.
+/// Called by architecture-specific code.
+///
+/// # Parameters
+/// - `irq` - given IRQ number.
+pub fn handle(irq: u8) {
+    let controller = controller();
+
+    if controller.is_spurious(irq) {
+        SPURIOUS.fetch_add(1, Ordering::Relaxed);
+        return;
+    }
+
+    dispatch(irq);
+    controller.eoi(irq);
+}
+
+/// Get number of spurious IRQs detected.
+///
+/// # Returns
+/// - Number of spurious IRQs.
+pub fn spurious_count() -> usize {
+    SPURIOUS.load(Ordering::Relaxed)
+}
+
+/// Get number of handled interrupts of IRQ line.
+///
+/// # Parameters
+/// - `irq` - given IRQ number.
+///
+/// # Returns
+/// - Number of interrupts passed to IRQ handler.
+pub fn irq_count(irq: u8) -> usize {
+    COUNTERS
+        .get(irq as usize)
+        .map_or(0, |counter| counter.load(Ordering::Relaxed))
+}
+
+/// Enable hardware interrupts.
+#[inline(always)]
+pub fn enable() {
+    #[cfg(target_arch = "x86")]
+    arch::x86::cpu::enable_interrupts();
+}
+
+/// Disable hardware interrupts.
+#[inline(always)]
+pub fn disable() {
+    #[cfg(target_arch = "x86")]
+    arch::x86::cpu::disable_interrupts();
+}
+
+/// Check whether hardware interrupts are enabled.
+///
+/// # Returns
+/// - `true`  - if interrupts are enabled.
+/// - `false` - otherwise.
+#[inline(always)]
+pub fn are_enabled() -> bool {
+    #[cfg(target_arch = "x86")]
+    arch::x86::cpu::are_interrupts_enabled()
+}
+
+/// Enable hardware interrupts & wait for the next one atomically, so that
+/// interrupt arriving right after enabling is not missed.
+#[inline(always)]
+pub fn enable_and_wait() {
+    #[cfg(target_arch = "x86")]
+    arch::x86::cpu::enable_interrupts_and_halt();
+}
+
+/// Run function with hardware interrupts disabled.
+///
+/// # Parameters
+/// - `func` - given function to run.
+///
+/// # Returns
+/// - Value returned by `func`.
+pub fn without_interrupts<T>(func: impl FnOnce() -> T) -> T {
+    let enabled = are_enabled();
+
+    disable();
+    let result = func();
+
+    if enabled {
+        enable();
+    }
+
+    result
+}
+
+/// Byte queue filled by IRQ handler & drained by kernel code (single
+/// producer, single consumer). Bytes are dropped when queue is full.
+pub struct IrqQueue<const N: usize> {
+    /// Queue storage.
+    buffer: UnsafeCell<[u8; N]>,
+    /// Total number of bytes pushed.
+    head: AtomicUsize,
+    /// Total number of bytes popped.
+    tail: AtomicUsize,
+}
+
+// Producer only writes free slots, consumer only reads filled ones.
+unsafe impl<const N: usize> Sync for IrqQueue<N> {}
+
+impl<const N: usize> IrqQueue<N> {
+    /// Construct new empty queue.
+    ///
+    /// # Returns
+    /// - New `IrqQueue` object.
+    pub const fn new() -> Self {
+        Self {
+            buffer: UnsafeCell::new([0; N]),
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+        }
+    }
+
+    /// Push byte (producer side).
+    ///
+    /// # Parameters
+    /// - `byte` - given byte to push.
+    ///
+    /// # Returns
+    /// - `true`  - in case of success.
+    /// - `false` - if queue is full.
+    pub fn push(&self, byte: u8) -> bool {
+        let head = self.head.load(Ordering::Relaxed);
+
+        if head.wrapping_sub(self.tail.load(Ordering::Acquire)) == N {
+            return false;
+        }
+
+        unsafe {
+            (*self.buffer.get())[head % N] = byte;
+        }
+
+        self.head.store(head.wrapping_add(1), Ordering::Release);
+        true
+    }
+
+    /// Pop byte (consumer side).
+    ///
+    /// # Returns
+    /// - Oldest byte - in case of success.
+    /// - `None`      - if queue is empty.
+    pub fn pop(&self) -> Option<u8> {
+        let tail = self.tail.load(Ordering::Relaxed);
+
+        if tail == self.head.load(Ordering::Acquire) {
+            return None;
+        }
+
+        let byte = unsafe { (*self.buffer.get())[tail % N] };
+
+        self.tail.store(tail.wrapping_add(1), Ordering::Release);
+        Some(byte)
+    }
+
+    /// Pop byte waiting for interrupts until queue is not empty.
+    ///
+    /// # Returns
+    /// - Oldest byte.
+    pub fn pop_wait(&self) -> u8 {
+        loop {
+            // Check & wait atomically, so that wake up is not missed.
+            disable();
+
+            if let Some(byte) = self.pop() {
+                enable();
+                return byte;
+            }
+
+            enable_and_wait();
+        }
+    }
+}
+
+impl<const N: usize> Default for IrqQueue<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "ktest")]
+pub mod tests {
+    use super::*;
+    use crate::ktest::TestCase;
+    use core::arch::asm;
+
+    #[cfg(target_arch = "x86")]
+    use arch::x86::pic::IRQ_BASE;
+
+    /// IRQ unit tests.
+    pub const TESTS: &[TestCase] = &[
+        TestCase::new("register_rejects_invalid", register_rejects_invalid),
+        TestCase::new("software_irq_dispatch", software_irq_dispatch),
+        TestCase::new("queue_wraps_around", queue_wraps_around),
+    ];
+
+    /// Unused IRQ line (second parallel port).
+    const TEST_IRQ: u8 = 5;
+
+    /// Number of test handler calls.
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    fn test_handler(irq: u8) {
+        assert_eq!(irq, TEST_IRQ);
+        CALLS.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn register_rejects_invalid() {
+        assert_eq!(
+            register_irq_handler(IRQ_COUNT as u8, test_handler),
+            Err(IrqError::InvalidIrq)
+        );
+        assert_eq!(
+            unregister_irq_handler(IRQ_COUNT as u8),
+            Err(IrqError::InvalidIrq)
+        );
+    }
+
+    fn software_irq_dispatch() {
+        assert_eq!(register_irq_handler(TEST_IRQ, test_handler), Ok(()));
+        assert_eq!(
+            register_irq_handler(TEST_IRQ, test_handler),
+            Err(IrqError::AlreadyRegistered)
+        );
+
+        let calls = CALLS.load(Ordering::Relaxed);
+        let count = irq_count(TEST_IRQ);
+
+        #[cfg(target_arch = "x86")]
+        unsafe {
+            asm!("int {}", const IRQ_BASE + TEST_IRQ, options(nomem));
+        }
+
+        assert_eq!(CALLS.load(Ordering::Relaxed), calls + 1);
+        assert_eq!(irq_count(TEST_IRQ), count + 1);
+        assert_eq!(unregister_irq_handler(TEST_IRQ), Ok(()));
+        assert!(!dispatch(TEST_IRQ));
+    }
+
+    fn queue_wraps_around() {
+        let queue = IrqQueue::<4>::new();
+
+        for round in 0..3u8 {
+            for i in 0..4 {
+                assert!(queue.push(round * 4 + i));
+            }
+
+            assert!(!queue.push(0xFF));
+
+            for i in 0..4 {
+                assert_eq!(queue.pop(), Some(round * 4 + i));
+            }
+
+            assert_eq!(queue.pop(), None);
+        }
+    }
+}
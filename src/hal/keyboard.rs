@@ -4,7 +4,10 @@
 
 //! Keyboard driver architecture-independent declarations.
 
-use crate::arch;
+use crate::{
+    arch,
+    kernel::cmdline::{Choice, Param, ParamKind, kernel_param},
+};
 
 /// Keyboard key enumeration.
 #[derive(Debug)]
@@ -28,6 +31,36 @@ pub enum Key {
     Char(char),
 }
 
+/// Keyboard layout enumeration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Keymap {
+    /// US QWERTY layout.
+    Us,
+    /// UK QWERTY layout.
+    Uk,
+}
+
+/// Selected keyboard layout.
+static KEYMAP: Choice<Keymap> =
+    Choice::new(Keymap::Us, &[("us", Keymap::Us), ("uk", Keymap::Uk)]);
+
+kernel_param! {
+    /// Kernel parameter selecting keyboard layout (`keymap=us|uk`).
+    static KEYMAP_PARAM = Param {
+        name: "keymap",
+        description: "Keyboard layout",
+        kind: ParamKind::Choice(&KEYMAP),
+    };
+}
+
+/// Get selected keyboard layout.
+///
+/// # Returns
+/// - Selected keyboard layout.
+pub fn keymap() -> Keymap {
+    KEYMAP.get()
+}
+
 /// Read keyboard key.
 ///
 /// # Returns
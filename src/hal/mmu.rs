@@ -0,0 +1,350 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Date: 2025-06-13
+// Author: Alexander Kuzin <alkuzindev@gmail.com>.
+
+//! MMU (Memory Management Unit) architecture-independent interface.
+
+use crate::{arch, kernel::mm::PhysAddr};
+use core::{fmt, ops::BitOr};
+
+/// Size of the smallest page in bytes.
+pub const PAGE_SIZE: usize = 4096;
+
+/// Page mapping flags. Mapped pages are always present & readable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MapFlags(u32);
+
+impl MapFlags {
+    /// Read-only kernel page.
+    pub const READ: Self = Self(0);
+    /// Page is writable.
+    pub const WRITABLE: Self = Self(1 << 0);
+    /// Page is accessible from user mode.
+    pub const USER: Self = Self(1 << 1);
+    /// Page caching is disabled (for MMIO).
+    pub const NO_CACHE: Self = Self(1 << 2);
+    /// Page is executable. Without this flag pages are non-executable if
+    /// the architecture supports it.
+    pub const EXECUTABLE: Self = Self(1 << 3);
+    /// Writes to page are combined in buffers (for framebuffers). Caching
+    /// is disabled instead if the architecture does not support it.
+    pub const WRITE_COMBINE: Self = Self(1 << 4);
+
+    /// Check whether all given flags are set.
+    ///
+    /// # Parameters
+    /// - `other` - given flags to check.
+    ///
+    /// # Returns
+    /// - `true`  - if all flags of `other` are set.
+    /// - `false` - otherwise.
+    #[inline(always)]
+    pub const fn contains(self, other: Self) -> bool {
+        (self.0 & other.0) == other.0
+    }
+}
+
+impl BitOr for MapFlags {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+/// Page mapping errors enumeration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MapError {
+    /// Virtual or physical address is not page aligned.
+    Unaligned,
+    /// Virtual address is already mapped.
+    AlreadyMapped,
+    /// No memory for paging structures.
+    NoMemory,
+    /// Physical address is not addressable by paging structures.
+    OutOfRange,
+}
+
+impl fmt::Display for MapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            Self::Unaligned => "address is not page aligned",
+            Self::AlreadyMapped => "page is already mapped",
+            Self::NoMemory => "no memory for paging structures",
+            Self::OutOfRange => "physical address is out of range",
+        };
+
+        f.write_str(msg)
+    }
+}
+
+/// Page fault description decoded from architecture-specific state.
+#[derive(Debug, Clone, Copy)]
+pub struct PageFault {
+    /// Faulting virtual address.
+    pub addr: usize,
+    /// Address of the faulting instruction.
+    pub ip: usize,
+    /// Fault was caused by write access (read access otherwise).
+    pub write: bool,
+    /// Fault was caused by instruction fetch.
+    pub exec: bool,
+    /// Fault happened in user mode.
+    pub user: bool,
+    /// Page was present (protection violation).
+    pub present: bool,
+}
+
+impl fmt::Display for PageFault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let access = if self.exec {
+            "execute"
+        } else if self.write {
+            "write"
+        } else {
+            "read"
+        };
+
+        write!(
+            f,
+            "{} access to {:#010X} at {:#010X} ({} mode, {})",
+            access,
+            self.addr,
+            self.ip,
+            if self.user { "user" } else { "kernel" },
+            if self.present {
+                "protection violation"
+            } else {
+                "page not present"
+            },
+        )
+    }
+}
+
+/// Page directory architecture-independent interface.
+pub trait PageDirectoryInterface: Sized {
+    /// Construct new page directory sharing kernel part of address space.
+    ///
+    /// # Returns
+    /// - New page directory - in case of success.
+    /// - `None`             - otherwise.
+    fn new() -> Option<Self>;
+
+    /// Map virtual page to physical frame.
+    ///
+    /// # Parameters
+    /// - `vaddr` - given page aligned virtual address.
+    /// - `paddr` - given page aligned physical address.
+    /// - `flags` - given page mapping flags.
+    ///
+    /// # Returns
+    /// - `Ok`  - in case of success.
+    /// - `Err` - otherwise.
+    fn map(
+        &mut self,
+        vaddr: usize,
+        paddr: PhysAddr,
+        flags: MapFlags,
+    ) -> Result<(), MapError>;
+
+    /// Unmap virtual page.
+    ///
+    /// # Parameters
+    /// - `vaddr` - given page aligned virtual address.
+    ///
+    /// # Returns
+    /// - Physical address the page was mapped to - in case of success.
+    /// - `None`                                  - if page was not mapped.
+    fn unmap(&mut self, vaddr: usize) -> Option<PhysAddr>;
+
+    /// Change mapping flags of virtual page.
+    ///
+    /// # Parameters
+    /// - `vaddr` - given page aligned virtual address.
+    /// - `flags` - given new page mapping flags.
+    ///
+    /// # Returns
+    /// - `Some` - in case of success.
+    /// - `None` - if page was not mapped.
+    fn protect(&mut self, vaddr: usize, flags: MapFlags) -> Option<()>;
+
+    /// Translate virtual address.
+    ///
+    /// # Parameters
+    /// - `vaddr` - given virtual address.
+    ///
+    /// # Returns
+    /// - Physical address - in case of success.
+    /// - `None`           - if address is not mapped.
+    fn translate(&self, vaddr: usize) -> Option<PhysAddr>;
+
+    /// Switch to this page directory.
+    ///
+    /// # Safety
+    /// - Currently executed code, stack & kernel data must be mapped.
+    unsafe fn activate(&self);
+
+    /// Check whether this page directory is currently used by the CPU.
+    ///
+    /// # Returns
+    /// - `true`  - if page directory is active.
+    /// - `false` - otherwise.
+    fn is_active(&self) -> bool;
+}
+
+/// Alias for architecture-specific page directory struct.
+#[cfg(target_arch = "x86")]
+pub type PageDirectory = arch::x86::paging::PageDirectory;
+
+/// Map virtual page to physical frame in kernel address space.
+///
+/// # Parameters
+/// - `vaddr` - given page aligned virtual address.
+/// - `paddr` - given page aligned physical address.
+/// - `flags` - given page mapping flags.
+///
+/// # Returns
+/// - `Ok`  - in case of success.
+/// - `Err` - otherwise.
+pub fn map(
+    vaddr: usize,
+    paddr: PhysAddr,
+    flags: MapFlags,
+) -> Result<(), MapError> {
+    #[cfg(target_arch = "x86")]
+    arch::x86::paging::map(vaddr, paddr, flags)
+}
+
+/// Map contiguous virtual range to physical range in kernel address space.
+/// On failure already mapped part of the range is unmapped.
+///
+/// # Parameters
+/// - `vaddr` - given page aligned virtual address.
+/// - `paddr` - given page aligned physical address.
+/// - `size`  - given range size in bytes (rounded up to pages).
+/// - `flags` - given page mapping flags.
+///
+/// # Returns
+/// - `Ok`  - in case of success.
+/// - `Err` - otherwise.
+pub fn map_range(
+    vaddr: usize,
+    paddr: PhysAddr,
+    size: usize,
+    flags: MapFlags,
+) -> Result<(), MapError> {
+    for offset in (0..size).step_by(PAGE_SIZE) {
+        if let Err(err) = map(vaddr + offset, paddr + offset as PhysAddr, flags)
+        {
+            for mapped in (0..offset).step_by(PAGE_SIZE) {
+                unmap(vaddr + mapped);
+            }
+
+            return Err(err);
+        }
+    }
+
+    Ok(())
+}
+
+/// Unmap virtual page from kernel address space.
+///
+/// # Parameters
+/// - `vaddr` - given page aligned virtual address.
+///
+/// # Returns
+/// - Physical address the page was mapped to - in case of success.
+/// - `None`                                  - if page was not mapped.
+pub fn unmap(vaddr: usize) -> Option<PhysAddr> {
+    #[cfg(target_arch = "x86")]
+    arch::x86::paging::unmap(vaddr)
+}
+
+/// Map physical frame at its direct virtual address (see `phys_to_virt`)
+/// in kernel address space. Kernel address space lock is not taken, so
+/// frames can be mapped while paging structures are being allocated.
+///
+/// # Parameters
+/// - `paddr` - given physical address of the frame below
+///   `DIRECT_MAP_LIMIT`.
+///
+/// # Returns
+/// - `Ok`  - in case of success.
+/// - `Err` - otherwise.
+pub fn map_direct(paddr: PhysAddr) -> Result<(), MapError> {
+    #[cfg(target_arch = "x86")]
+    arch::x86::paging::map_direct(paddr)
+}
+
+/// Unmap physical frame from its direct virtual address in kernel address
+/// space.
+///
+/// # Parameters
+/// - `paddr` - given physical address of the frame below
+///   `DIRECT_MAP_LIMIT`.
+pub fn unmap_direct(paddr: PhysAddr) {
+    #[cfg(target_arch = "x86")]
+    arch::x86::paging::unmap_direct(paddr);
+}
+
+/// Translate virtual address of kernel address space.
+///
+/// # Parameters
+/// - `vaddr` - given virtual address.
+///
+/// # Returns
+/// - Physical address - in case of success.
+/// - `None`           - if address is not mapped.
+pub fn translate(vaddr: usize) -> Option<PhysAddr> {
+    #[cfg(target_arch = "x86")]
+    arch::x86::paging::translate(vaddr)
+}
+
+/// Get end of physical memory addressable by paging structures.
+///
+/// # Returns
+/// - Physical address limit (exclusive).
+pub fn phys_addr_limit() -> PhysAddr {
+    #[cfg(target_arch = "x86")]
+    arch::x86::paging::phys_addr_limit()
+}
+
+/// Invalidate TLB entry of single page.
+///
+/// # Parameters
+/// - `vaddr` - given virtual address of the page.
+pub fn flush_tlb(vaddr: usize) {
+    #[cfg(target_arch = "x86")]
+    arch::x86::paging::flush_tlb(vaddr);
+}
+
+/// Invalidate all TLB entries.
+pub fn flush_tlb_all() {
+    #[cfg(target_arch = "x86")]
+    arch::x86::paging::flush_tlb_all();
+}
+
+/// Create empty kernel address space.
+///
+/// # Parameters
+/// - `kernel_base` - given start of the kernel part of address space.
+///
+/// # Returns
+/// - `Ok`  - in case of success.
+/// - `Err` - otherwise.
+pub fn init(kernel_base: usize) -> Result<(), MapError> {
+    #[cfg(target_arch = "x86")]
+    arch::x86::paging::init(kernel_base)
+}
+
+/// Switch to kernel address space dropping bootloader mappings.
+pub fn activate() {
+    #[cfg(target_arch = "x86")]
+    arch::x86::paging::activate();
+}
+
+/// Switch back to kernel address space from user address space.
+pub fn activate_kernel() {
+    #[cfg(target_arch = "x86")]
+    arch::x86::paging::activate_kernel();
+}
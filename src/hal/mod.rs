@@ -6,12 +6,24 @@
 
 use crate::arch;
 
+pub mod clocksource;
 pub mod cpu;
+pub mod irq;
 pub mod uart;
 pub mod keyboard;
+pub mod mmu;
+pub mod rtc;
+pub mod timer;
 
 /// Initialize architecture-specific part of the kernel.
 pub fn init() {
     #[cfg(target_arch = "x86")]
     arch::x86::init();
 }
+
+/// Initialize architecture-specific part of the kernel that requires memory
+/// management.
+pub fn init_late() {
+    #[cfg(target_arch = "x86")]
+    arch::x86::init_late();
+}
@@ -0,0 +1,16 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Date: 2025-06-13
+// Author: Alexander Kuzin <alkuzindev@gmail.com>.
+
+//! Real-time clock architecture-independent interface.
+
+use crate::{arch, kernel::time::DateTime};
+
+/// Read current date & time from real-time clock.
+///
+/// # Returns
+/// - Current date & time (UTC).
+pub fn read() -> DateTime {
+    #[cfg(target_arch = "x86")]
+    arch::x86::drivers::rtc::read()
+}
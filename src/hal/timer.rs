@@ -0,0 +1,70 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Date: 2025-06-13
+// Author: Alexander Kuzin <alkuzindev@gmail.com>.
+
+//! System timer architecture-independent interface.
+
+use super::clocksource;
+use crate::{
+    arch,
+    kernel::cmdline::{Param, ParamKind, kernel_param},
+};
+use core::sync::atomic::{AtomicU32, Ordering};
+
+/// Default system timer tick rate in Hz.
+pub const DEFAULT_TICK_RATE: u32 = 1000;
+
+/// Selected system timer tick rate in Hz.
+static TICK_RATE: AtomicU32 = AtomicU32::new(DEFAULT_TICK_RATE);
+
+#[cfg(target_arch = "x86")]
+kernel_param! {
+    /// Kernel parameter setting system timer tick rate (`timer_hz=19..10000`).
+    static TIMER_HZ_PARAM = Param {
+        name: "timer_hz",
+        description: "System timer tick rate in Hz",
+        kind: ParamKind::U32 {
+            value: &TICK_RATE,
+            min: arch::x86::drivers::pit::MIN_FREQUENCY,
+            max: arch::x86::drivers::pit::MAX_FREQUENCY,
+        },
+    };
+}
+
+/// Get tick rate selected on kernel command line.
+///
+/// # Returns
+/// - Requested tick rate in Hz.
+pub fn requested_tick_rate() -> u32 {
+    TICK_RATE.load(Ordering::Relaxed)
+}
+
+/// Get system timer tick rate.
+///
+/// # Returns
+/// - Tick rate in Hz (zero if timer is not initialized).
+pub fn tick_rate() -> u32 {
+    #[cfg(target_arch = "x86")]
+    arch::x86::drivers::pit::frequency()
+}
+
+/// Get number of system timer ticks.
+///
+/// # Returns
+/// - Number of timer interrupts.
+pub fn ticks() -> u64 {
+    #[cfg(target_arch = "x86")]
+    arch::x86::drivers::pit::ticks()
+}
+
+/// Get time since system timer initialization. Selected clocksource is
+/// used if there is one, otherwise system timer is read.
+///
+/// # Returns
+/// - Number of nanoseconds since boot.
+pub fn uptime_ns() -> u64 {
+    clocksource::uptime_ns().unwrap_or_else(|| {
+        #[cfg(target_arch = "x86")]
+        arch::x86::drivers::pit::elapsed_ns()
+    })
+}
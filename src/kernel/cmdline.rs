@@ -0,0 +1,364 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Date: 2025-06-13
+// Author: Alexander Kuzin <alkuzindev@gmail.com>.
+
+//! Kernel command line parameters.
+//!
+//! # Description
+//! Kernel modules declare typed parameters with their own storage next to
+//! their code by `kernel_param!`. The linker collects declared parameters
+//! into `.kernel_params` section, they are set from the bootloader provided
+//! command line early during kernel initialization.
+//!
+//! Command line consists of whitespace separated `name=value` pairs and
+//! `name` flags.
+
+use crate::log;
+use core::{
+    fmt, slice,
+    sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering},
+};
+
+// Kernel parameters section bounds (declared in linker file).
+unsafe extern "C" {
+    static kernel_params_begin: u32;
+    static kernel_params_end: u32;
+}
+
+/// Kernel parameter type & storage.
+pub enum ParamKind {
+    /// Boolean flag set by parameter presence (`name`).
+    Flag(&'static AtomicBool),
+    /// Unsigned integer in specific range (`name=42`).
+    U32 {
+        /// Parameter value storage.
+        value: &'static AtomicU32,
+        /// Minimal allowed value.
+        min: u32,
+        /// Maximal allowed value.
+        max: u32,
+    },
+    /// One of the listed values (`name=choice`).
+    Choice(&'static dyn ChoiceStorage),
+}
+
+/// Type independent storage of `Choice` parameter.
+pub trait ChoiceStorage: Sync {
+    /// Select value by its name.
+    ///
+    /// # Parameters
+    /// - `name` - given value name.
+    ///
+    /// # Returns
+    /// - `Ok`                       - in case of success.
+    /// - `ParamError::InvalidValue` - if there is no such value.
+    fn select(&self, name: &str) -> Result<(), ParamError>;
+}
+
+/// Storage of parameter choosing one of the listed values.
+pub struct Choice<T: 'static> {
+    /// Index of the chosen value (`usize::MAX` if default is used).
+    index: AtomicUsize,
+    /// Value used until other one is chosen.
+    default: T,
+    /// Allowed values with their names.
+    choices: &'static [(&'static str, T)],
+}
+
+impl<T: Copy> Choice<T> {
+    /// Construct new choice storage.
+    ///
+    /// # Parameters
+    /// - `default` - given value used until other one is chosen.
+    /// - `choices` - given allowed values with their names.
+    ///
+    /// # Returns
+    /// - New `Choice` object.
+    pub const fn new(
+        default: T,
+        choices: &'static [(&'static str, T)],
+    ) -> Self {
+        Self {
+            index: AtomicUsize::new(usize::MAX),
+            default,
+            choices,
+        }
+    }
+
+    /// Get chosen value.
+    ///
+    /// # Returns
+    /// - Chosen value or default one.
+    pub fn get(&self) -> T {
+        let index = self.index.load(Ordering::Relaxed);
+        self.choices
+            .get(index)
+            .map_or(self.default, |&(_, value)| value)
+    }
+}
+
+impl<T: Sync> ChoiceStorage for Choice<T> {
+    fn select(&self, name: &str) -> Result<(), ParamError> {
+        let index = self
+            .choices
+            .iter()
+            .position(|&(choice, _)| choice == name)
+            .ok_or(ParamError::InvalidValue)?;
+
+        self.index.store(index, Ordering::Relaxed);
+        Ok(())
+    }
+}
+
+/// Kernel command line parameter.
+pub struct Param {
+    /// Parameter name.
+    pub name: &'static str,
+    /// Parameter short description.
+    pub description: &'static str,
+    /// Parameter type & storage.
+    pub kind: ParamKind,
+}
+
+/// Kernel command line parameter errors enumeration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParamError {
+    /// No parameter with such name.
+    Unknown,
+    /// Parameter requires value.
+    MissingValue,
+    /// Flag parameter was given a value.
+    UnexpectedValue,
+    /// Parameter value can not be parsed.
+    InvalidValue,
+    /// Parameter value is out of allowed range.
+    OutOfRange,
+}
+
+impl fmt::Display for ParamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            Self::Unknown => "unknown parameter",
+            Self::MissingValue => "missing value",
+            Self::UnexpectedValue => "flag does not take a value",
+            Self::InvalidValue => "invalid value",
+            Self::OutOfRange => "value is out of range",
+        };
+
+        f.write_str(msg)
+    }
+}
+
+impl Param {
+    /// Set parameter value.
+    ///
+    /// # Parameters
+    /// - `value` - given raw parameter value (`None` for flags).
+    ///
+    /// # Returns
+    /// - `Ok`  - in case of success.
+    /// - `Err` - otherwise.
+    pub fn set(&self, value: Option<&str>) -> Result<(), ParamError> {
+        match self.kind {
+            ParamKind::Flag(flag) => {
+                if value.is_some() {
+                    return Err(ParamError::UnexpectedValue);
+                }
+
+                flag.store(true, Ordering::Relaxed);
+            }
+            ParamKind::U32 {
+                value: storage,
+                min,
+                max,
+            } => {
+                let value = value.ok_or(ParamError::MissingValue)?;
+                let value: u32 =
+                    value.parse().map_err(|_| ParamError::InvalidValue)?;
+
+                if value < min || value > max {
+                    return Err(ParamError::OutOfRange);
+                }
+
+                storage.store(value, Ordering::Relaxed);
+            }
+            ParamKind::Choice(storage) => {
+                storage.select(value.ok_or(ParamError::MissingValue)?)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Declare kernel command line parameter & put reference to it into
+/// `.kernel_params` section.
+#[macro_export]
+macro_rules! kernel_param {
+    ($(#[$meta:meta])* $vis:vis static $name:ident = $param:expr;) => {
+        $(#[$meta])*
+        $vis static $name: $crate::kernel::cmdline::Param = $param;
+
+        const _: () = {
+            #[used]
+            #[unsafe(link_section = ".kernel_params")]
+            static PARAM: &$crate::kernel::cmdline::Param = &$name;
+        };
+    };
+}
+
+// Re-export kernel parameter macro rule.
+pub(crate) use kernel_param;
+
+/// Get all declared kernel parameters.
+///
+/// # Returns
+/// - Kernel parameters collected by the linker.
+pub fn params() -> &'static [&'static Param] {
+    let begin = &raw const kernel_params_begin as *const &'static Param;
+    let end = &raw const kernel_params_end as usize;
+    let count = (end - begin as usize) / size_of::<&Param>();
+
+    unsafe { slice::from_raw_parts(begin, count) }
+}
+
+/// Find kernel parameter by name.
+///
+/// # Parameters
+/// - `name` - given parameter name.
+///
+/// # Returns
+/// - Kernel parameter - in case of success.
+/// - `None`           - otherwise.
+pub fn find(name: &str) -> Option<&'static Param> {
+    params().iter().find(|param| param.name == name).copied()
+}
+
+/// Set single command line argument.
+///
+/// # Parameters
+/// - `arg` - given `name=value` pair or `name` flag.
+///
+/// # Returns
+/// - `Ok`  - in case of success.
+/// - `Err` - otherwise.
+fn set_arg(arg: &str) -> Result<(), ParamError> {
+    let (name, value) = match arg.split_once('=') {
+        Some((name, value)) => (name, Some(value)),
+        None => (arg, None),
+    };
+
+    find(name).ok_or(ParamError::Unknown)?.set(value)
+}
+
+/// Parse kernel command line & set declared parameters.
+///
+/// # Parameters
+/// - `cmdline` - given kernel command line.
+pub fn parse(cmdline: &str) {
+    let mut args = cmdline.split_ascii_whitespace().peekable();
+
+    // Multiboot bootloaders pass kernel image path as the first argument.
+    if args.peek().is_some_and(|arg| arg.starts_with('/')) {
+        args.next();
+    }
+
+    for arg in args {
+        if let Err(err) = set_arg(arg) {
+            log::fail!("Kernel parameter <{}>: {}", arg, err);
+        }
+    }
+}
+
+#[cfg(feature = "ktest")]
+pub mod tests {
+    use super::*;
+    use crate::ktest::TestCase;
+
+    /// Kernel command line unit tests.
+    pub const TESTS: &[TestCase] = &[
+        TestCase::new("flag_param", flag_param),
+        TestCase::new("u32_param", u32_param),
+        TestCase::new("choice_param", choice_param),
+        TestCase::new("unknown_param", unknown_param),
+        TestCase::new("declared_params", declared_params),
+    ];
+
+    fn flag_param() {
+        static FLAG: AtomicBool = AtomicBool::new(false);
+        let param = Param {
+            name: "flag",
+            description: "",
+            kind: ParamKind::Flag(&FLAG),
+        };
+
+        assert_eq!(param.set(Some("1")), Err(ParamError::UnexpectedValue));
+        assert!(!FLAG.load(Ordering::Relaxed));
+        assert_eq!(param.set(None), Ok(()));
+        assert!(FLAG.load(Ordering::Relaxed));
+    }
+
+    fn u32_param() {
+        static VALUE: AtomicU32 = AtomicU32::new(0);
+        let param = Param {
+            name: "value",
+            description: "",
+            kind: ParamKind::U32 {
+                value: &VALUE,
+                min: 1,
+                max: 10,
+            },
+        };
+
+        assert_eq!(param.set(None), Err(ParamError::MissingValue));
+        assert_eq!(param.set(Some("x")), Err(ParamError::InvalidValue));
+        assert_eq!(param.set(Some("11")), Err(ParamError::OutOfRange));
+        assert_eq!(param.set(Some("7")), Ok(()));
+        assert_eq!(VALUE.load(Ordering::Relaxed), 7);
+    }
+
+    fn choice_param() {
+        static VALUE: Choice<u8> =
+            Choice::new(2, &[("a", 10), ("b", 20), ("c", 30)]);
+        let param = Param {
+            name: "choice",
+            description: "",
+            kind: ParamKind::Choice(&VALUE),
+        };
+
+        assert_eq!(VALUE.get(), 2);
+        assert_eq!(param.set(None), Err(ParamError::MissingValue));
+        assert_eq!(param.set(Some("d")), Err(ParamError::InvalidValue));
+        assert_eq!(VALUE.get(), 2);
+        assert_eq!(param.set(Some("c")), Ok(()));
+        assert_eq!(VALUE.get(), 30);
+    }
+
+    fn unknown_param() {
+        assert_eq!(set_arg("nosuchparam=1"), Err(ParamError::Unknown));
+        assert_eq!(set_arg("loglevel"), Err(ParamError::MissingValue));
+        assert!(find("console").is_some());
+    }
+
+    fn declared_params() {
+        let names = [
+            "loglevel",
+            "console",
+            "logtime",
+            "noterm",
+            "keymap",
+            "slab_debug",
+            "syscall_trace",
+        ];
+
+        for name in names {
+            assert!(find(name).is_some(), "{name} is not declared");
+        }
+
+        // Every parameter is declared once.
+        for (i, param) in params().iter().enumerate() {
+            let count = params()[i..].iter().filter(|p| p.name == param.name);
+            assert_eq!(count.count(), 1);
+        }
+    }
+}
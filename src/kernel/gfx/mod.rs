@@ -7,7 +7,12 @@
 mod font;
 pub mod terminal;
 
-use crate::{drivers::vbe::Framebuffer, log, multiboot::MultibootInfo};
+use crate::{
+    bootinfo::BootInfo,
+    drivers::vbe::Framebuffer,
+    kernel::mm::mmio::{self, CacheMode},
+    log,
+};
 use core::ptr;
 
 /// RGB color type.
@@ -40,20 +45,25 @@ pub enum Color {
     Orange = rgb!(0xFF, 0x70, 0x00),
 }
 
-/// Extract framebuffer info from boot info.
+/// Extract framebuffer info from boot info & map framebuffer memory.
 ///
 /// # Parameters
-/// - `boot_info` - given multiboot info structure.
+/// - `boot_info` - given bootloader independent boot information.
 ///
 /// # Returns
 /// - Framebuffer info struct.
-fn get_framebuffer(boot_info: &MultibootInfo) -> Framebuffer {
+fn get_framebuffer(boot_info: &BootInfo) -> Framebuffer {
+    let info = boot_info.framebuffer.expect("No framebuffer info");
+    let size = info.size() as usize;
+    let mem = mmio::ioremap(info.addr, size, CacheMode::WriteCombining)
+        .expect("Failed to map framebuffer");
+
     Framebuffer {
-        addr: boot_info.framebuffer_addr as u32,
-        pitch: boot_info.framebuffer_pitch,
-        width: boot_info.framebuffer_width,
-        height: boot_info.framebuffer_height,
-        bpp: boot_info.framebuffer_bpp,
+        mem,
+        pitch: info.pitch,
+        width: info.width,
+        height: info.height,
+        bpp: info.bpp,
     }
 }
 
@@ -156,15 +166,16 @@ pub fn draw_char(
 /// Initialize kernel graphics.
 ///
 /// # Parameters
-/// - `boot_info` - given multiboot info structure.
+/// - `boot_info` - given bootloader independent boot information.
 ///
 /// # Returns
 /// - Framebuffer info struct.
-pub fn init(boot_info: &MultibootInfo) -> Framebuffer {
+pub fn init(boot_info: &BootInfo) -> Framebuffer {
     let fb = get_framebuffer(boot_info);
 
     log::debug!("Bootloader provided framebuffer:");
-    log::debug!("Address: <{:#010x}>", fb.addr);
+    log::debug!("Physical address: <{:#010x}>", fb.mem.paddr());
+    log::debug!("Mapped address: <{:#010x}>", fb.mem.vaddr());
     log::debug!("Pitch: {}", fb.pitch);
     log::debug!("Resolution: {}x{}", fb.width, fb.height);
     log::debug!("Bytes per pixel: {}", fb.bpp);
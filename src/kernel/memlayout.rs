@@ -4,6 +4,8 @@
 
 //! Declares kernel memory layout.
 
+use core::ops::Range;
+
 // Kernel memory layout variables (declared in linker file).
 unsafe extern "C" {
     static kernel_phys_begin: u32;
@@ -11,8 +13,17 @@ unsafe extern "C" {
     static kernel_virt_begin: u32;
     static kernel_virt_end: u32;
     static base_address: u32;
+    static stack_guard: u32;
     static stack_bottom: u32;
     static stack_top: u32;
+    static kernel_text_begin: u32;
+    static kernel_text_end: u32;
+    static kernel_rodata_begin: u32;
+    static kernel_rodata_end: u32;
+    static kernel_data_begin: u32;
+    static kernel_data_end: u32;
+    static kernel_bss_begin: u32;
+    static kernel_bss_end: u32;
 }
 
 /// Physical address of memory begin.
@@ -72,6 +83,15 @@ pub fn kernel_size() -> u32 {
     kernel_end_paddr() - kernel_begin_paddr()
 }
 
+/// Get stack guard page.
+///
+/// # Returns
+/// - Virtual address of the stack guard page (just below stack bottom).
+#[inline(always)]
+pub fn stack_guard_vaddr() -> usize {
+    unsafe { &stack_guard as *const _ as usize }
+}
+
 /// Get stack bottom.
 ///
 /// # Returns
@@ -98,3 +118,51 @@ pub fn stack_top_vaddr() -> usize {
 pub fn stack_size() -> usize {
     stack_top_vaddr() - stack_bottom_vaddr()
 }
+
+/// Get kernel code section.
+///
+/// # Returns
+/// - Virtual addresses range of `.text` section.
+#[inline(always)]
+pub fn text_section() -> Range<usize> {
+    unsafe {
+        (&kernel_text_begin as *const _ as usize)
+            ..(&kernel_text_end as *const _ as usize)
+    }
+}
+
+/// Get kernel read-only data section.
+///
+/// # Returns
+/// - Virtual addresses range of `.rodata` section.
+#[inline(always)]
+pub fn rodata_section() -> Range<usize> {
+    unsafe {
+        (&kernel_rodata_begin as *const _ as usize)
+            ..(&kernel_rodata_end as *const _ as usize)
+    }
+}
+
+/// Get kernel initialized data section.
+///
+/// # Returns
+/// - Virtual addresses range of `.data` section.
+#[inline(always)]
+pub fn data_section() -> Range<usize> {
+    unsafe {
+        (&kernel_data_begin as *const _ as usize)
+            ..(&kernel_data_end as *const _ as usize)
+    }
+}
+
+/// Get kernel uninitialized data section (including boot stack).
+///
+/// # Returns
+/// - Virtual addresses range of `.bss` section.
+#[inline(always)]
+pub fn bss_section() -> Range<usize> {
+    unsafe {
+        (&kernel_bss_begin as *const _ as usize)
+            ..(&kernel_bss_end as *const _ as usize)
+    }
+}
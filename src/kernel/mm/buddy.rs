@@ -0,0 +1,524 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Date: 2025-06-13
+// Author: Alexander Kuzin <alkuzindev@gmail.com>.
+
+//! Buddy allocator of contiguous physical blocks.
+//!
+//! # Description
+//! Memory of each zone is split into blocks of `2^order` frames, where
+//! order is in range `0..=MAX_ORDER`. Free blocks of the same order are
+//! linked into doubly linked lists through per frame links stored in zone
+//! metadata, so that zone memory itself is never mapped. When block is
+//! freed, it is merged with its buddy while the buddy is free as well.
+//!
+//! Each zone is an explicit contiguous range reserved from the frame
+//! allocator during initialization: up to `DMA_ZONE_SIZE` below `DMA_LIMIT`
+//! for ISA DMA constrained devices and up to `NORMAL_ZONE_SIZE` above it
+//! (below `DIRECT_MAP_LIMIT`, so that blocks can be accessed through
+//! `with_direct`).
+//! Reserved frames are owned by the buddy allocator only and are never
+//! returned to the frame allocator, all other frames stay with it.
+
+use super::{DIRECT_MAP_LIMIT, PAGE_SIZE, PhysAddr, frame, phys_to_virt};
+use crate::log;
+use core::slice;
+use spin::Mutex;
+
+/// Maximal block order.
+pub const MAX_ORDER: usize = 10;
+
+/// Number of block orders.
+const ORDERS: usize = MAX_ORDER + 1;
+
+/// End of physical memory accessible by ISA DMA controller.
+pub const DMA_LIMIT: PhysAddr = 0x1000000;
+
+/// Maximal size of the DMA zone.
+const DMA_ZONE_SIZE: u64 = 0x100000;
+
+/// Maximal size of the normal zone.
+const NORMAL_ZONE_SIZE: u64 = 0x1000000;
+
+/// Block state flag of the free block head frame.
+const FREE: u8 = 0x80;
+
+/// Empty free list link.
+const NIL: u32 = u32::MAX;
+
+/// Memory zone types enumeration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ZoneType {
+    /// Memory below `DMA_LIMIT`.
+    Dma,
+    /// Memory without addressing restrictions.
+    Normal,
+}
+
+/// Free list links of the block first frame.
+#[repr(C)]
+struct FreeBlock {
+    /// Index of the next free block of the same order.
+    next: u32,
+    /// Index of the previous free block of the same order.
+    prev: u32,
+}
+
+/// Buddy allocator zone.
+pub struct Zone {
+    /// Zone type.
+    ztype: ZoneType,
+    /// Physical address of the first zone frame.
+    base: PhysAddr,
+    /// Number of frames in zone.
+    frame_count: usize,
+    /// Per frame free list links.
+    links: *mut FreeBlock,
+    /// Per frame block state (`FREE | order` for free block heads).
+    state: *mut u8,
+    /// Heads of free lists per order.
+    heads: [u32; ORDERS],
+    /// Number of free blocks per order.
+    counts: [usize; ORDERS],
+}
+
+// Zone metadata is only accessed under zone lock.
+unsafe impl Send for Zone {}
+
+impl Zone {
+    /// Construct new empty zone.
+    ///
+    /// # Parameters
+    /// - `ztype` - given zone type.
+    ///
+    /// # Returns
+    /// - New `Zone` object without memory.
+    pub const fn empty(ztype: ZoneType) -> Self {
+        Self {
+            ztype,
+            base: 0,
+            frame_count: 0,
+            links: core::ptr::null_mut(),
+            state: core::ptr::null_mut(),
+            heads: [NIL; ORDERS],
+            counts: [0; ORDERS],
+        }
+    }
+
+    /// Construct zone covering physical range. All zone memory is
+    /// considered used until it is freed. Zone metadata is allocated from
+    /// directly mapped frames & is never freed.
+    ///
+    /// # Parameters
+    /// - `ztype`       - given zone type.
+    /// - `base`        - given physical address of the first frame.
+    /// - `frame_count` - given number of frames.
+    ///
+    /// # Returns
+    /// - New `Zone` object - in case of success.
+    /// - `None`            - if there is no memory for the metadata.
+    fn new(
+        ztype: ZoneType,
+        base: PhysAddr,
+        frame_count: usize,
+    ) -> Option<Self> {
+        let links_size = frame_count * size_of::<FreeBlock>();
+        let size = links_size + frame_count;
+        let paddr = frame::alloc_direct(size.div_ceil(PAGE_SIZE as usize), 1)?;
+        let links = phys_to_virt(paddr);
+
+        let mut zone = Self {
+            links: links as *mut FreeBlock,
+            state: (links + links_size) as *mut u8,
+            base,
+            frame_count,
+            ..Self::empty(ztype)
+        };
+
+        zone.state().fill(0);
+        Some(zone)
+    }
+
+    /// Get per frame block state map.
+    ///
+    /// # Returns
+    /// - Block state map as mutable slice.
+    #[inline(always)]
+    fn state(&mut self) -> &mut [u8] {
+        unsafe { slice::from_raw_parts_mut(self.state, self.frame_count) }
+    }
+
+    /// Get free block links.
+    ///
+    /// # Parameters
+    /// - `index` - given index of the block first frame.
+    ///
+    /// # Returns
+    /// - Free list links of the block.
+    #[inline(always)]
+    fn block(&self, index: u32) -> &'static mut FreeBlock {
+        debug_assert!((index as usize) < self.frame_count);
+        unsafe { &mut *self.links.add(index as usize) }
+    }
+
+    /// Check whether zone contains physical address.
+    ///
+    /// # Parameters
+    /// - `paddr` - given physical address.
+    ///
+    /// # Returns
+    /// - `true`  - if address belongs to the zone.
+    /// - `false` - otherwise.
+    #[inline(always)]
+    pub fn contains(&self, paddr: PhysAddr) -> bool {
+        let end = self.base + self.frame_count as PhysAddr * PAGE_SIZE;
+        self.base <= paddr && paddr < end
+    }
+
+    /// Insert block into free list.
+    ///
+    /// # Parameters
+    /// - `index` - given index of the block first frame.
+    /// - `order` - given block order.
+    fn push(&mut self, index: u32, order: usize) {
+        let head = self.heads[order];
+        let block = self.block(index);
+        block.next = head;
+        block.prev = NIL;
+
+        if head != NIL {
+            self.block(head).prev = index;
+        }
+
+        self.heads[order] = index;
+        self.counts[order] += 1;
+        self.state()[index as usize] = FREE | order as u8;
+    }
+
+    /// Remove block from free list.
+    ///
+    /// # Parameters
+    /// - `index` - given index of the block first frame.
+    /// - `order` - given block order.
+    fn remove(&mut self, index: u32, order: usize) {
+        let (next, prev) = {
+            let block = self.block(index);
+            (block.next, block.prev)
+        };
+
+        if prev == NIL {
+            self.heads[order] = next;
+        } else {
+            self.block(prev).next = next;
+        }
+
+        if next != NIL {
+            self.block(next).prev = prev;
+        }
+
+        self.counts[order] -= 1;
+        self.state()[index as usize] = 0;
+    }
+
+    /// Check whether any frame of block is free.
+    ///
+    /// # Parameters
+    /// - `index` - given index of the block first frame.
+    /// - `order` - given block order.
+    ///
+    /// # Returns
+    /// - `true`  - if block overlaps any free block.
+    /// - `false` - otherwise.
+    fn overlaps_free(&mut self, index: u32, order: usize) -> bool {
+        let first = index as usize;
+
+        // Free block inside the given one has its head inside.
+        if self.state()[first..first + (1 << order)]
+            .iter()
+            .any(|&state| (state & FREE) != 0)
+        {
+            return true;
+        }
+
+        // Free block containing the given one has its head at aligned index.
+        (order + 1..ORDERS).any(|outer| {
+            let head = first & !((1 << outer) - 1);
+            self.state()[head] == FREE | outer as u8
+        })
+    }
+
+    /// Allocate block of contiguous frames.
+    ///
+    /// # Parameters
+    /// - `order` - given block order.
+    ///
+    /// # Returns
+    /// - Physical address of the block - in case of success.
+    /// - `None`                        - otherwise.
+    pub fn alloc(&mut self, order: usize) -> Option<PhysAddr> {
+        if order > MAX_ORDER {
+            return None;
+        }
+
+        let mut current = (order..ORDERS).find(|&o| self.heads[o] != NIL)?;
+        let index = self.heads[current];
+        self.remove(index, current);
+
+        // Return upper halves of the split block back to free lists.
+        while current > order {
+            current -= 1;
+            self.push(index + (1 << current), current);
+        }
+
+        Some(self.base + index as PhysAddr * PAGE_SIZE)
+    }
+
+    /// Free block of contiguous frames merging it with free buddies.
+    ///
+    /// # Parameters
+    /// - `paddr` - given physical address of the block.
+    /// - `order` - given block order.
+    pub fn free(&mut self, paddr: PhysAddr, order: usize) {
+        let mut index = ((paddr - self.base) / PAGE_SIZE) as u32;
+
+        if order > MAX_ORDER
+            || (index & ((1 << order) - 1)) != 0
+            || index as usize + (1 << order) > self.frame_count
+            || self.overlaps_free(index, order)
+        {
+            log::fail!("Invalid free of block <{:#X}> ({})", paddr, order);
+            return;
+        }
+
+        let mut order = order;
+
+        while order < MAX_ORDER {
+            let buddy = index ^ (1 << order);
+
+            if buddy as usize + (1 << order) > self.frame_count
+                || self.state()[buddy as usize] != FREE | order as u8
+            {
+                break;
+            }
+
+            self.remove(buddy, order);
+            index &= buddy;
+            order += 1;
+        }
+
+        self.push(index, order);
+    }
+
+    /// Get number of free frames.
+    ///
+    /// # Returns
+    /// - Number of free frames in zone.
+    pub fn free_frames(&self) -> usize {
+        self.counts
+            .iter()
+            .enumerate()
+            .map(|(order, count)| count << order)
+            .sum()
+    }
+
+    /// Print zone free lists for debug.
+    pub fn dump(&self) {
+        log::debug!(
+            "{:?} zone [{:#010X}-{:#010X}], {} free frames",
+            self.ztype,
+            self.base,
+            self.base + self.frame_count as PhysAddr * PAGE_SIZE,
+            self.free_frames()
+        );
+
+        for (order, count) in self.counts.iter().enumerate() {
+            log::debug!("  order {:>2}: {} free blocks", order, count);
+        }
+    }
+}
+
+/// Zone of memory below `DMA_LIMIT`.
+static DMA_ZONE: Mutex<Zone> = Mutex::new(Zone::empty(ZoneType::Dma));
+
+/// Zone of memory without addressing restrictions.
+static NORMAL_ZONE: Mutex<Zone> = Mutex::new(Zone::empty(ZoneType::Normal));
+
+/// Allocate block of `2^order` contiguous physical frames. Block is not
+/// mapped, callers map it where needed.
+///
+/// # Parameters
+/// - `order` - given block order.
+/// - `ztype` - given zone to allocate from. Normal zone falls back to DMA.
+///
+/// # Returns
+/// - Physical address of the block - in case of success.
+/// - `None`                        - otherwise.
+pub fn alloc_pages(order: usize, ztype: ZoneType) -> Option<PhysAddr> {
+    match ztype {
+        ZoneType::Dma => DMA_ZONE.lock().alloc(order),
+        ZoneType::Normal => NORMAL_ZONE
+            .lock()
+            .alloc(order)
+            .or_else(|| DMA_ZONE.lock().alloc(order)),
+    }
+}
+
+/// Free block of `2^order` contiguous physical frames.
+///
+/// # Parameters
+/// - `paddr` - given physical address of the block.
+/// - `order` - given block order.
+pub fn free_pages(paddr: PhysAddr, order: usize) {
+    let mut dma = DMA_ZONE.lock();
+
+    if dma.contains(paddr) {
+        dma.free(paddr, order);
+        return;
+    }
+
+    drop(dma);
+    let mut normal = NORMAL_ZONE.lock();
+
+    if normal.contains(paddr) {
+        normal.free(paddr, order);
+    } else {
+        log::fail!("Block <{:#X}> does not belong to any zone", paddr);
+    }
+}
+
+/// Print free lists of all zones for debug.
+pub fn dump() {
+    DMA_ZONE.lock().dump();
+    NORMAL_ZONE.lock().dump();
+}
+
+/// Reserve zone range from frame allocator.
+///
+/// # Parameters
+/// - `ztype` - given zone type.
+/// - `size`  - given maximal zone size (power of two number of frames).
+/// - `start` - given physical address the zone must start at or above.
+/// - `limit` - given physical address the zone must end below.
+///
+/// # Returns
+/// - New `Zone` object with all memory free - in case of success.
+/// - `None`                                  - otherwise.
+fn reserve_zone(
+    ztype: ZoneType,
+    size: u64,
+    start: PhysAddr,
+    limit: PhysAddr,
+) -> Option<Zone> {
+    let mut frame_count = (size / PAGE_SIZE) as usize;
+
+    // Shrink zone until contiguous aligned range is found.
+    while frame_count > 0 {
+        let block = frame_count.min(1 << MAX_ORDER);
+
+        if let Some(base) =
+            frame::alloc_frames_within(frame_count, block, start, limit)
+        {
+            let Some(mut zone) = Zone::new(ztype, base, frame_count) else {
+                frame::free_frames(base, frame_count);
+                return None;
+            };
+
+            let order = block.trailing_zeros() as usize;
+
+            for index in (0..frame_count).step_by(block) {
+                zone.free(base + (index as u64) * PAGE_SIZE, order);
+            }
+
+            return Some(zone);
+        }
+
+        frame_count /= 2;
+    }
+
+    None
+}
+
+/// Initialize buddy allocator zones.
+pub fn init() {
+    match reserve_zone(ZoneType::Dma, DMA_ZONE_SIZE, 0, DMA_LIMIT) {
+        Some(zone) => *DMA_ZONE.lock() = zone,
+        None => log::fail!("No memory for DMA zone"),
+    }
+
+    match reserve_zone(
+        ZoneType::Normal,
+        NORMAL_ZONE_SIZE,
+        DMA_LIMIT,
+        DIRECT_MAP_LIMIT,
+    ) {
+        Some(zone) => *NORMAL_ZONE.lock() = zone,
+        None => log::fail!("No memory for normal zone"),
+    }
+
+    dump();
+}
+
+#[cfg(feature = "ktest")]
+pub mod tests {
+    use super::*;
+    use crate::ktest::TestCase;
+
+    /// Buddy allocator unit tests.
+    pub const TESTS: &[TestCase] = &[
+        TestCase::new("alloc_is_aligned", alloc_is_aligned),
+        TestCase::new("buddies_merge", buddies_merge),
+        TestCase::new("double_free", double_free),
+        TestCase::new("dma_zone_limit", dma_zone_limit),
+    ];
+
+    fn alloc_is_aligned() {
+        for order in 0..=MAX_ORDER {
+            let paddr = alloc_pages(order, ZoneType::Normal).unwrap();
+            assert_eq!(paddr & ((PAGE_SIZE << order) - 1), 0);
+            free_pages(paddr, order);
+        }
+
+        assert!(alloc_pages(MAX_ORDER + 1, ZoneType::Normal).is_none());
+    }
+
+    fn buddies_merge() {
+        let before = NORMAL_ZONE.lock().free_frames();
+        let pair = alloc_pages(1, ZoneType::Normal).unwrap();
+        let (a, b) = (pair, pair + PAGE_SIZE);
+        let state = |paddr: PhysAddr| {
+            let mut zone = NORMAL_ZONE.lock();
+            let index = ((paddr - zone.base) / PAGE_SIZE) as usize;
+            zone.state()[index]
+        };
+
+        // Halves of allocated block are freed separately.
+        free_pages(a, 0);
+        assert_eq!(state(a), FREE);
+        assert_eq!(NORMAL_ZONE.lock().free_frames(), before - 1);
+
+        // Second half merges with the first one into a bigger block.
+        free_pages(b, 0);
+        assert_ne!(state(a), FREE);
+        assert_eq!(state(b), 0);
+        assert_eq!(NORMAL_ZONE.lock().free_frames(), before);
+    }
+
+    fn double_free() {
+        let before = NORMAL_ZONE.lock().free_frames();
+        let paddr = alloc_pages(2, ZoneType::Normal).unwrap();
+        free_pages(paddr, 2);
+
+        // Neither the block head nor inner frames can be freed again.
+        free_pages(paddr, 2);
+        free_pages(paddr + PAGE_SIZE, 0);
+        free_pages(paddr + 2 * PAGE_SIZE, 1);
+
+        assert_eq!(NORMAL_ZONE.lock().free_frames(), before);
+    }
+
+    fn dma_zone_limit() {
+        let paddr = alloc_pages(4, ZoneType::Dma).unwrap();
+
+        assert!(paddr + (PAGE_SIZE << 4) <= DMA_LIMIT);
+        free_pages(paddr, 4);
+    }
+}
@@ -0,0 +1,567 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Date: 2025-06-13
+// Author: Alexander Kuzin <alkuzindev@gmail.com>.
+
+//! Physical page frame allocator.
+//!
+//! # Description
+//! Bitmap based allocator of 4 KiB physical frames. Each bit of the bitmap
+//! tracks single frame (1 - used, 0 - free). The bitmap itself is placed in
+//! the first usable region that is big enough and is directly accessible
+//! by the kernel. Memory above the limit addressable by paging structures
+//! is not tracked.
+//!
+//! Frames are not mapped by default. Frames accessed by the kernel through
+//! `phys_to_virt` are allocated by `alloc_direct`, which maps them at their
+//! direct virtual addresses until they are freed by `free_direct`.
+
+use super::{
+    DIRECT_MAP_LIMIT, PAGE_SIZE, PhysAddr, align_up, memmap::PhysMemoryMap,
+    phys_to_virt, virt_to_phys,
+};
+use crate::{hal::mmu, log};
+use core::{ops::Range, slice};
+use spin::Mutex;
+
+/// Number of frames tracked by single bitmap word.
+const BITS_PER_WORD: usize = u32::BITS as usize;
+
+/// Frame allocator usage statistics.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FrameStats {
+    /// Total number of usable frames.
+    pub total: usize,
+    /// Number of free frames.
+    pub free: usize,
+}
+
+impl FrameStats {
+    /// Get number of used frames.
+    ///
+    /// # Returns
+    /// - Number of used frames.
+    #[inline(always)]
+    pub fn used(&self) -> usize {
+        self.total - self.free
+    }
+}
+
+/// Bitmap physical frame allocator.
+pub struct FrameAllocator {
+    /// Frames bitmap.
+    bitmap: *mut u32,
+    /// Number of frames tracked by bitmap.
+    frame_count: usize,
+    /// Usage statistics.
+    stats: FrameStats,
+    /// Index of the frame to start search from.
+    next: usize,
+}
+
+// Bitmap is only accessed under global frame allocator lock.
+unsafe impl Send for FrameAllocator {}
+
+impl FrameAllocator {
+    /// Construct new empty frame allocator.
+    ///
+    /// # Returns
+    /// - New `FrameAllocator` object without frames.
+    pub const fn empty() -> Self {
+        Self {
+            bitmap: core::ptr::null_mut(),
+            frame_count: 0,
+            stats: FrameStats { total: 0, free: 0 },
+            next: 0,
+        }
+    }
+
+    /// Construct frame allocator from physical memory map.
+    ///
+    /// # Parameters
+    /// - `map` - given sanitized physical memory map.
+    ///
+    /// # Returns
+    /// - New `FrameAllocator` object - in case of success.
+    /// - `None`                      - if there is no place for the bitmap.
+    pub fn new(map: &PhysMemoryMap) -> Option<Self> {
+        let limit = mmu::phys_addr_limit();
+        let frame_count = (map.usable_end().min(limit) / PAGE_SIZE) as usize;
+        let words = frame_count.div_ceil(BITS_PER_WORD);
+        let bitmap_size =
+            align_up((words * size_of::<u32>()) as u64, PAGE_SIZE);
+
+        // Find place for the bitmap in directly accessible memory.
+        let bitmap_paddr = map.usable().find_map(|region| {
+            let end = region.start + bitmap_size;
+            (end <= region.end && end <= DIRECT_MAP_LIMIT)
+                .then_some(region.start)
+        })?;
+
+        let mut allocator = Self {
+            bitmap: phys_to_virt(bitmap_paddr) as *mut u32,
+            frame_count,
+            stats: FrameStats::default(),
+            next: 0,
+        };
+
+        // Mark all frames as used, then free usable ones.
+        allocator.bitmap().fill(u32::MAX);
+
+        for region in map.usable().filter(|region| region.start < limit) {
+            let first = (region.start / PAGE_SIZE) as usize;
+            let last = (region.end.min(limit) / PAGE_SIZE) as usize;
+
+            for frame in first..last {
+                allocator.clear(frame);
+            }
+
+            allocator.stats.total += last - first;
+        }
+
+        allocator.stats.free = allocator.stats.total;
+
+        // Bitmap frames are never freed.
+        let first = (bitmap_paddr / PAGE_SIZE) as usize;
+        let count = (bitmap_size / PAGE_SIZE) as usize;
+
+        for frame in first..first + count {
+            allocator.set(frame);
+        }
+
+        allocator.stats.free -= count;
+        Some(allocator)
+    }
+
+    /// Get frames bitmap.
+    ///
+    /// # Returns
+    /// - Frames bitmap as mutable slice.
+    #[inline(always)]
+    fn bitmap(&mut self) -> &mut [u32] {
+        let words = self.frame_count.div_ceil(BITS_PER_WORD);
+        unsafe { slice::from_raw_parts_mut(self.bitmap, words) }
+    }
+
+    /// Check whether frame is used.
+    ///
+    /// # Parameters
+    /// - `frame` - given frame index.
+    ///
+    /// # Returns
+    /// - `true`  - if frame is used.
+    /// - `false` - otherwise.
+    #[inline(always)]
+    fn is_used(&mut self, frame: usize) -> bool {
+        let word = self.bitmap()[frame / BITS_PER_WORD];
+        (word & (1 << (frame % BITS_PER_WORD))) != 0
+    }
+
+    /// Mark frame as used.
+    ///
+    /// # Parameters
+    /// - `frame` - given frame index.
+    #[inline(always)]
+    fn set(&mut self, frame: usize) {
+        let word = frame / BITS_PER_WORD;
+        self.bitmap()[word] |= 1 << (frame % BITS_PER_WORD);
+    }
+
+    /// Mark frame as free.
+    ///
+    /// # Parameters
+    /// - `frame` - given frame index.
+    #[inline(always)]
+    fn clear(&mut self, frame: usize) {
+        let word = frame / BITS_PER_WORD;
+        self.bitmap()[word] &= !(1 << (frame % BITS_PER_WORD));
+    }
+
+    /// Find range of free frames.
+    ///
+    /// # Parameters
+    /// - `count` - given number of frames.
+    /// - `align` - given alignment of the first frame (in frames).
+    /// - `from`  - given index of the frame to start search from.
+    /// - `to`    - given index of the frame to end search at (exclusive).
+    ///
+    /// # Returns
+    /// - Index of the first frame of the range - in case of success.
+    /// - `None`                                - otherwise.
+    fn find_free(
+        &mut self,
+        count: usize,
+        align: usize,
+        from: usize,
+        to: usize,
+    ) -> Option<usize> {
+        let mut first = from.next_multiple_of(align);
+
+        while first + count <= to {
+            // Skip fully used words quickly.
+            if (first & (BITS_PER_WORD - 1)) == 0
+                && self.bitmap()[first / BITS_PER_WORD] == u32::MAX
+            {
+                first = (first + BITS_PER_WORD).next_multiple_of(align);
+                continue;
+            }
+
+            match (first..first + count).find(|&frame| self.is_used(frame)) {
+                Some(used) => first = (used + 1).next_multiple_of(align),
+                None => return Some(first),
+            }
+        }
+
+        None
+    }
+
+    /// Allocate range of contiguous physical frames.
+    ///
+    /// # Parameters
+    /// - `count` - given number of frames.
+    /// - `align` - given alignment of the first frame (in frames).
+    ///
+    /// # Returns
+    /// - Physical address of the first frame - in case of success.
+    /// - `None`                              - otherwise.
+    pub fn alloc_range(
+        &mut self,
+        count: usize,
+        align: usize,
+    ) -> Option<PhysAddr> {
+        if count == 0 || count > self.stats.free {
+            return None;
+        }
+
+        let align = align.max(1);
+        let (next, end) = (self.next, self.frame_count);
+
+        // Search from the last allocation first, then wrap around.
+        let first = match self.find_free(count, align, next, end) {
+            Some(first) => first,
+            None => self.find_free(count, align, 0, end)?,
+        };
+
+        for frame in first..first + count {
+            self.set(frame);
+        }
+
+        self.stats.free -= count;
+        self.next = first + count;
+
+        Some(first as PhysAddr * PAGE_SIZE)
+    }
+
+    /// Allocate range of contiguous physical frames below specific address.
+    ///
+    /// # Parameters
+    /// - `count` - given number of frames.
+    /// - `align` - given alignment of the first frame (in frames).
+    /// - `limit` - given physical address the range must end below.
+    ///
+    /// # Returns
+    /// - Physical address of the first frame - in case of success.
+    /// - `None`                              - otherwise.
+    #[inline(always)]
+    pub fn alloc_range_below(
+        &mut self,
+        count: usize,
+        align: usize,
+        limit: PhysAddr,
+    ) -> Option<PhysAddr> {
+        self.alloc_range_within(count, align, 0, limit)
+    }
+
+    /// Allocate range of contiguous physical frames inside physical range.
+    ///
+    /// # Parameters
+    /// - `count` - given number of frames.
+    /// - `align` - given alignment of the first frame (in frames).
+    /// - `start` - given physical address the range must start at or above.
+    /// - `limit` - given physical address the range must end below.
+    ///
+    /// # Returns
+    /// - Physical address of the first frame - in case of success.
+    /// - `None`                              - otherwise.
+    pub fn alloc_range_within(
+        &mut self,
+        count: usize,
+        align: usize,
+        start: PhysAddr,
+        limit: PhysAddr,
+    ) -> Option<PhysAddr> {
+        let from = start.div_ceil(PAGE_SIZE) as usize;
+        let to = ((limit / PAGE_SIZE) as usize).min(self.frame_count);
+
+        if count == 0 || count > self.stats.free {
+            return None;
+        }
+
+        let first = self.find_free(count, align.max(1), from, to)?;
+
+        for frame in first..first + count {
+            self.set(frame);
+        }
+
+        self.stats.free -= count;
+        Some(first as PhysAddr * PAGE_SIZE)
+    }
+
+    /// Free range of contiguous physical frames.
+    ///
+    /// # Parameters
+    /// - `paddr` - given physical address of the first frame.
+    /// - `count` - given number of frames.
+    pub fn free_range(&mut self, paddr: PhysAddr, count: usize) {
+        let first = (paddr / PAGE_SIZE) as usize;
+
+        for frame in first..first + count {
+            if frame >= self.frame_count || !self.is_used(frame) {
+                log::fail!(
+                    "Double free of frame <{:#X}>",
+                    frame as u64 * PAGE_SIZE
+                );
+                continue;
+            }
+
+            self.clear(frame);
+            self.stats.free += 1;
+        }
+
+        self.next = self.next.min(first);
+    }
+
+    /// Get frame allocator usage statistics.
+    ///
+    /// # Returns
+    /// - Frame allocator usage statistics.
+    #[inline(always)]
+    pub fn stats(&self) -> FrameStats {
+        self.stats
+    }
+
+    /// Get physical range occupied by the bitmap.
+    ///
+    /// # Returns
+    /// - Page aligned physical range of the bitmap.
+    pub fn bitmap_range(&self) -> Range<PhysAddr> {
+        let words = self.frame_count.div_ceil(BITS_PER_WORD);
+        let start = virt_to_phys(self.bitmap as usize);
+        let size = align_up((words * size_of::<u32>()) as u64, PAGE_SIZE);
+
+        start..start + size
+    }
+}
+
+/// Global physical frame allocator.
+static FRAME_ALLOCATOR: Mutex<FrameAllocator> =
+    Mutex::new(FrameAllocator::empty());
+
+/// Allocate single physical frame.
+///
+/// # Returns
+/// - Physical address of the frame - in case of success.
+/// - `None`                        - otherwise.
+pub fn alloc_frame() -> Option<PhysAddr> {
+    FRAME_ALLOCATOR.lock().alloc_range(1, 1)
+}
+
+/// Allocate range of contiguous physical frames.
+///
+/// # Parameters
+/// - `count` - given number of frames.
+/// - `align` - given alignment of the first frame (in frames).
+///
+/// # Returns
+/// - Physical address of the first frame - in case of success.
+/// - `None`                              - otherwise.
+pub fn alloc_frames(count: usize, align: usize) -> Option<PhysAddr> {
+    FRAME_ALLOCATOR.lock().alloc_range(count, align)
+}
+
+/// Allocate range of contiguous physical frames below specific address.
+///
+/// # Parameters
+/// - `count` - given number of frames.
+/// - `align` - given alignment of the first frame (in frames).
+/// - `limit` - given physical address the range must end below.
+///
+/// # Returns
+/// - Physical address of the first frame - in case of success.
+/// - `None`                              - otherwise.
+pub fn alloc_frames_below(
+    count: usize,
+    align: usize,
+    limit: PhysAddr,
+) -> Option<PhysAddr> {
+    FRAME_ALLOCATOR
+        .lock()
+        .alloc_range_below(count, align, limit)
+}
+
+/// Allocate range of contiguous physical frames inside physical range.
+///
+/// # Parameters
+/// - `count` - given number of frames.
+/// - `align` - given alignment of the first frame (in frames).
+/// - `start` - given physical address the range must start at or above.
+/// - `limit` - given physical address the range must end below.
+///
+/// # Returns
+/// - Physical address of the first frame - in case of success.
+/// - `None`                              - otherwise.
+pub fn alloc_frames_within(
+    count: usize,
+    align: usize,
+    start: PhysAddr,
+    limit: PhysAddr,
+) -> Option<PhysAddr> {
+    FRAME_ALLOCATOR
+        .lock()
+        .alloc_range_within(count, align, start, limit)
+}
+
+/// Allocate range of contiguous physical frames mapped at their direct
+/// virtual addresses.
+///
+/// # Parameters
+/// - `count` - given number of frames.
+/// - `align` - given alignment of the first frame (in frames).
+///
+/// # Returns
+/// - Physical address of the first frame - in case of success.
+/// - `None`                              - otherwise.
+pub fn alloc_direct(count: usize, align: usize) -> Option<PhysAddr> {
+    let paddr = alloc_frames_below(count, align, DIRECT_MAP_LIMIT)?;
+    let frame = |index: usize| paddr + index as PhysAddr * PAGE_SIZE;
+
+    let mapped = (0..count)
+        .take_while(|&index| mmu::map_direct(frame(index)).is_ok())
+        .count();
+
+    if mapped < count {
+        (0..mapped).for_each(|index| mmu::unmap_direct(frame(index)));
+        free_frames(paddr, count);
+        return None;
+    }
+
+    Some(paddr)
+}
+
+/// Unmap & free range of contiguous physical frames allocated by
+/// `alloc_direct`.
+///
+/// # Parameters
+/// - `paddr` - given physical address of the first frame.
+/// - `count` - given number of frames.
+pub fn free_direct(paddr: PhysAddr, count: usize) {
+    for index in 0..count {
+        mmu::unmap_direct(paddr + index as PhysAddr * PAGE_SIZE);
+    }
+
+    free_frames(paddr, count);
+}
+
+/// Free single physical frame.
+///
+/// # Parameters
+/// - `paddr` - given physical address of the frame.
+pub fn free_frame(paddr: PhysAddr) {
+    FRAME_ALLOCATOR.lock().free_range(paddr, 1);
+}
+
+/// Free range of contiguous physical frames.
+///
+/// # Parameters
+/// - `paddr` - given physical address of the first frame.
+/// - `count` - given number of frames.
+pub fn free_frames(paddr: PhysAddr, count: usize) {
+    FRAME_ALLOCATOR.lock().free_range(paddr, count);
+}
+
+/// Get frame allocator usage statistics.
+///
+/// # Returns
+/// - Frame allocator usage statistics.
+pub fn stats() -> FrameStats {
+    FRAME_ALLOCATOR.lock().stats()
+}
+
+/// Get physical range occupied by the frames bitmap.
+///
+/// # Returns
+/// - Page aligned physical range of the bitmap.
+pub fn bitmap_range() -> Range<PhysAddr> {
+    FRAME_ALLOCATOR.lock().bitmap_range()
+}
+
+/// Initialize physical frame allocator.
+///
+/// # Parameters
+/// - `map` - given sanitized physical memory map.
+pub fn init(map: &PhysMemoryMap) {
+    let allocator =
+        FrameAllocator::new(map).expect("No memory for frames bitmap");
+    *FRAME_ALLOCATOR.lock() = allocator;
+}
+
+#[cfg(feature = "ktest")]
+pub mod tests {
+    use super::*;
+    use crate::ktest::TestCase;
+
+    /// Physical frame allocator unit tests.
+    pub const TESTS: &[TestCase] = &[
+        TestCase::new("alloc_free_frame", alloc_free_frame),
+        TestCase::new("alloc_aligned_range", alloc_aligned_range),
+        TestCase::new("alloc_below_limit", alloc_below_limit),
+        TestCase::new("alloc_direct_mapped", alloc_direct_mapped),
+    ];
+
+    fn alloc_free_frame() {
+        let before = stats();
+        let a = alloc_frame().unwrap();
+        let b = alloc_frame().unwrap();
+
+        assert_ne!(a, b);
+        assert_eq!(a % PAGE_SIZE, 0);
+        assert_eq!(stats().free, before.free - 2);
+
+        free_frame(a);
+        free_frame(b);
+        assert_eq!(stats().free, before.free);
+    }
+
+    fn alloc_aligned_range() {
+        let before = stats();
+        let paddr = alloc_frames(16, 16).unwrap();
+
+        assert_eq!(paddr % (16 * PAGE_SIZE), 0);
+        assert_eq!(stats().free, before.free - 16);
+
+        free_frames(paddr, 16);
+        assert_eq!(stats().free, before.free);
+    }
+
+    fn alloc_below_limit() {
+        let limit = 16 * 1024 * 1024;
+        let paddr = alloc_frames_below(4, 1, limit).unwrap();
+
+        assert!(paddr + 4 * PAGE_SIZE <= limit);
+        free_frames(paddr, 4);
+    }
+
+    fn alloc_direct_mapped() {
+        let before = stats();
+        let paddr = alloc_direct(2, 1).unwrap();
+        let vaddr = phys_to_virt(paddr);
+
+        assert_eq!(
+            mmu::translate(vaddr + PAGE_SIZE as usize),
+            Some(paddr + PAGE_SIZE)
+        );
+
+        free_direct(paddr, 2);
+        assert_eq!(mmu::translate(vaddr), None);
+        assert_eq!(stats().free, before.free);
+    }
+}
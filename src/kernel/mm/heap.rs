@@ -0,0 +1,459 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Date: 2025-06-13
+// Author: Alexander Kuzin <alkuzindev@gmail.com>.
+
+//! Kernel heap.
+//!
+//! # Description
+//! Heap occupies dedicated window of kernel virtual address space starting
+//! at `HEAP_BASE`. Free memory is tracked by address ordered list of holes
+//! stored inside the free memory itself. Allocation takes the first hole
+//! that fits, freed blocks are merged with adjacent holes. When no hole
+//! fits, heap grows by mapping newly allocated physical frames at its end.
+//!
+//! Heap lock is only taken with interrupts disabled, so that IRQ handlers
+//! are able to allocate. Frames are allocated & mapped outside of the lock:
+//! heap window range is reserved under the lock first, then mapped memory
+//! is added to the heap.
+
+use super::{PAGE_SIZE, frame, kstack::KSTACK_BASE};
+use crate::{
+    hal::{
+        irq,
+        mmu::{self, MapFlags},
+    },
+    log,
+};
+use core::{
+    alloc::{GlobalAlloc, Layout},
+    ptr,
+};
+use spin::Mutex;
+
+/// Start of the kernel heap virtual window.
+pub const HEAP_BASE: usize = 0xF0000000;
+
+/// End of the kernel heap virtual window (exclusive).
+pub const HEAP_END: usize = KSTACK_BASE;
+
+/// Initial kernel heap size.
+const HEAP_INITIAL_SIZE: usize = 0x100000;
+
+/// Minimal number of bytes heap grows by.
+const HEAP_GROW_MIN: usize = 0x10000;
+
+/// Free memory hole header.
+struct Hole {
+    /// Hole size in bytes (including header).
+    size: usize,
+    /// Next hole with greater address.
+    next: *mut Hole,
+}
+
+/// Smallest block that can hold hole header.
+const MIN_BLOCK: usize = size_of::<Hole>();
+
+/// Kernel heap usage statistics.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct HeapStats {
+    /// Number of mapped heap bytes.
+    pub size: usize,
+    /// Number of allocated bytes.
+    pub used: usize,
+}
+
+/// First-fit heap over list of holes.
+pub struct Heap {
+    /// Dummy hole heading address ordered list of holes.
+    head: Hole,
+    /// End of mapped heap memory.
+    top: usize,
+    /// Usage statistics.
+    stats: HeapStats,
+}
+
+// Holes are only accessed under global heap lock.
+unsafe impl Send for Heap {}
+
+/// Round block size up so that freed block is able to hold hole header.
+///
+/// # Parameters
+/// - `size` - given requested size in bytes.
+///
+/// # Returns
+/// - Actual block size in bytes.
+#[inline(always)]
+const fn block_size(size: usize) -> usize {
+    let size = size.next_multiple_of(align_of::<Hole>());
+
+    if size < MIN_BLOCK { MIN_BLOCK } else { size }
+}
+
+impl Heap {
+    /// Construct new empty heap.
+    ///
+    /// # Returns
+    /// - New `Heap` object without memory.
+    pub const fn empty() -> Self {
+        Self {
+            head: Hole {
+                size: 0,
+                next: ptr::null_mut(),
+            },
+            top: HEAP_BASE,
+            stats: HeapStats { size: 0, used: 0 },
+        }
+    }
+
+    /// Allocate memory block.
+    ///
+    /// # Parameters
+    /// - `layout` - given memory block layout.
+    ///
+    /// # Returns
+    /// - Address of allocated block - in case of success.
+    /// - `None`                     - otherwise.
+    pub fn alloc(&mut self, layout: Layout) -> Option<usize> {
+        let size = block_size(layout.size());
+        let align = layout.align().max(align_of::<Hole>());
+        let addr = self.alloc_first_fit(size, align)?;
+
+        self.stats.used += size;
+        Some(addr)
+    }
+
+    /// Free memory block.
+    ///
+    /// # Parameters
+    /// - `addr`   - given address of allocated block.
+    /// - `layout` - given memory block layout.
+    pub fn free(&mut self, addr: usize, layout: Layout) {
+        let size = block_size(layout.size());
+
+        self.insert_hole(addr, size);
+        self.stats.used -= size;
+    }
+
+    /// Find first hole able to hold aligned block & carve block out of it.
+    ///
+    /// # Parameters
+    /// - `size`  - given block size in bytes.
+    /// - `align` - given block alignment.
+    ///
+    /// # Returns
+    /// - Address of allocated block - in case of success.
+    /// - `None`                     - otherwise.
+    fn alloc_first_fit(&mut self, size: usize, align: usize) -> Option<usize> {
+        let mut prev: *mut Hole = &raw mut self.head;
+
+        unsafe {
+            while !(*prev).next.is_null() {
+                let hole = (*prev).next;
+                let start = hole as usize;
+                let end = start + (*hole).size;
+
+                // Front padding must be able to hold hole header as well.
+                let mut addr = start.next_multiple_of(align);
+
+                if addr != start && addr - start < MIN_BLOCK {
+                    addr = (start + MIN_BLOCK).next_multiple_of(align);
+                }
+
+                let back = end.saturating_sub(addr + size);
+
+                if addr + size > end || (back != 0 && back < MIN_BLOCK) {
+                    prev = hole;
+                    continue;
+                }
+
+                let mut next = (*hole).next;
+
+                if back != 0 {
+                    let tail = (addr + size) as *mut Hole;
+                    tail.write(Hole { size: back, next });
+                    next = tail;
+                }
+
+                if addr == start {
+                    (*prev).next = next;
+                } else {
+                    (*hole).size = addr - start;
+                    (*hole).next = next;
+                }
+
+                return Some(addr);
+            }
+        }
+
+        None
+    }
+
+    /// Insert hole into address ordered list merging it with neighbours.
+    ///
+    /// # Parameters
+    /// - `addr` - given hole address.
+    /// - `size` - given hole size in bytes.
+    fn insert_hole(&mut self, addr: usize, size: usize) {
+        let head: *mut Hole = &raw mut self.head;
+        let mut prev = head;
+
+        unsafe {
+            while !(*prev).next.is_null() && ((*prev).next as usize) < addr {
+                prev = (*prev).next;
+            }
+
+            let next = (*prev).next;
+            let hole = addr as *mut Hole;
+            hole.write(Hole { size, next });
+
+            // Merge with the following hole.
+            if !next.is_null() && addr + size == next as usize {
+                (*hole).size += (*next).size;
+                (*hole).next = (*next).next;
+            }
+
+            // Merge with the preceding hole (dummy head is never merged).
+            if prev != head && prev as usize + (*prev).size == addr {
+                (*prev).size += (*hole).size;
+                (*prev).next = (*hole).next;
+            } else {
+                (*prev).next = hole;
+            }
+        }
+    }
+
+    /// Reserve heap window range at the end of the heap to grow by.
+    ///
+    /// # Parameters
+    /// - `size` - given minimal number of bytes to grow by.
+    ///
+    /// # Returns
+    /// - Start & size of reserved range - in case of success.
+    /// - `None`                         - if heap window is exhausted.
+    fn reserve(&mut self, size: usize) -> Option<(usize, usize)> {
+        let size = size.max(HEAP_GROW_MIN).next_multiple_of(PAGE_SIZE as usize);
+
+        if size > HEAP_END - self.top {
+            return None;
+        }
+
+        let start = self.top;
+        self.top += size;
+        Some((start, size))
+    }
+
+    /// Add mapped part of reserved range to the heap. Unmapped rest of the
+    /// range is returned if nothing was reserved after it.
+    ///
+    /// # Parameters
+    /// - `start`    - given start address of reserved range.
+    /// - `mapped`   - given number of mapped bytes at range start.
+    /// - `reserved` - given size of reserved range in bytes.
+    fn add_memory(&mut self, start: usize, mapped: usize, reserved: usize) {
+        if mapped != 0 {
+            self.insert_hole(start, mapped);
+            self.stats.size += mapped;
+        }
+
+        if self.top == start + reserved {
+            self.top = start + mapped;
+        }
+    }
+
+    /// Get heap usage statistics.
+    ///
+    /// # Returns
+    /// - Heap usage statistics.
+    #[inline(always)]
+    pub fn stats(&self) -> HeapStats {
+        self.stats
+    }
+}
+
+/// Map newly allocated physical frames to heap window range.
+///
+/// # Parameters
+/// - `start` - given page aligned start address of the range.
+/// - `size`  - given range size in bytes.
+///
+/// # Returns
+/// - Number of bytes mapped at range start.
+fn map_pages(start: usize, size: usize) -> usize {
+    let page_size = PAGE_SIZE as usize;
+
+    (start..start + size)
+        .step_by(page_size)
+        .take_while(|&vaddr| {
+            frame::alloc_frame().is_some_and(|paddr| {
+                mmu::map(vaddr, paddr, MapFlags::WRITABLE)
+                    .inspect_err(|_| frame::free_frame(paddr))
+                    .is_ok()
+            })
+        })
+        .count()
+        * page_size
+}
+
+/// Kernel heap global allocator.
+pub struct KernelHeap(Mutex<Heap>);
+
+impl KernelHeap {
+    /// Run function with heap locked & interrupts disabled, so that IRQ
+    /// handler allocating memory never waits for the lock it interrupted.
+    ///
+    /// # Parameters
+    /// - `func` - given function called with locked heap.
+    ///
+    /// # Returns
+    /// - Result of the function.
+    fn locked<T>(&self, func: impl FnOnce(&mut Heap) -> T) -> T {
+        irq::without_interrupts(|| func(&mut self.0.lock()))
+    }
+
+    /// Grow heap by mapping new memory outside of the heap lock.
+    ///
+    /// # Parameters
+    /// - `size` - given minimal number of bytes to grow by.
+    ///
+    /// # Returns
+    /// - `Some` - in case of success.
+    /// - `None` - otherwise.
+    fn grow(&self, size: usize) -> Option<()> {
+        let (start, reserved) = self.locked(|heap| heap.reserve(size))?;
+        let mapped = map_pages(start, reserved);
+
+        // Keep pages that were mapped even if the rest failed.
+        self.locked(|heap| heap.add_memory(start, mapped, reserved));
+        (mapped == reserved).then_some(())
+    }
+}
+
+unsafe impl GlobalAlloc for KernelHeap {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let addr = self.locked(|heap| heap.alloc(layout)).or_else(|| {
+            let align = layout.align().max(align_of::<Hole>());
+            self.grow(block_size(layout.size()) + align)?;
+            self.locked(|heap| heap.alloc(layout))
+        });
+
+        match addr {
+            Some(addr) => addr as *mut u8,
+            None => ptr::null_mut(),
+        }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.locked(|heap| heap.free(ptr as usize, layout));
+    }
+}
+
+/// Kernel heap used by `alloc` crate.
+#[global_allocator]
+static KERNEL_HEAP: KernelHeap = KernelHeap(Mutex::new(Heap::empty()));
+
+/// Kernel heap allocation failure handler.
+///
+/// # Parameters
+/// - `layout` - given layout of failed allocation.
+#[alloc_error_handler]
+fn alloc_error(layout: Layout) -> ! {
+    log::fail!(
+        "Failed to allocate {} bytes (align {}) from kernel heap",
+        layout.size(),
+        layout.align()
+    );
+
+    panic!("Out of kernel heap memory");
+}
+
+/// Get kernel heap usage statistics.
+///
+/// # Returns
+/// - Kernel heap usage statistics.
+pub fn stats() -> HeapStats {
+    KERNEL_HEAP.locked(|heap| heap.stats())
+}
+
+/// Initialize kernel heap.
+pub fn init() {
+    if KERNEL_HEAP.grow(HEAP_INITIAL_SIZE).is_none() {
+        panic!("No memory for kernel heap");
+    }
+}
+
+#[cfg(feature = "ktest")]
+pub mod tests {
+    use super::*;
+    use crate::ktest::TestCase;
+    use alloc::{boxed::Box, collections::BTreeMap, string::String, vec::Vec};
+
+    /// Kernel heap unit tests.
+    pub const TESTS: &[TestCase] = &[
+        TestCase::new("box_and_vec", box_and_vec),
+        TestCase::new("aligned_alloc", aligned_alloc),
+        TestCase::new("heap_grows", heap_grows),
+        TestCase::new("freed_memory_is_reused", freed_memory_is_reused),
+        TestCase::new("alloc_keeps_irq_state", alloc_keeps_irq_state),
+    ];
+
+    fn box_and_vec() {
+        let value = Box::new(42u32);
+        let mut vec: Vec<u32> = (0..1000).collect();
+        vec.push(*value);
+
+        let mut map = BTreeMap::new();
+        map.insert(String::from("key"), vec.len());
+
+        assert_eq!(vec.iter().sum::<u32>(), 499500 + 42);
+        assert_eq!(map.get("key"), Some(&1001));
+    }
+
+    fn aligned_alloc() {
+        let layout = Layout::from_size_align(100, 4096).unwrap();
+
+        unsafe {
+            let ptr = alloc::alloc::alloc(layout);
+            assert!(!ptr.is_null());
+            assert_eq!(ptr as usize & 4095, 0);
+            alloc::alloc::dealloc(ptr, layout);
+        }
+    }
+
+    fn heap_grows() {
+        let before = stats();
+        let big: Vec<u8> = alloc::vec![0xAA; before.size * 2];
+
+        assert!(stats().size > before.size);
+        assert!(big.iter().all(|&byte| byte == 0xAA));
+    }
+
+    fn freed_memory_is_reused() {
+        let used = stats().used;
+        let first = Box::new([0u64; 64]);
+        let addr = &*first as *const _ as usize;
+        drop(first);
+
+        let second = Box::new([1u64; 64]);
+        assert_eq!(&*second as *const _ as usize, addr);
+        drop(second);
+
+        assert_eq!(stats().used, used);
+    }
+
+    fn alloc_keeps_irq_state() {
+        let enabled = irq::are_enabled();
+        drop(Box::new(0u32));
+        assert_eq!(irq::are_enabled(), enabled);
+
+        // Allocation with interrupts disabled (as in IRQ handler) leaves
+        // them disabled, growing the heap if needed.
+        irq::without_interrupts(|| {
+            let big: Vec<u8> = alloc::vec![0x55; stats().size];
+
+            assert!(!irq::are_enabled());
+            assert!(big.iter().all(|&byte| byte == 0x55));
+        });
+
+        assert_eq!(irq::are_enabled(), enabled);
+    }
+}
@@ -0,0 +1,111 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Date: 2025-06-13
+// Author: Alexander Kuzin <alkuzindev@gmail.com>.
+
+//! Kernel virtual address space.
+//!
+//! # Description
+//! Kernel page directory maps only:
+//! - kernel image sections (including boot stack), except for boot stack
+//!   guard page, with W^X rights: `.text` is read-only & executable,
+//!   `.rodata` is read-only, `.data` & `.bss` are writable (non-executable
+//!   if supported),
+//! - frames bitmap, paging structures and frames allocated through
+//!   `frame::alloc_direct` (by slab & buddy allocators) at their direct
+//!   addresses, only while they are in use,
+//! - kernel heap & kernel stacks pages mapped by their allocators,
+//! - device memory explicitly requested through `mmio::ioremap`.
+//!
+//! Everything else, including free physical memory and the whole user part
+//! of the address space, is left unmapped, so that null pointer and wild
+//! accesses fault.
+
+use super::{
+    PAGE_SIZE, align_down, align_up, frame, phys_to_virt, virt_to_phys,
+};
+use crate::{
+    hal::mmu::{self, MapError, MapFlags},
+    kernel::memlayout,
+    log,
+};
+use core::ops::Range;
+
+/// Map virtual range to physical memory page by page.
+///
+/// # Parameters
+/// - `vaddr` - given page aligned virtual address.
+/// - `size`  - given range size in bytes (rounded up to pages).
+/// - `flags` - given page mapping flags.
+///
+/// # Returns
+/// - `Ok`  - in case of success.
+/// - `Err` - otherwise.
+fn map_direct(
+    vaddr: usize,
+    size: usize,
+    flags: MapFlags,
+) -> Result<(), MapError> {
+    let paddr = virt_to_phys(vaddr);
+    mmu::map_range(vaddr, paddr, size, flags)
+}
+
+/// Map kernel image section.
+///
+/// # Parameters
+/// - `name`    - given section name.
+/// - `section` - given section virtual addresses range.
+/// - `flags`   - given page mapping flags.
+fn map_section(name: &str, section: Range<usize>, flags: MapFlags) {
+    let start = align_down(section.start as u64, PAGE_SIZE) as usize;
+    let end = align_up(section.end as u64, PAGE_SIZE) as usize;
+
+    if let Err(err) = map_direct(start, end - start, flags) {
+        panic!("Failed to map kernel section <{}>: {}", name, err);
+    }
+
+    log::debug!("Mapped {:<7} [{:#010X}-{:#010X}]", name, start, end);
+}
+
+/// Map frames bitmap, which is allocated before kernel page directory.
+fn map_frames_bitmap() {
+    let range = frame::bitmap_range();
+    let vaddr = phys_to_virt(range.start);
+    let size = (range.end - range.start) as usize;
+
+    if let Err(err) = map_direct(vaddr, size, MapFlags::WRITABLE) {
+        panic!("Failed to map frames bitmap: {}", err);
+    }
+
+    log::debug!(
+        "Mapped frames bitmap [{:#010X}-{:#010X}]",
+        vaddr,
+        vaddr + size
+    );
+}
+
+/// Unmap boot stack guard page, so that boot stack overflow faults.
+fn unmap_stack_guard() {
+    let guard = memlayout::stack_guard_vaddr();
+
+    // Guard frame belongs to kernel image, so it is never reused.
+    mmu::unmap(guard);
+    log::debug!("Unmapped boot stack guard page <{:#010X}>", guard);
+}
+
+/// Build kernel page directory & switch to it.
+pub fn init() {
+    let kernel_base = memlayout::base_vaddr() as usize;
+
+    if let Err(err) = mmu::init(kernel_base) {
+        panic!("Failed to create kernel page directory: {}", err);
+    }
+
+    map_section(".text", memlayout::text_section(), MapFlags::EXECUTABLE);
+    map_section(".rodata", memlayout::rodata_section(), MapFlags::READ);
+    map_section(".data", memlayout::data_section(), MapFlags::WRITABLE);
+    map_section(".bss", memlayout::bss_section(), MapFlags::WRITABLE);
+    map_frames_bitmap();
+    unmap_stack_guard();
+
+    mmu::activate();
+}
@@ -0,0 +1,204 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Date: 2025-06-13
+// Author: Alexander Kuzin <alkuzindev@gmail.com>.
+
+//! Kernel stacks.
+//!
+//! # Description
+//! Kernel stacks live in dedicated window of kernel virtual address space
+//! split into equal slots. Every slot starts with unmapped guard page, so
+//! that stack overflow faults instead of corrupting neighbour memory.
+
+use super::{PAGE_SIZE, frame, mmio::MMIO_BASE};
+use crate::{
+    hal::mmu::{self, MapFlags},
+    kernel::memlayout,
+};
+use alloc::vec::Vec;
+use spin::Mutex;
+
+/// Start of the kernel stacks virtual window.
+pub const KSTACK_BASE: usize = 0xF7000000;
+
+/// End of the kernel stacks virtual window (exclusive).
+pub const KSTACK_END: usize = MMIO_BASE;
+
+/// Kernel stack size (without guard page).
+pub const KSTACK_SIZE: usize = 0x10000;
+
+/// Guard page size.
+const GUARD_SIZE: usize = PAGE_SIZE as usize;
+
+/// Size of single stack slot (guard page & stack).
+const SLOT_SIZE: usize = GUARD_SIZE + KSTACK_SIZE;
+
+/// Number of stack slots in the window.
+const SLOTS: usize = (KSTACK_END - KSTACK_BASE) / SLOT_SIZE;
+
+/// Stack slots allocator.
+struct Slots {
+    /// First never used slot.
+    next: usize,
+    /// Released slots.
+    free: Vec<usize>,
+}
+
+/// Stack slots of the window.
+static STACK_SLOTS: Mutex<Slots> = Mutex::new(Slots {
+    next: 0,
+    free: Vec::new(),
+});
+
+/// Kernel stack with guard page below it.
+#[derive(Debug)]
+pub struct KernelStack {
+    /// Slot index in kernel stacks window.
+    slot: usize,
+}
+
+impl KernelStack {
+    /// Allocate & map new kernel stack.
+    ///
+    /// # Returns
+    /// - New `KernelStack` object - in case of success.
+    /// - `None`                   - otherwise.
+    pub fn new() -> Option<Self> {
+        let slot = {
+            let mut slots = STACK_SLOTS.lock();
+
+            match slots.free.pop() {
+                Some(slot) => slot,
+                None if slots.next < SLOTS => {
+                    slots.next += 1;
+                    slots.next - 1
+                }
+                None => return None,
+            }
+        };
+
+        // Stack is dropped (unmapped) if some page fails to map.
+        let stack = Self { slot };
+
+        for vaddr in (stack.bottom()..stack.top()).step_by(GUARD_SIZE) {
+            let paddr = frame::alloc_frame()?;
+
+            if mmu::map(vaddr, paddr, MapFlags::WRITABLE).is_err() {
+                frame::free_frame(paddr);
+                return None;
+            }
+        }
+
+        Some(stack)
+    }
+
+    /// Get stack bottom (lowest usable address).
+    ///
+    /// # Returns
+    /// - Virtual address of the stack bottom.
+    #[inline(always)]
+    pub fn bottom(&self) -> usize {
+        KSTACK_BASE + self.slot * SLOT_SIZE + GUARD_SIZE
+    }
+
+    /// Get stack top (initial stack pointer).
+    ///
+    /// # Returns
+    /// - Virtual address of the stack top.
+    #[inline(always)]
+    pub fn top(&self) -> usize {
+        self.bottom() + KSTACK_SIZE
+    }
+}
+
+impl Drop for KernelStack {
+    /// Unmap stack, free its frames & release its slot.
+    fn drop(&mut self) {
+        for vaddr in (self.bottom()..self.top()).step_by(GUARD_SIZE) {
+            if let Some(paddr) = mmu::unmap(vaddr) {
+                frame::free_frame(paddr);
+            }
+        }
+
+        STACK_SLOTS.lock().free.push(self.slot);
+    }
+}
+
+/// Get top of the boot kernel stack.
+///
+/// # Returns
+/// - Virtual address of the boot stack top.
+pub fn boot_stack_top() -> usize {
+    memlayout::stack_top_vaddr()
+}
+
+/// Check whether address belongs to guard page of some kernel stack.
+///
+/// # Parameters
+/// - `vaddr` - given virtual address.
+///
+/// # Returns
+/// - `true`  - if address is inside guard page.
+/// - `false` - otherwise.
+pub fn is_guard_page(vaddr: usize) -> bool {
+    let boot_guard = memlayout::stack_guard_vaddr();
+
+    if (boot_guard..boot_guard + GUARD_SIZE).contains(&vaddr) {
+        return true;
+    }
+
+    (KSTACK_BASE..KSTACK_END).contains(&vaddr)
+        && (vaddr - KSTACK_BASE) % SLOT_SIZE < GUARD_SIZE
+}
+
+#[cfg(feature = "ktest")]
+pub mod tests {
+    use super::*;
+    use crate::ktest::TestCase;
+
+    /// Kernel stacks unit tests.
+    pub const TESTS: &[TestCase] = &[
+        TestCase::new("boot_stack_guard", boot_stack_guard),
+        TestCase::new("stack_has_guard", stack_has_guard),
+        TestCase::new("stack_is_released", stack_is_released),
+    ];
+
+    fn boot_stack_guard() {
+        let guard = memlayout::stack_guard_vaddr();
+
+        assert_eq!(guard + GUARD_SIZE, memlayout::stack_bottom_vaddr());
+        assert!(mmu::translate(guard).is_none());
+        assert!(mmu::translate(memlayout::stack_bottom_vaddr()).is_some());
+        assert!(is_guard_page(guard));
+    }
+
+    fn stack_has_guard() {
+        let stack = KernelStack::new().unwrap();
+        let guard = stack.bottom() - GUARD_SIZE;
+
+        assert_eq!(stack.top() - stack.bottom(), KSTACK_SIZE);
+        assert!(mmu::translate(guard).is_none());
+        assert!(mmu::translate(stack.bottom()).is_some());
+        assert!(mmu::translate(stack.top() - 1).is_some());
+        assert!(is_guard_page(guard));
+        assert!(!is_guard_page(stack.bottom()));
+
+        unsafe {
+            let ptr = (stack.top() - 4) as *mut u32;
+            ptr.write_volatile(0xC0FFEE);
+            assert_eq!(ptr.read_volatile(), 0xC0FFEE);
+        }
+    }
+
+    fn stack_is_released() {
+        let free = frame::stats().free;
+        let stack = KernelStack::new().unwrap();
+        let bottom = stack.bottom();
+        drop(stack);
+
+        assert_eq!(frame::stats().free, free);
+        assert!(mmu::translate(bottom).is_none());
+
+        let stack = KernelStack::new().unwrap();
+        assert_eq!(stack.bottom(), bottom);
+    }
+}
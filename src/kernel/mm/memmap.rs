@@ -0,0 +1,381 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Date: 2025-06-13
+// Author: Alexander Kuzin <alkuzindev@gmail.com>.
+
+//! Sanitized physical memory map.
+//!
+//! # Description
+//! Bootloader provided memory map may contain overlapping, unsorted and
+//! unaligned regions. `PhysMemoryMap` splits all regions at their
+//! boundaries, so that every part of physical memory has a single type.
+//! If regions overlap, the more restrictive type wins. Memory occupied by
+//! the kernel image, boot modules and framebuffer is carved out of usable
+//! memory and usable regions are shrunk to page boundaries.
+
+use super::{PAGE_SIZE, PhysAddr, align_down, align_up};
+use crate::{
+    bootinfo::{BootInfo, MAX_MEMORY_REGIONS, MAX_MODULES, MemoryRegionType},
+    kernel::memlayout,
+    log,
+};
+
+/// Maximum number of sanitized memory map regions.
+pub const MAX_REGIONS: usize = 128;
+
+/// Maximum number of raw regions (memory map & carved out regions).
+const MAX_RAW_REGIONS: usize = MAX_MEMORY_REGIONS + MAX_MODULES + 4;
+
+/// End of low memory reserved for BIOS & real mode structures.
+const LOW_MEMORY_END: PhysAddr = 0x100000;
+
+/// Physical memory region type. Types are declared in order from least
+/// to most restrictive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PhysRegionType {
+    /// Memory that can be used by allocators.
+    Usable,
+    /// Kernel image.
+    Kernel,
+    /// Boot module.
+    Module,
+    /// Framebuffer memory.
+    Framebuffer,
+    /// Memory holding ACPI tables that can be reclaimed after parsing.
+    AcpiReclaimable,
+    /// ACPI non-volatile storage.
+    AcpiNvs,
+    /// Reserved memory.
+    Reserved,
+    /// Defective RAM.
+    BadRam,
+}
+
+impl From<MemoryRegionType> for PhysRegionType {
+    /// Convert boot memory region type.
+    ///
+    /// # Parameters
+    /// - `value` - given boot memory region type.
+    ///
+    /// # Returns
+    /// - `PhysRegionType` enumeration member associated with `value`.
+    fn from(value: MemoryRegionType) -> Self {
+        match value {
+            MemoryRegionType::Available => Self::Usable,
+            MemoryRegionType::AcpiReclaimable => Self::AcpiReclaimable,
+            MemoryRegionType::AcpiNvs => Self::AcpiNvs,
+            MemoryRegionType::BadRam => Self::BadRam,
+            // Unknown types must never be used.
+            MemoryRegionType::Reserved | MemoryRegionType::Unknown(_) => {
+                Self::Reserved
+            }
+        }
+    }
+}
+
+/// Physical memory region.
+#[derive(Debug, Clone, Copy)]
+pub struct PhysRegion {
+    /// Region physical start address.
+    pub start: PhysAddr,
+    /// Region physical end address (exclusive).
+    pub end: PhysAddr,
+    /// Region type.
+    pub rtype: PhysRegionType,
+}
+
+impl PhysRegion {
+    /// Empty region.
+    const EMPTY: Self = Self {
+        start: 0,
+        end: 0,
+        rtype: PhysRegionType::Reserved,
+    };
+
+    /// Get region size.
+    ///
+    /// # Returns
+    /// - Region size in bytes.
+    #[inline(always)]
+    pub fn size(&self) -> u64 {
+        self.end - self.start
+    }
+
+    /// Check whether region contains whole address range.
+    ///
+    /// # Parameters
+    /// - `start` - given range start address.
+    /// - `end`   - given range end address (exclusive).
+    ///
+    /// # Returns
+    /// - `true`  - if region contains the range.
+    /// - `false` - otherwise.
+    #[inline(always)]
+    fn contains(&self, start: PhysAddr, end: PhysAddr) -> bool {
+        self.start <= start && end <= self.end
+    }
+}
+
+/// Sanitized physical memory map.
+pub struct PhysMemoryMap {
+    /// Sorted non-overlapping memory regions.
+    regions: [PhysRegion; MAX_REGIONS],
+    /// Number of memory regions.
+    count: usize,
+}
+
+impl PhysMemoryMap {
+    /// Build sanitized physical memory map.
+    ///
+    /// # Parameters
+    /// - `boot_info` - given bootloader independent boot information.
+    ///
+    /// # Returns
+    /// - New `PhysMemoryMap` object.
+    pub fn new(boot_info: &BootInfo) -> Self {
+        let mut raw = [PhysRegion::EMPTY; MAX_RAW_REGIONS];
+        let mut count = 0;
+
+        let mut push = |start: PhysAddr, end: PhysAddr, rtype| {
+            if start < end && count < MAX_RAW_REGIONS {
+                raw[count] = PhysRegion { start, end, rtype };
+                count += 1;
+            }
+        };
+
+        for region in boot_info.memory_regions() {
+            push(region.start, region.end(), region.rtype.into());
+        }
+
+        // Carve out memory that is already in use.
+        push(0, LOW_MEMORY_END, PhysRegionType::Reserved);
+        push(
+            memlayout::kernel_begin_paddr() as PhysAddr,
+            memlayout::kernel_end_paddr() as PhysAddr,
+            PhysRegionType::Kernel,
+        );
+
+        for module in boot_info.modules() {
+            push(module.start, module.end, PhysRegionType::Module);
+        }
+
+        if let Some(fb) = boot_info.framebuffer {
+            push(fb.addr, fb.addr + fb.size(), PhysRegionType::Framebuffer);
+        }
+
+        Self::from_raw(&raw[..count])
+    }
+
+    /// Build sanitized physical memory map from raw regions.
+    ///
+    /// # Parameters
+    /// - `raw` - given raw (possibly overlapping) memory regions.
+    ///
+    /// # Returns
+    /// - New `PhysMemoryMap` object.
+    fn from_raw(raw: &[PhysRegion]) -> Self {
+        let mut map = Self {
+            regions: [PhysRegion::EMPTY; MAX_REGIONS],
+            count: 0,
+        };
+
+        // Collect all region boundaries.
+        let mut points = [0 as PhysAddr; 2 * MAX_RAW_REGIONS];
+        let mut npoints = 0;
+
+        for region in raw {
+            points[npoints] = region.start;
+            points[npoints + 1] = region.end;
+            npoints += 2;
+        }
+
+        let points = &mut points[..npoints];
+        points.sort_unstable();
+
+        // Assign the most restrictive type to each elementary interval.
+        for pair in points.windows(2) {
+            let (start, end) = (pair[0], pair[1]);
+
+            if start == end {
+                continue;
+            }
+
+            let rtype = raw
+                .iter()
+                .filter(|region| region.contains(start, end))
+                .map(|region| region.rtype)
+                .max();
+
+            if let Some(rtype) = rtype {
+                map.push(start, end, rtype);
+            }
+        }
+
+        map.align_usable();
+        map
+    }
+
+    /// Append region merging it with the previous region if possible.
+    ///
+    /// # Parameters
+    /// - `start` - given region start address.
+    /// - `end`   - given region end address (exclusive).
+    /// - `rtype` - given region type.
+    fn push(&mut self, start: PhysAddr, end: PhysAddr, rtype: PhysRegionType) {
+        if self.count > 0 {
+            let last = &mut self.regions[self.count - 1];
+
+            if last.end == start && last.rtype == rtype {
+                last.end = end;
+                return;
+            }
+        }
+
+        if self.count >= MAX_REGIONS {
+            log::fail!("Physical memory map is full, dropped <{:#X}>", start);
+            return;
+        }
+
+        self.regions[self.count] = PhysRegion { start, end, rtype };
+        self.count += 1;
+    }
+
+    /// Shrink usable regions to page boundaries.
+    /// Usable regions smaller than a page are removed.
+    fn align_usable(&mut self) {
+        let mut count = 0;
+
+        for i in 0..self.count {
+            let mut region = self.regions[i];
+
+            if region.rtype == PhysRegionType::Usable {
+                region.start = align_up(region.start, PAGE_SIZE);
+                region.end = align_down(region.end, PAGE_SIZE);
+
+                if region.start >= region.end {
+                    continue;
+                }
+            }
+
+            self.regions[count] = region;
+            count += 1;
+        }
+
+        self.count = count;
+    }
+
+    /// Get sanitized memory regions.
+    ///
+    /// # Returns
+    /// - Sorted non-overlapping memory regions slice.
+    pub fn regions(&self) -> &[PhysRegion] {
+        &self.regions[..self.count]
+    }
+
+    /// Get usable memory regions.
+    ///
+    /// # Returns
+    /// - Iterator over page-aligned usable memory regions.
+    pub fn usable(&self) -> impl Iterator<Item = &PhysRegion> {
+        self.regions()
+            .iter()
+            .filter(|region| region.rtype == PhysRegionType::Usable)
+    }
+
+    /// Get total usable memory size.
+    ///
+    /// # Returns
+    /// - Total size of usable memory in bytes.
+    pub fn usable_size(&self) -> u64 {
+        self.usable().map(PhysRegion::size).sum()
+    }
+
+    /// Get end of usable memory.
+    ///
+    /// # Returns
+    /// - Physical address right after the last usable byte.
+    pub fn usable_end(&self) -> PhysAddr {
+        self.usable().map(|region| region.end).max().unwrap_or(0)
+    }
+
+    /// Print physical memory map for debug.
+    pub fn display(&self) {
+        log::debug!("Physical memory map:");
+
+        for region in self.regions() {
+            log::debug!(
+                "[{:#012X}-{:#012X}] {:?}",
+                region.start,
+                region.end,
+                region.rtype
+            );
+        }
+
+        log::info!("Usable memory: {} KiB", self.usable_size() / 1024);
+    }
+}
+
+#[cfg(feature = "ktest")]
+pub mod tests {
+    use super::*;
+    use crate::ktest::TestCase;
+
+    /// Physical memory map unit tests.
+    pub const TESTS: &[TestCase] = &[
+        TestCase::new("restrictive_type_wins", restrictive_type_wins),
+        TestCase::new("usable_is_page_aligned", usable_is_page_aligned),
+        TestCase::new("adjacent_regions_merge", adjacent_regions_merge),
+    ];
+
+    /// Construct raw region.
+    const fn region(start: u64, end: u64, rtype: PhysRegionType) -> PhysRegion {
+        PhysRegion { start, end, rtype }
+    }
+
+    fn restrictive_type_wins() {
+        let map = PhysMemoryMap::from_raw(&[
+            region(0x100000, 0x400000, PhysRegionType::Usable),
+            region(0x200000, 0x300000, PhysRegionType::Reserved),
+            region(0x280000, 0x500000, PhysRegionType::AcpiNvs),
+        ]);
+        let regions = map.regions();
+
+        assert_eq!(regions.len(), 3);
+        assert_eq!(regions[0].end, 0x200000);
+        assert_eq!(regions[1].rtype, PhysRegionType::Reserved);
+        assert_eq!((regions[1].start, regions[1].end), (0x200000, 0x300000));
+        assert_eq!(regions[2].rtype, PhysRegionType::AcpiNvs);
+        assert_eq!((regions[2].start, regions[2].end), (0x300000, 0x500000));
+        assert_eq!(map.usable_size(), 0x100000);
+    }
+
+    fn usable_is_page_aligned() {
+        let map = PhysMemoryMap::from_raw(&[
+            region(0x1000_0010, 0x1000_2FF0, PhysRegionType::Usable),
+            region(0x2000_0000, 0x2000_0800, PhysRegionType::Usable),
+        ]);
+        let regions = map.regions();
+
+        assert_eq!(regions.len(), 1);
+        assert_eq!(
+            (regions[0].start, regions[0].end),
+            (0x1000_1000, 0x1000_2000)
+        );
+    }
+
+    fn adjacent_regions_merge() {
+        let map = PhysMemoryMap::from_raw(&[
+            region(0x3000_0000, 0x3000_4000, PhysRegionType::Usable),
+            region(0x3000_4000, 0x3000_8000, PhysRegionType::Usable),
+            region(0x3000_2000, 0x3000_3000, PhysRegionType::Kernel),
+        ]);
+        let regions = map.regions();
+
+        assert_eq!(regions.len(), 3);
+        assert_eq!(regions[1].rtype, PhysRegionType::Kernel);
+        assert_eq!(
+            (regions[2].start, regions[2].end),
+            (0x3000_3000, 0x3000_8000)
+        );
+        assert_eq!(map.usable_end(), 0x3000_8000);
+    }
+}
@@ -0,0 +1,340 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Date: 2025-06-13
+// Author: Alexander Kuzin <alkuzindev@gmail.com>.
+
+//! Memory mapped device I/O.
+//!
+//! # Description
+//! Device memory (framebuffer, controller registers) is not part of the
+//! direct mapping. Drivers explicitly map it into dedicated window of
+//! kernel virtual address space with `ioremap` and access it through
+//! `MmioRegion`, either by offset or as typed register block made of
+//! `Volatile` fields:
+//!
+//! ```
+//! #[repr(C)]
+//! struct Registers {
+//!     id: Volatile<u32>,
+//!     control: Volatile<u32>,
+//! }
+//!
+//! let region = ioremap(paddr, size, CacheMode::Uncached)?;
+//! let regs = unsafe { region.block::<Registers>(0) };
+//! regs.control.update(|value| value | 1);
+//! ```
+
+use super::{PAGE_SIZE, PhysAddr, align_down, align_up};
+use crate::{
+    hal::mmu::{self, MapFlags},
+    log,
+};
+use core::{cell::UnsafeCell, fmt, ptr};
+use spin::Mutex;
+
+/// Start of the kernel virtual window for device memory.
+pub const MMIO_BASE: usize = 0xF8000000;
+
+/// End of the kernel virtual window for device memory (exclusive).
+pub const MMIO_END: usize = 0xFFC00000;
+
+/// Next free virtual address of device memory window.
+static NEXT_VADDR: Mutex<usize> = Mutex::new(MMIO_BASE);
+
+/// Value accessed only with volatile reads & writes (device register).
+#[repr(transparent)]
+pub struct Volatile<T: Copy>(UnsafeCell<T>);
+
+// Registers are shared with device anyway, every access is volatile.
+unsafe impl<T: Copy + Send> Sync for Volatile<T> {}
+
+impl<T: Copy> Volatile<T> {
+    /// Construct volatile value.
+    ///
+    /// # Parameters
+    /// - `value` - given initial value.
+    ///
+    /// # Returns
+    /// - New `Volatile` object.
+    pub const fn new(value: T) -> Self {
+        Self(UnsafeCell::new(value))
+    }
+
+    /// Read value.
+    ///
+    /// # Returns
+    /// - Current value.
+    #[inline(always)]
+    pub fn read(&self) -> T {
+        unsafe { ptr::read_volatile(self.0.get()) }
+    }
+
+    /// Write value.
+    ///
+    /// # Parameters
+    /// - `value` - given value to write.
+    #[inline(always)]
+    pub fn write(&self, value: T) {
+        unsafe { ptr::write_volatile(self.0.get(), value) }
+    }
+
+    /// Read value, modify it & write it back.
+    ///
+    /// # Parameters
+    /// - `func` - given function to compute new value from current one.
+    #[inline(always)]
+    pub fn update(&self, func: impl FnOnce(T) -> T) {
+        self.write(func(self.read()));
+    }
+}
+
+impl<T: Copy + fmt::Debug> fmt::Debug for Volatile<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Volatile").field(&self.read()).finish()
+    }
+}
+
+/// Memory type of device memory mapping.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheMode {
+    /// Caching is disabled (for controller registers).
+    Uncached,
+    /// Writes are combined in buffers (for framebuffers).
+    WriteCombining,
+}
+
+/// Device memory mapped into kernel virtual address space.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MmioRegion {
+    /// Virtual address of device memory.
+    vaddr: usize,
+    /// Physical address of device memory.
+    paddr: PhysAddr,
+    /// Size of device memory in bytes.
+    size: usize,
+}
+
+impl MmioRegion {
+    /// Get virtual address of device memory.
+    ///
+    /// # Returns
+    /// - Virtual address of the region start.
+    #[inline(always)]
+    pub fn vaddr(&self) -> usize {
+        self.vaddr
+    }
+
+    /// Get physical address of device memory.
+    ///
+    /// # Returns
+    /// - Physical address of the region start.
+    #[inline(always)]
+    pub fn paddr(&self) -> PhysAddr {
+        self.paddr
+    }
+
+    /// Get size of device memory.
+    ///
+    /// # Returns
+    /// - Region size in bytes.
+    #[inline(always)]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Get raw pointer to device memory (for bulk copies).
+    ///
+    /// # Returns
+    /// - Region start as mutable raw pointer.
+    #[inline(always)]
+    pub fn as_ptr<T>(&self) -> *mut T {
+        self.vaddr as *mut T
+    }
+
+    /// Get register at given offset.
+    ///
+    /// # Parameters
+    /// - `offset` - given register offset in bytes.
+    ///
+    /// # Returns
+    /// - Volatile register reference.
+    pub fn reg<T: Copy>(&self, offset: usize) -> &Volatile<T> {
+        self.check::<T>(offset);
+        unsafe { &*((self.vaddr + offset) as *const Volatile<T>) }
+    }
+
+    /// Read register at given offset.
+    ///
+    /// # Parameters
+    /// - `offset` - given register offset in bytes.
+    ///
+    /// # Returns
+    /// - Register value.
+    #[inline(always)]
+    pub fn read<T: Copy>(&self, offset: usize) -> T {
+        self.reg::<T>(offset).read()
+    }
+
+    /// Write register at given offset.
+    ///
+    /// # Parameters
+    /// - `offset` - given register offset in bytes.
+    /// - `value`  - given value to write.
+    #[inline(always)]
+    pub fn write<T: Copy>(&self, offset: usize, value: T) {
+        self.reg::<T>(offset).write(value);
+    }
+
+    /// Get typed register block at given offset.
+    ///
+    /// # Parameters
+    /// - `offset` - given register block offset in bytes.
+    ///
+    /// # Returns
+    /// - Register block reference.
+    ///
+    /// # Safety
+    /// - `T` must describe device registers, fields accessed by the device
+    ///   must be `Volatile`.
+    pub unsafe fn block<T>(&self, offset: usize) -> &T {
+        self.check::<T>(offset);
+        unsafe { &*((self.vaddr + offset) as *const T) }
+    }
+
+    /// Check that value of given type at given offset lies inside region &
+    /// is properly aligned.
+    ///
+    /// # Parameters
+    /// - `offset` - given value offset in bytes.
+    fn check<T>(&self, offset: usize) {
+        assert!(
+            offset + size_of::<T>() <= self.size,
+            "MMIO access at {offset:#X} is out of region bounds"
+        );
+        assert!(
+            (self.vaddr + offset) % align_of::<T>() == 0,
+            "Unaligned MMIO access at {offset:#X}"
+        );
+    }
+}
+
+/// Map device memory into kernel address space.
+///
+/// # Parameters
+/// - `paddr` - given physical address of device memory.
+/// - `size`  - given size of device memory in bytes.
+/// - `mode`  - given memory type of the mapping.
+///
+/// # Returns
+/// - Mapped device memory - in case of success.
+/// - `None`               - otherwise.
+pub fn ioremap(
+    paddr: PhysAddr,
+    size: usize,
+    mode: CacheMode,
+) -> Option<MmioRegion> {
+    let start = align_down(paddr, PAGE_SIZE);
+    let end = align_up(paddr + size as u64, PAGE_SIZE);
+    let len = (end - start) as usize;
+
+    let mut next = NEXT_VADDR.lock();
+    let vaddr = *next;
+
+    if len > MMIO_END - vaddr {
+        log::fail!("MMIO window is exhausted, can not map <{:#X}>", paddr);
+        return None;
+    }
+
+    let flags = match mode {
+        CacheMode::Uncached => MapFlags::WRITABLE | MapFlags::NO_CACHE,
+        CacheMode::WriteCombining => {
+            MapFlags::WRITABLE | MapFlags::WRITE_COMBINE
+        }
+    };
+
+    if let Err(err) = mmu::map_range(vaddr, start, len, flags) {
+        log::fail!("Failed to map MMIO <{:#X}>: {}", paddr, err);
+        return None;
+    }
+
+    *next += len;
+
+    Some(MmioRegion {
+        vaddr: vaddr + (paddr - start) as usize,
+        paddr,
+        size,
+    })
+}
+
+/// Unmap device memory. Virtual window space is not reused.
+///
+/// # Parameters
+/// - `region` - given mapped device memory.
+pub fn iounmap(region: MmioRegion) {
+    let start = align_down(region.vaddr as u64, PAGE_SIZE) as usize;
+    let end = align_up((region.vaddr + region.size) as u64, PAGE_SIZE) as usize;
+
+    for vaddr in (start..end).step_by(PAGE_SIZE as usize) {
+        mmu::unmap(vaddr);
+    }
+}
+
+#[cfg(feature = "ktest")]
+pub mod tests {
+    use super::*;
+    use crate::{
+        kernel::mm::{DIRECT_MAP_LIMIT, frame, phys_to_virt},
+        ktest::TestCase,
+    };
+
+    /// MMIO unit tests.
+    pub const TESTS: &[TestCase] = &[
+        TestCase::new("ioremap_access", ioremap_access),
+        TestCase::new("register_block", register_block),
+    ];
+
+    /// Register block layout used by tests.
+    #[repr(C)]
+    struct Registers {
+        id: Volatile<u32>,
+        control: Volatile<u32>,
+        counter: Volatile<u64>,
+    }
+
+    fn ioremap_access() {
+        let paddr = frame::alloc_direct(1, 1).unwrap();
+        let region = ioremap(paddr + 0x10, 0x20, CacheMode::Uncached).unwrap();
+
+        assert_eq!(region.paddr(), paddr + 0x10);
+        assert_eq!(region.size(), 0x20);
+        assert_eq!(mmu::translate(region.vaddr()), Some(paddr + 0x10));
+
+        // Write through MMIO mapping & read through direct mapping.
+        region.write::<u32>(4, 0xCAFEBABE);
+        let direct = (phys_to_virt(paddr) + 0x14) as *const u32;
+        assert_eq!(unsafe { direct.read_volatile() }, 0xCAFEBABE);
+        assert_eq!(region.read::<u32>(4), 0xCAFEBABE);
+
+        iounmap(region);
+        assert_eq!(mmu::translate(region.vaddr()), None);
+        frame::free_direct(paddr, 1);
+    }
+
+    fn register_block() {
+        let paddr = frame::alloc_frames_below(1, 1, DIRECT_MAP_LIMIT).unwrap();
+        let size = size_of::<Registers>();
+        let region = ioremap(paddr, size, CacheMode::WriteCombining).unwrap();
+        let regs = unsafe { region.block::<Registers>(0) };
+
+        regs.id.write(0x1234);
+        regs.control.write(0b0101);
+        regs.control.update(|value| value | 0b1000);
+        regs.counter.write(u64::MAX);
+
+        assert_eq!(region.read::<u32>(0), 0x1234);
+        assert_eq!(regs.control.read(), 0b1101);
+        assert_eq!(region.read::<u64>(8), u64::MAX);
+
+        iounmap(region);
+        frame::free_frame(paddr);
+    }
+}
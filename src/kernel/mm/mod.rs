@@ -0,0 +1,143 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Date: 2025-06-13
+// Author: Alexander Kuzin <alkuzindev@gmail.com>.
+
+//! Kernel memory management subsystem.
+
+pub mod buddy;
+pub mod frame;
+pub mod heap;
+mod kspace;
+pub mod kstack;
+pub mod memmap;
+pub mod mmio;
+pub mod slab;
+pub mod vm;
+
+use crate::{bootinfo::BootInfo, hal::mmu, log};
+use memmap::PhysMemoryMap;
+use spin::Once;
+
+/// Physical address type (wide enough for PAE addresses).
+pub type PhysAddr = u64;
+
+/// Size of the page (and physical frame) in bytes.
+pub const PAGE_SIZE: u64 = 4096;
+
+/// Virtual address of the direct mapping of physical memory.
+/// Kernel image is linked to be loaded at the same offset.
+pub const KERNEL_BASE: usize = 0xC0000000;
+
+/// End of physical memory directly accessible by the kernel. Frames below
+/// this limit are mapped at `KERNEL_BASE + paddr` only while the kernel
+/// uses them (see `frame::alloc_direct` & `with_direct`).
+pub const DIRECT_MAP_LIMIT: PhysAddr = 0x30000000;
+
+/// Convert directly accessible physical address to virtual address.
+///
+/// # Parameters
+/// - `paddr` - given physical address below `DIRECT_MAP_LIMIT`.
+///
+/// # Returns
+/// - Virtual address of the physical address.
+#[inline(always)]
+pub fn phys_to_virt(paddr: PhysAddr) -> usize {
+    debug_assert!(paddr < DIRECT_MAP_LIMIT);
+    KERNEL_BASE + paddr as usize
+}
+
+/// Convert directly mapped virtual address to physical address.
+///
+/// # Parameters
+/// - `vaddr` - given virtual address of directly mapped memory.
+///
+/// # Returns
+/// - Physical address of the virtual address.
+#[inline(always)]
+pub fn virt_to_phys(vaddr: usize) -> PhysAddr {
+    debug_assert!(vaddr >= KERNEL_BASE);
+    (vaddr - KERNEL_BASE) as PhysAddr
+}
+
+/// Access physical frame through temporary mapping at its direct virtual
+/// address.
+///
+/// # Parameters
+/// - `paddr` - given physical address of the frame below `DIRECT_MAP_LIMIT`.
+/// - `func`  - given function called with virtual address of the frame.
+///
+/// # Returns
+/// - Result of the function - in case of success.
+/// - `None`                 - if frame can not be mapped.
+pub fn with_direct<R>(
+    paddr: PhysAddr,
+    func: impl FnOnce(usize) -> R,
+) -> Option<R> {
+    mmu::map_direct(paddr).ok()?;
+    let result = func(phys_to_virt(paddr));
+    mmu::unmap_direct(paddr);
+
+    Some(result)
+}
+
+/// Align address up.
+///
+/// # Parameters
+/// - `addr`  - given address to align.
+/// - `align` - given power of two alignment.
+///
+/// # Returns
+/// - Smallest address aligned to `align` that is greater or equal to `addr`.
+#[inline(always)]
+pub const fn align_up(addr: u64, align: u64) -> u64 {
+    (addr + align - 1) & !(align - 1)
+}
+
+/// Align address down.
+///
+/// # Parameters
+/// - `addr`  - given address to align.
+/// - `align` - given power of two alignment.
+///
+/// # Returns
+/// - Largest address aligned to `align` that is less or equal to `addr`.
+#[inline(always)]
+pub const fn align_down(addr: u64, align: u64) -> u64 {
+    addr & !(align - 1)
+}
+
+/// Sanitized physical memory map.
+static MEMORY_MAP: Once<PhysMemoryMap> = Once::new();
+
+/// Get sanitized physical memory map.
+///
+/// # Returns
+/// - Physical memory map built during memory management initialization.
+pub fn memory_map() -> &'static PhysMemoryMap {
+    MEMORY_MAP
+        .get()
+        .expect("Physical memory map is not initialized")
+}
+
+/// Initialize memory management subsystem.
+///
+/// # Parameters
+/// - `boot_info` - given bootloader independent boot information.
+pub fn init(boot_info: &BootInfo) {
+    let map = MEMORY_MAP.call_once(|| PhysMemoryMap::new(boot_info));
+    map.display();
+    log::success!("Built physical memory map");
+
+    frame::init(map);
+    log::success!("Initialized physical frame allocator");
+
+    kspace::init();
+    log::success!("Switched to kernel page directory");
+
+    // Buddy allocator metadata is mapped in kernel page directory.
+    buddy::init();
+    log::success!("Initialized buddy allocator");
+
+    heap::init();
+    log::success!("Initialized kernel heap");
+}
@@ -0,0 +1,545 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Date: 2025-06-13
+// Author: Alexander Kuzin <alkuzindev@gmail.com>.
+
+//! Slab allocator of fixed size kernel objects.
+//!
+//! # Description
+//! Slab cache hands out objects of single type. Cache memory consists of
+//! slabs of physically contiguous frames, each slab is carved into equal
+//! object slots. Free slots of the slab are linked through link words
+//! placed right after the objects, so that object memory is left intact.
+//!
+//! Optional constructor initializes objects once, when their slab is
+//! created. Objects must be freed in constructed state, so constructor is
+//! not called on every allocation.
+//!
+//! In debug mode freed objects are filled with poison pattern, which is
+//! verified on the next allocation of the object to catch writes after
+//! free. Constructor is called on every allocation in this mode.
+
+use super::{PAGE_SIZE, frame, phys_to_virt, virt_to_phys};
+use crate::{
+    kernel::cmdline::{Param, ParamKind, kernel_param},
+    log,
+};
+use core::{
+    marker::PhantomData,
+    ptr::{self, NonNull},
+    sync::atomic::{AtomicBool, Ordering},
+};
+use spin::Mutex;
+
+/// Byte pattern freed objects are filled with in debug mode.
+const POISON: u8 = 0x6B;
+
+/// Minimal number of objects slab should hold.
+const MIN_OBJECTS: usize = 8;
+
+/// Maximal number of frames in slab.
+const MAX_SLAB_PAGES: usize = 16;
+
+/// Whether to poison freed objects of all caches.
+static SLAB_DEBUG: AtomicBool = AtomicBool::new(false);
+
+kernel_param! {
+    /// Kernel parameter enabling slab debug mode (`slab_debug`).
+    static SLAB_DEBUG_PARAM = Param {
+        name: "slab_debug",
+        description: "Poison freed slab objects & check them on allocation",
+        kind: ParamKind::Flag(&SLAB_DEBUG),
+    };
+}
+
+/// Object constructor hook.
+pub type Constructor<T> = fn(*mut T);
+
+/// Slab header placed at the beginning of the slab.
+struct Slab {
+    /// Next slab of the same list.
+    next: *mut Slab,
+    /// Previous slab of the same list.
+    prev: *mut Slab,
+    /// First free object.
+    free: usize,
+    /// Number of allocated objects.
+    used: usize,
+}
+
+/// Slab cache usage statistics.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SlabStats {
+    /// Number of slabs.
+    pub slabs: usize,
+    /// Number of allocated objects.
+    pub used: usize,
+    /// Total number of allocations.
+    pub allocs: usize,
+    /// Total number of frees.
+    pub frees: usize,
+    /// Number of objects found modified after free.
+    pub corrupted: usize,
+}
+
+/// Doubly linked list of slabs.
+struct SlabList {
+    /// First slab of the list.
+    head: *mut Slab,
+}
+
+impl SlabList {
+    /// Empty list.
+    const EMPTY: Self = Self {
+        head: ptr::null_mut(),
+    };
+
+    /// Insert slab at the head of the list.
+    ///
+    /// # Parameters
+    /// - `slab` - given slab to insert.
+    fn push(&mut self, slab: *mut Slab) {
+        unsafe {
+            (*slab).prev = ptr::null_mut();
+            (*slab).next = self.head;
+
+            if !self.head.is_null() {
+                (*self.head).prev = slab;
+            }
+        }
+
+        self.head = slab;
+    }
+
+    /// Remove slab from the list.
+    ///
+    /// # Parameters
+    /// - `slab` - given slab to remove.
+    fn remove(&mut self, slab: *mut Slab) {
+        unsafe {
+            let (next, prev) = ((*slab).next, (*slab).prev);
+
+            if prev.is_null() {
+                self.head = next;
+            } else {
+                (*prev).next = next;
+            }
+
+            if !next.is_null() {
+                (*next).prev = prev;
+            }
+        }
+    }
+}
+
+/// Mutable part of the slab cache.
+struct CacheState {
+    /// Slabs with free objects.
+    partial: SlabList,
+    /// Slabs without free objects.
+    full: SlabList,
+    /// Usage statistics.
+    stats: SlabStats,
+}
+
+// Slabs are only accessed under cache lock.
+unsafe impl Send for CacheState {}
+
+/// Slab cache of `T` objects.
+pub struct SlabCache<T> {
+    /// Cache name.
+    name: &'static str,
+    /// Optional object constructor.
+    ctor: Option<Constructor<T>>,
+    /// Whether to poison freed objects.
+    poison: bool,
+    /// Offset of the link word inside the slot.
+    link_offset: usize,
+    /// Size of the object slot in bytes.
+    slot_size: usize,
+    /// Offset of the first slot inside the slab.
+    first_offset: usize,
+    /// Number of frames in slab.
+    slab_pages: usize,
+    /// Number of objects in slab.
+    objects: usize,
+    /// Cache slabs & statistics.
+    state: Mutex<CacheState>,
+    /// Cache does not own `T` values.
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> SlabCache<T> {
+    /// Construct new slab cache.
+    ///
+    /// # Parameters
+    /// - `name` - given cache name.
+    /// - `ctor` - given optional object constructor.
+    ///
+    /// # Returns
+    /// - New `SlabCache` object without slabs.
+    pub const fn new(name: &'static str, ctor: Option<Constructor<T>>) -> Self {
+        let word = size_of::<usize>();
+        let align = if align_of::<T>() > word {
+            align_of::<T>()
+        } else {
+            word
+        };
+
+        let link_offset = size_of::<T>().next_multiple_of(word);
+        let slot_size = (link_offset + word).next_multiple_of(align);
+        let first_offset = size_of::<Slab>().next_multiple_of(align);
+
+        // Grow slab until it holds enough objects.
+        let page_size = PAGE_SIZE as usize;
+        let mut slab_pages = 1;
+
+        while slab_pages < MAX_SLAB_PAGES
+            && (slab_pages * page_size - first_offset) / slot_size < MIN_OBJECTS
+        {
+            slab_pages *= 2;
+        }
+
+        let objects = (slab_pages * page_size - first_offset) / slot_size;
+        assert!(objects > 0, "Object does not fit into slab");
+
+        Self {
+            name,
+            ctor,
+            poison: false,
+            link_offset,
+            slot_size,
+            first_offset,
+            slab_pages,
+            objects,
+            state: Mutex::new(CacheState {
+                partial: SlabList::EMPTY,
+                full: SlabList::EMPTY,
+                stats: SlabStats {
+                    slabs: 0,
+                    used: 0,
+                    allocs: 0,
+                    frees: 0,
+                    corrupted: 0,
+                },
+            }),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Enable poisoning of freed objects regardless of `slab_debug`.
+    ///
+    /// # Returns
+    /// - Slab cache in debug mode.
+    pub const fn with_poison(mut self) -> Self {
+        self.poison = true;
+        self
+    }
+
+    /// Check whether freed objects are poisoned.
+    ///
+    /// # Returns
+    /// - `true`  - if cache is in debug mode.
+    /// - `false` - otherwise.
+    #[inline(always)]
+    fn is_debug(&self) -> bool {
+        self.poison || SLAB_DEBUG.load(Ordering::Relaxed)
+    }
+
+    /// Get slab size in bytes.
+    ///
+    /// # Returns
+    /// - Slab size in bytes.
+    #[inline(always)]
+    fn slab_size(&self) -> usize {
+        self.slab_pages * PAGE_SIZE as usize
+    }
+
+    /// Get link word of object slot.
+    ///
+    /// # Parameters
+    /// - `obj` - given object address.
+    ///
+    /// # Returns
+    /// - Pointer to the link word.
+    #[inline(always)]
+    fn link(&self, obj: usize) -> *mut usize {
+        (obj + self.link_offset) as *mut usize
+    }
+
+    /// Fill object with poison pattern.
+    ///
+    /// # Parameters
+    /// - `obj` - given object address.
+    fn poison(&self, obj: usize) {
+        unsafe {
+            ptr::write_bytes(obj as *mut u8, POISON, size_of::<T>());
+        }
+    }
+
+    /// Check whether object poison pattern is intact.
+    ///
+    /// # Parameters
+    /// - `obj` - given object address.
+    ///
+    /// # Returns
+    /// - `true`  - if object was not modified after free.
+    /// - `false` - otherwise.
+    fn is_poisoned(&self, obj: usize) -> bool {
+        let bytes = obj as *const u8;
+        (0..size_of::<T>()).all(|i| unsafe { *bytes.add(i) } == POISON)
+    }
+
+    /// Allocate & carve new slab.
+    ///
+    /// # Returns
+    /// - New slab - in case of success.
+    /// - `None`   - otherwise.
+    fn new_slab(&self) -> Option<*mut Slab> {
+        let paddr = frame::alloc_direct(self.slab_pages, self.slab_pages)?;
+        let base = phys_to_virt(paddr);
+        let debug = self.is_debug();
+        let mut free = 0;
+
+        // Link slots in reverse, so that lower addresses are used first.
+        for i in (0..self.objects).rev() {
+            let obj = base + self.first_offset + i * self.slot_size;
+
+            if debug {
+                self.poison(obj);
+            } else if let Some(ctor) = self.ctor {
+                ctor(obj as *mut T);
+            }
+
+            unsafe { self.link(obj).write(free) };
+            free = obj;
+        }
+
+        let slab = base as *mut Slab;
+
+        unsafe {
+            slab.write(Slab {
+                next: ptr::null_mut(),
+                prev: ptr::null_mut(),
+                free,
+                used: 0,
+            });
+        }
+
+        Some(slab)
+    }
+
+    /// Allocate object.
+    ///
+    /// # Returns
+    /// - Pointer to constructed object - in case of success.
+    /// - `None`                        - otherwise.
+    pub fn alloc(&self) -> Option<NonNull<T>> {
+        let mut state = self.state.lock();
+
+        if state.partial.head.is_null() {
+            let slab = self.new_slab()?;
+            state.partial.push(slab);
+            state.stats.slabs += 1;
+        }
+
+        let slab = state.partial.head;
+        let obj = unsafe {
+            let obj = (*slab).free;
+            (*slab).free = self.link(obj).read();
+            (*slab).used += 1;
+
+            if (*slab).free == 0 {
+                state.partial.remove(slab);
+                state.full.push(slab);
+            }
+
+            obj
+        };
+
+        state.stats.used += 1;
+        state.stats.allocs += 1;
+
+        if self.is_debug() {
+            if !self.is_poisoned(obj) {
+                state.stats.corrupted += 1;
+                log::fail!(
+                    "Slab <{}>: object <{:#X}> was modified after free",
+                    self.name,
+                    obj
+                );
+            }
+
+            if let Some(ctor) = self.ctor {
+                ctor(obj as *mut T);
+            }
+        }
+
+        NonNull::new(obj as *mut T)
+    }
+
+    /// Free object.
+    ///
+    /// # Parameters
+    /// - `obj` - given object allocated from this cache.
+    ///
+    /// # Safety
+    /// - Object must not be used after free.
+    /// - Object must be in constructed state if cache has constructor.
+    pub unsafe fn free(&self, obj: NonNull<T>) {
+        let obj = obj.as_ptr() as usize;
+        let slab_size = self.slab_size();
+        let base = obj & !(slab_size - 1);
+        let offset = obj - base;
+
+        if offset < self.first_offset
+            || !(offset - self.first_offset).is_multiple_of(self.slot_size)
+            || (offset - self.first_offset) / self.slot_size >= self.objects
+        {
+            log::fail!("Slab <{}>: invalid free of <{:#X}>", self.name, obj);
+            return;
+        }
+
+        if self.is_debug() {
+            self.poison(obj);
+        }
+
+        let mut state = self.state.lock();
+        let slab = base as *mut Slab;
+
+        unsafe {
+            if (*slab).free == 0 {
+                state.full.remove(slab);
+                state.partial.push(slab);
+            }
+
+            self.link(obj).write((*slab).free);
+            (*slab).free = obj;
+            (*slab).used -= 1;
+
+            // Return empty slab unless it is the last one with free objects.
+            let last = state.partial.head == slab && (*slab).next.is_null();
+
+            if (*slab).used == 0 && !last {
+                state.partial.remove(slab);
+                state.stats.slabs -= 1;
+                frame::free_direct(virt_to_phys(base), self.slab_pages);
+            }
+        }
+
+        state.stats.used -= 1;
+        state.stats.frees += 1;
+    }
+
+    /// Get cache usage statistics.
+    ///
+    /// # Returns
+    /// - Cache usage statistics.
+    pub fn stats(&self) -> SlabStats {
+        self.state.lock().stats
+    }
+
+    /// Print cache statistics for debug.
+    pub fn display(&self) {
+        let stats = self.stats();
+
+        log::debug!(
+            "Slab <{}>: {} B objects, {} per slab, {} slabs, {} used",
+            self.name,
+            self.slot_size,
+            self.objects,
+            stats.slabs,
+            stats.used
+        );
+    }
+}
+
+#[cfg(feature = "ktest")]
+pub mod tests {
+    use super::*;
+    use crate::ktest::TestCase;
+
+    /// Slab allocator unit tests.
+    pub const TESTS: &[TestCase] = &[
+        TestCase::new("objects_are_constructed", objects_are_constructed),
+        TestCase::new("slabs_grow_and_shrink", slabs_grow_and_shrink),
+        TestCase::new("poison_detects_use_after_free", poison_detects_uaf),
+    ];
+
+    /// Test object magic value.
+    const MAGIC: u32 = 0xCAFEBABE;
+
+    /// Test object.
+    struct Object {
+        magic: u32,
+        data: [u8; 60],
+    }
+
+    /// Test object constructor.
+    fn construct(obj: *mut Object) {
+        unsafe {
+            obj.write(Object {
+                magic: MAGIC,
+                data: [0; 60],
+            });
+        }
+    }
+
+    static CACHE: SlabCache<Object> = SlabCache::new("test", Some(construct));
+
+    static DEBUG_CACHE: SlabCache<Object> =
+        SlabCache::new("test_debug", Some(construct)).with_poison();
+
+    fn objects_are_constructed() {
+        let a = CACHE.alloc().unwrap();
+        let b = CACHE.alloc().unwrap();
+
+        assert_ne!(a, b);
+        assert!((a.as_ptr() as usize).is_multiple_of(align_of::<Object>()));
+        assert_eq!(unsafe { a.as_ref().magic }, MAGIC);
+        assert_eq!(unsafe { b.as_ref().data }, [0; 60]);
+        assert_eq!(CACHE.stats().used, 2);
+
+        unsafe {
+            CACHE.free(a);
+            CACHE.free(b);
+        }
+
+        assert_eq!(CACHE.stats().used, 0);
+    }
+
+    fn slabs_grow_and_shrink() {
+        let count = CACHE.objects * 3;
+        let mut objects = [None; 256];
+        assert!(count <= objects.len());
+
+        for obj in objects.iter_mut().take(count) {
+            *obj = CACHE.alloc();
+        }
+
+        assert!(CACHE.stats().slabs >= 3);
+
+        for obj in objects.iter().take(count) {
+            unsafe { CACHE.free(obj.unwrap()) };
+        }
+
+        let stats = CACHE.stats();
+        assert_eq!(stats.used, 0);
+        assert_eq!(stats.slabs, 1);
+        CACHE.display();
+    }
+
+    fn poison_detects_uaf() {
+        let obj = DEBUG_CACHE.alloc().unwrap();
+        assert_eq!(unsafe { obj.as_ref().magic }, MAGIC);
+        unsafe { DEBUG_CACHE.free(obj) };
+
+        // Deliberately write into freed object.
+        unsafe { (*obj.as_ptr()).data[0] = 1 };
+
+        let again = DEBUG_CACHE.alloc().unwrap();
+        assert_eq!(again, obj);
+        assert_eq!(DEBUG_CACHE.stats().corrupted, 1);
+        assert_eq!(unsafe { again.as_ref().magic }, MAGIC);
+        unsafe { DEBUG_CACHE.free(again) };
+    }
+}
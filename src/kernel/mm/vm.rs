@@ -0,0 +1,1183 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Date: 2025-06-13
+// Author: Alexander Kuzin <alkuzindev@gmail.com>.
+
+//! Virtual address spaces.
+//!
+//! # Description
+//! `AddressSpace` owns page directory and address ordered set of virtual
+//! regions of its user part. Each region has its own protection, flags and
+//! backing memory. Kernel part of the address space (above kernel base) is
+//! shared by all address spaces and is never described by regions.
+//!
+//! Anonymous memory is allocated on demand: its pages are mapped by page
+//! fault handler on first access. Anonymous pages of forked address space
+//! are shared read-only & copied on first write (copy-on-write).
+
+use super::{
+    DIRECT_MAP_LIMIT, KERNEL_BASE, PAGE_SIZE, PhysAddr, frame, with_direct,
+};
+use crate::hal::mmu::{
+    self, MapError, MapFlags, PageDirectory, PageDirectoryInterface, PageFault,
+};
+use alloc::{collections::BTreeMap, sync::Arc, vec::Vec};
+use core::{fmt, ops::BitOr, ptr};
+use spin::Mutex;
+
+/// Page size in bytes.
+const PAGE: usize = PAGE_SIZE as usize;
+
+/// End of user part of the address space (exclusive).
+pub const USER_END: usize = KERNEL_BASE;
+
+/// Region memory protection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Prot(u32);
+
+impl Prot {
+    /// Memory can be read.
+    pub const READ: Self = Self(1 << 0);
+    /// Memory can be written.
+    pub const WRITE: Self = Self(1 << 1);
+    /// Memory can be executed.
+    pub const EXEC: Self = Self(1 << 2);
+
+    /// Check whether all given protection bits are set.
+    ///
+    /// # Parameters
+    /// - `other` - given protection bits to check.
+    ///
+    /// # Returns
+    /// - `true`  - if all bits of `other` are set.
+    /// - `false` - otherwise.
+    #[inline(always)]
+    pub const fn contains(self, other: Self) -> bool {
+        (self.0 & other.0) == other.0
+    }
+}
+
+impl BitOr for Prot {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+/// Region flags.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RegionFlags(u32);
+
+impl RegionFlags {
+    /// No flags.
+    pub const NONE: Self = Self(0);
+    /// Region is accessible from user mode.
+    pub const USER: Self = Self(1 << 0);
+    /// Region caching is disabled (for device memory).
+    pub const NO_CACHE: Self = Self(1 << 1);
+    /// Anonymous pages are allocated at map time instead of on demand.
+    pub const POPULATE: Self = Self(1 << 2);
+
+    /// Check whether all given flags are set.
+    ///
+    /// # Parameters
+    /// - `other` - given flags to check.
+    ///
+    /// # Returns
+    /// - `true`  - if all flags of `other` are set.
+    /// - `false` - otherwise.
+    #[inline(always)]
+    pub const fn contains(self, other: Self) -> bool {
+        (self.0 & other.0) == other.0
+    }
+}
+
+impl BitOr for RegionFlags {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+/// Physical memory shared between address spaces.
+#[derive(Debug)]
+pub struct SharedMemory {
+    /// Shared physical frames.
+    frames: Vec<PhysAddr>,
+}
+
+impl SharedMemory {
+    /// Allocate zeroed shared memory.
+    ///
+    /// # Parameters
+    /// - `size` - given memory size in bytes (rounded up to pages).
+    ///
+    /// # Returns
+    /// - New shared memory object - in case of success.
+    /// - `None`                   - otherwise.
+    pub fn new(size: usize) -> Option<Arc<Self>> {
+        let mut memory = Self { frames: Vec::new() };
+
+        for _ in 0..size.div_ceil(PAGE) {
+            memory.frames.push(alloc_zeroed_frame()?);
+        }
+
+        Some(Arc::new(memory))
+    }
+
+    /// Get shared memory size.
+    ///
+    /// # Returns
+    /// - Shared memory size in bytes.
+    #[inline(always)]
+    pub fn size(&self) -> usize {
+        self.frames.len() * PAGE
+    }
+}
+
+impl Drop for SharedMemory {
+    /// Free shared frames once the last region using them is unmapped.
+    fn drop(&mut self) {
+        for &paddr in &self.frames {
+            frame::free_frame(paddr);
+        }
+    }
+}
+
+/// Region backing memory.
+#[derive(Debug, Clone)]
+pub enum Backing {
+    /// Zero filled private memory.
+    Anonymous,
+    /// Fixed physical memory (device memory), never freed by address space.
+    Physical(PhysAddr),
+    /// Memory shared between address spaces.
+    Shared {
+        /// Shared memory object.
+        memory: Arc<SharedMemory>,
+        /// Offset of the region start inside shared memory (in pages).
+        offset: usize,
+    },
+}
+
+/// Virtual memory region.
+#[derive(Debug, Clone)]
+pub struct Region {
+    /// Region start virtual address.
+    pub start: usize,
+    /// Region end virtual address (exclusive).
+    pub end: usize,
+    /// Region memory protection.
+    pub prot: Prot,
+    /// Region flags.
+    pub flags: RegionFlags,
+    /// Region backing memory.
+    pub backing: Backing,
+}
+
+impl Region {
+    /// Get page mapping flags of the region.
+    ///
+    /// # Returns
+    /// - Page mapping flags.
+    fn map_flags(&self) -> MapFlags {
+        self.page_flags(self.prot.contains(Prot::WRITE))
+    }
+
+    /// Get page mapping flags of copy-on-write pages of the region.
+    ///
+    /// # Returns
+    /// - Read-only page mapping flags.
+    fn cow_flags(&self) -> MapFlags {
+        self.page_flags(false)
+    }
+
+    /// Get page mapping flags of the region.
+    ///
+    /// # Parameters
+    /// - `writable` - given whether pages are writable.
+    ///
+    /// # Returns
+    /// - Page mapping flags.
+    fn page_flags(&self, writable: bool) -> MapFlags {
+        let mut flags = MapFlags::READ;
+
+        if writable {
+            flags = flags | MapFlags::WRITABLE;
+        }
+
+        if self.prot.contains(Prot::EXEC) {
+            flags = flags | MapFlags::EXECUTABLE;
+        }
+
+        if self.flags.contains(RegionFlags::USER) {
+            flags = flags | MapFlags::USER;
+        }
+
+        if self.flags.contains(RegionFlags::NO_CACHE) {
+            flags = flags | MapFlags::NO_CACHE;
+        }
+
+        flags
+    }
+
+    /// Split region in two at given address.
+    ///
+    /// # Parameters
+    /// - `addr` - given page aligned address inside the region.
+    ///
+    /// # Returns
+    /// - Upper part of the region (this region becomes lower part).
+    fn split_off(&mut self, addr: usize) -> Self {
+        let offset = addr - self.start;
+        let backing = match &self.backing {
+            Backing::Anonymous => Backing::Anonymous,
+            Backing::Physical(paddr) => {
+                Backing::Physical(paddr + offset as PhysAddr)
+            }
+            Backing::Shared {
+                memory,
+                offset: pages,
+            } => Backing::Shared {
+                memory: memory.clone(),
+                offset: pages + offset / PAGE,
+            },
+        };
+
+        let upper = Self {
+            start: addr,
+            backing,
+            ..self.clone()
+        };
+
+        self.end = addr;
+        upper
+    }
+
+    /// Check whether region is backed by anonymous memory.
+    ///
+    /// # Returns
+    /// - `true`  - if region is anonymous.
+    /// - `false` - otherwise.
+    #[inline(always)]
+    fn is_anonymous(&self) -> bool {
+        matches!(self.backing, Backing::Anonymous)
+    }
+}
+
+/// Virtual memory errors enumeration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VmError {
+    /// Address or size is not page aligned.
+    Unaligned,
+    /// Range is empty or is not in user part of the address space.
+    InvalidRange,
+    /// Range overlaps existing region.
+    Overlap,
+    /// Range is not covered by regions.
+    NotMapped,
+    /// Backing memory is smaller than the region.
+    BackingTooSmall,
+    /// No memory for frames or paging structures.
+    NoMemory,
+    /// Backing physical address is not addressable by paging structures.
+    OutOfRange,
+}
+
+impl fmt::Display for VmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            Self::Unaligned => "address is not page aligned",
+            Self::InvalidRange => "invalid address range",
+            Self::Overlap => "range overlaps existing region",
+            Self::NotMapped => "range is not mapped",
+            Self::BackingTooSmall => "backing memory is too small",
+            Self::NoMemory => "out of memory",
+            Self::OutOfRange => "physical address is out of range",
+        };
+
+        f.write_str(msg)
+    }
+}
+
+impl From<MapError> for VmError {
+    fn from(err: MapError) -> Self {
+        match err {
+            MapError::Unaligned => Self::Unaligned,
+            MapError::AlreadyMapped => Self::Overlap,
+            MapError::NoMemory => Self::NoMemory,
+            MapError::OutOfRange => Self::OutOfRange,
+        }
+    }
+}
+
+/// Allocate zeroed directly accessible physical frame. Frame is zeroed
+/// through temporary direct mapping.
+///
+/// # Returns
+/// - Physical address of the frame - in case of success.
+/// - `None`                        - otherwise.
+fn alloc_zeroed_frame() -> Option<PhysAddr> {
+    let paddr = frame::alloc_frames_below(1, 1, DIRECT_MAP_LIMIT)?;
+    let zeroed = with_direct(paddr, |vaddr| unsafe {
+        ptr::write_bytes(vaddr as *mut u8, 0, PAGE);
+    });
+
+    if zeroed.is_none() {
+        frame::free_frame(paddr);
+    }
+
+    zeroed.map(|_| paddr)
+}
+
+/// Copy contents of directly accessible physical frame to another one.
+///
+/// # Parameters
+/// - `src` - given physical address of the source frame.
+/// - `dst` - given physical address of the destination frame.
+///
+/// # Returns
+/// - `Some` - in case of success.
+/// - `None` - if frames can not be mapped.
+fn copy_frame(src: PhysAddr, dst: PhysAddr) -> Option<()> {
+    with_direct(src, |from| {
+        with_direct(dst, |to| unsafe {
+            ptr::copy_nonoverlapping(from as *const u8, to as *mut u8, PAGE);
+        })
+    })?
+}
+
+/// Number of extra references to anonymous frames shared by forked address
+/// spaces. Frames missing here have single owner.
+static SHARED_FRAMES: Mutex<BTreeMap<PhysAddr, usize>> =
+    Mutex::new(BTreeMap::new());
+
+/// Add reference to anonymous frame.
+///
+/// # Parameters
+/// - `paddr` - given physical address of the frame.
+fn share_frame(paddr: PhysAddr) {
+    *SHARED_FRAMES.lock().entry(paddr).or_insert(0) += 1;
+}
+
+/// Check whether anonymous frame is shared by several address spaces.
+///
+/// # Parameters
+/// - `paddr` - given physical address of the frame.
+///
+/// # Returns
+/// - `true`  - if frame is shared.
+/// - `false` - otherwise.
+fn is_shared(paddr: PhysAddr) -> bool {
+    SHARED_FRAMES.lock().contains_key(&paddr)
+}
+
+/// Drop reference to anonymous frame & free it if it was the last one.
+///
+/// # Parameters
+/// - `paddr` - given physical address of the frame.
+fn release_frame(paddr: PhysAddr) {
+    let mut shared = SHARED_FRAMES.lock();
+
+    match shared.get_mut(&paddr) {
+        Some(1) => {
+            shared.remove(&paddr);
+        }
+        Some(refs) => *refs -= 1,
+        None => frame::free_frame(paddr),
+    }
+}
+
+/// Validate virtual range of user part of the address space.
+///
+/// # Parameters
+/// - `start` - given range start address.
+/// - `size`  - given range size in bytes.
+///
+/// # Returns
+/// - Range end address - in case of success.
+/// - `Err`             - otherwise.
+fn check_range(start: usize, size: usize) -> Result<usize, VmError> {
+    if (start | size) & (PAGE - 1) != 0 {
+        return Err(VmError::Unaligned);
+    }
+
+    match start.checked_add(size) {
+        Some(end) if size != 0 && end <= USER_END => Ok(end),
+        _ => Err(VmError::InvalidRange),
+    }
+}
+
+/// Isolated virtual address space.
+pub struct AddressSpace {
+    /// Page directory of the address space.
+    directory: PageDirectory,
+    /// Regions of user part ordered by their start address.
+    regions: BTreeMap<usize, Region>,
+}
+
+impl AddressSpace {
+    /// Construct new address space with empty user part.
+    ///
+    /// # Returns
+    /// - New `AddressSpace` object - in case of success.
+    /// - `None`                    - otherwise.
+    pub fn new() -> Option<Self> {
+        Some(Self {
+            directory: PageDirectory::new()?,
+            regions: BTreeMap::new(),
+        })
+    }
+
+    /// Get regions of the address space.
+    ///
+    /// # Returns
+    /// - Iterator over regions ordered by their start address.
+    pub fn regions(&self) -> impl Iterator<Item = &Region> {
+        self.regions.values()
+    }
+
+    /// Find region containing virtual address.
+    ///
+    /// # Parameters
+    /// - `vaddr` - given virtual address.
+    ///
+    /// # Returns
+    /// - Region containing the address - in case of success.
+    /// - `None`                        - otherwise.
+    pub fn find_region(&self, vaddr: usize) -> Option<&Region> {
+        self.regions
+            .range(..=vaddr)
+            .next_back()
+            .map(|(_, region)| region)
+            .filter(|region| vaddr < region.end)
+    }
+
+    /// Check whether user mode is allowed to access range.
+    ///
+    /// # Parameters
+    /// - `start` - given range start address.
+    /// - `size`  - given range size.
+    /// - `prot`  - given required memory protection.
+    ///
+    /// # Returns
+    /// - `true`  - if range is covered by user regions allowing access.
+    /// - `false` - otherwise.
+    pub fn is_user_accessible(
+        &self,
+        start: usize,
+        size: usize,
+        prot: Prot,
+    ) -> bool {
+        let Some(end) = start.checked_add(size) else {
+            return false;
+        };
+
+        if end > USER_END {
+            return false;
+        }
+
+        let mut addr = start;
+
+        while addr < end {
+            let Some(region) = self.find_region(addr) else {
+                return false;
+            };
+
+            if !region.prot.contains(prot)
+                || !region.flags.contains(RegionFlags::USER)
+            {
+                return false;
+            }
+
+            addr = region.end;
+        }
+
+        true
+    }
+
+    /// Check whether range overlaps any region.
+    ///
+    /// # Parameters
+    /// - `start` - given range start address.
+    /// - `end`   - given range end address (exclusive).
+    ///
+    /// # Returns
+    /// - `true`  - if range overlaps some region.
+    /// - `false` - otherwise.
+    fn overlaps(&self, start: usize, end: usize) -> bool {
+        self.find_region(start).is_some()
+            || self.regions.range(start..end).next().is_some()
+    }
+
+    /// Split region containing address, so that address becomes boundary.
+    ///
+    /// # Parameters
+    /// - `addr` - given page aligned virtual address.
+    fn split_at(&mut self, addr: usize) {
+        let key = match self.find_region(addr) {
+            Some(region) if region.start != addr => region.start,
+            _ => return,
+        };
+
+        if let Some(region) = self.regions.get_mut(&key) {
+            let upper = region.split_off(addr);
+            self.regions.insert(addr, upper);
+        }
+    }
+
+    /// Invalidate TLB entry if address space is active.
+    ///
+    /// # Parameters
+    /// - `vaddr` - given virtual address of the page.
+    fn flush(&self, vaddr: usize) {
+        if self.directory.is_active() {
+            mmu::flush_tlb(vaddr);
+        }
+    }
+
+    /// Map pages of region.
+    ///
+    /// # Parameters
+    /// - `region` - given region to map.
+    ///
+    /// # Returns
+    /// - `Ok`  - in case of success.
+    /// - `Err` - otherwise.
+    fn map_pages(&mut self, region: &Region) -> Result<(), VmError> {
+        let flags = region.map_flags();
+
+        let populate = region.flags.contains(RegionFlags::POPULATE);
+
+        for vaddr in (region.start..region.end).step_by(PAGE) {
+            let offset = vaddr - region.start;
+            let paddr = match &region.backing {
+                // Mapped on first access by page fault handler.
+                Backing::Anonymous if !populate => return Ok(()),
+                Backing::Anonymous => {
+                    alloc_zeroed_frame().ok_or(VmError::NoMemory)?
+                }
+                Backing::Physical(paddr) => paddr + offset as PhysAddr,
+                Backing::Shared {
+                    memory,
+                    offset: pages,
+                } => memory.frames[pages + offset / PAGE],
+            };
+
+            if let Err(err) = self.directory.map(vaddr, paddr, flags) {
+                if let Backing::Anonymous = region.backing {
+                    frame::free_frame(paddr);
+                }
+
+                return Err(err.into());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Unmap pages of region & free its private frames.
+    ///
+    /// # Parameters
+    /// - `region` - given region to unmap.
+    fn unmap_pages(&mut self, region: &Region) {
+        for vaddr in (region.start..region.end).step_by(PAGE) {
+            let Some(paddr) = self.directory.unmap(vaddr) else {
+                continue;
+            };
+
+            self.flush(vaddr);
+
+            if region.is_anonymous() {
+                release_frame(paddr);
+            }
+        }
+    }
+
+    /// Map new region.
+    ///
+    /// # Parameters
+    /// - `start`   - given page aligned region start address.
+    /// - `size`    - given page aligned region size in bytes.
+    /// - `prot`    - given region memory protection.
+    /// - `flags`   - given region flags.
+    /// - `backing` - given region backing memory.
+    ///
+    /// # Returns
+    /// - `Ok`  - in case of success.
+    /// - `Err` - otherwise.
+    pub fn map(
+        &mut self,
+        start: usize,
+        size: usize,
+        prot: Prot,
+        flags: RegionFlags,
+        backing: Backing,
+    ) -> Result<(), VmError> {
+        let end = check_range(start, size)?;
+
+        if self.overlaps(start, end) {
+            return Err(VmError::Overlap);
+        }
+
+        match &backing {
+            Backing::Physical(paddr) if paddr & (PAGE_SIZE - 1) != 0 => {
+                return Err(VmError::Unaligned);
+            }
+            Backing::Shared { memory, offset }
+                if offset * PAGE + size > memory.size() =>
+            {
+                return Err(VmError::BackingTooSmall);
+            }
+            _ => {}
+        }
+
+        let region = Region {
+            start,
+            end,
+            prot,
+            flags,
+            backing,
+        };
+
+        if let Err(err) = self.map_pages(&region) {
+            self.unmap_pages(&region);
+            return Err(err);
+        }
+
+        self.regions.insert(start, region);
+        Ok(())
+    }
+
+    /// Unmap virtual range. Regions partially covered by the range are
+    /// split & only covered parts are unmapped.
+    ///
+    /// # Parameters
+    /// - `start` - given page aligned range start address.
+    /// - `size`  - given page aligned range size in bytes.
+    ///
+    /// # Returns
+    /// - `Ok`  - in case of success.
+    /// - `Err` - otherwise.
+    pub fn unmap(&mut self, start: usize, size: usize) -> Result<(), VmError> {
+        let end = check_range(start, size)?;
+
+        if !self.overlaps(start, end) {
+            return Err(VmError::NotMapped);
+        }
+
+        self.split_at(start);
+        self.split_at(end);
+
+        let keys: Vec<usize> = self
+            .regions
+            .range(start..end)
+            .map(|(&key, _)| key)
+            .collect();
+
+        for key in keys {
+            if let Some(region) = self.regions.remove(&key) {
+                self.unmap_pages(&region);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Change memory protection of virtual range. Range must be fully
+    /// covered by regions.
+    ///
+    /// # Parameters
+    /// - `start` - given page aligned range start address.
+    /// - `size`  - given page aligned range size in bytes.
+    /// - `prot`  - given new memory protection.
+    ///
+    /// # Returns
+    /// - `Ok`  - in case of success.
+    /// - `Err` - otherwise.
+    pub fn protect(
+        &mut self,
+        start: usize,
+        size: usize,
+        prot: Prot,
+    ) -> Result<(), VmError> {
+        let end = check_range(start, size)?;
+
+        // Check that there are no holes in the range.
+        let mut addr = start;
+
+        while addr < end {
+            addr = self.find_region(addr).ok_or(VmError::NotMapped)?.end;
+        }
+
+        self.split_at(start);
+        self.split_at(end);
+
+        let keys: Vec<usize> = self
+            .regions
+            .range(start..end)
+            .map(|(&key, _)| key)
+            .collect();
+
+        for key in keys {
+            let Some(region) = self.regions.get_mut(&key) else {
+                continue;
+            };
+
+            region.prot = prot;
+            let region = region.clone();
+            let flags = region.map_flags();
+
+            for vaddr in (region.start..region.end).step_by(PAGE) {
+                let Some(paddr) = self.directory.translate(vaddr) else {
+                    continue;
+                };
+
+                // Shared pages must stay read-only to be copied on write.
+                let flags = if region.is_anonymous() && is_shared(paddr) {
+                    region.cow_flags()
+                } else {
+                    flags
+                };
+
+                self.directory.protect(vaddr, flags);
+                self.flush(vaddr);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Translate virtual address.
+    ///
+    /// # Parameters
+    /// - `vaddr` - given virtual address.
+    ///
+    /// # Returns
+    /// - Physical address - in case of success.
+    /// - `None`           - if address is not mapped.
+    pub fn translate(&self, vaddr: usize) -> Option<PhysAddr> {
+        self.directory.translate(vaddr)
+    }
+
+    /// Duplicate address space. Anonymous pages are shared by both address
+    /// spaces & copied on first write, other backings are mapped as is.
+    ///
+    /// # Returns
+    /// - New `AddressSpace` object - in case of success.
+    /// - `None`                    - otherwise.
+    pub fn fork(&mut self) -> Option<Self> {
+        let mut child = Self::new()?;
+        let regions: Vec<Region> = self.regions.values().cloned().collect();
+
+        for region in regions {
+            // Insert region first, so that failed child frees its pages.
+            child.regions.insert(region.start, region.clone());
+
+            let flags = if region.is_anonymous() {
+                region.cow_flags()
+            } else {
+                region.map_flags()
+            };
+
+            for vaddr in (region.start..region.end).step_by(PAGE) {
+                let Some(paddr) = self.directory.translate(vaddr) else {
+                    continue;
+                };
+
+                child.directory.map(vaddr, paddr, flags).ok()?;
+
+                if region.is_anonymous() {
+                    share_frame(paddr);
+                    self.directory.protect(vaddr, flags);
+                    self.flush(vaddr);
+                }
+            }
+        }
+
+        Some(child)
+    }
+
+    /// Resolve page fault inside region of the address space.
+    ///
+    /// # Parameters
+    /// - `fault` - given page fault description.
+    ///
+    /// # Returns
+    /// - `true`  - if fault was resolved.
+    /// - `false` - if fault is genuine access violation.
+    pub fn handle_fault(&mut self, fault: &PageFault) -> bool {
+        let Some(region) = self.find_region(fault.addr) else {
+            return false;
+        };
+
+        let allowed = if fault.exec {
+            region.prot.contains(Prot::EXEC)
+        } else if fault.write {
+            region.prot.contains(Prot::WRITE)
+        } else {
+            region.prot.contains(Prot::READ)
+        };
+
+        if !allowed
+            || !region.is_anonymous()
+            || (fault.user && !region.flags.contains(RegionFlags::USER))
+        {
+            return false;
+        }
+
+        let flags = region.map_flags();
+        let vaddr = fault.addr & !(PAGE - 1);
+
+        if !fault.present {
+            // Demand paging: first access to anonymous page.
+            let Some(paddr) = alloc_zeroed_frame() else {
+                return false;
+            };
+
+            if self.directory.map(vaddr, paddr, flags).is_err() {
+                frame::free_frame(paddr);
+                return false;
+            }
+        } else if fault.write {
+            let Some(paddr) = self.directory.translate(vaddr) else {
+                return false;
+            };
+
+            // Copy-on-write: the last owner takes the frame over.
+            if is_shared(paddr) {
+                let Some(copy) =
+                    frame::alloc_frames_below(1, 1, DIRECT_MAP_LIMIT)
+                else {
+                    return false;
+                };
+
+                if copy_frame(paddr, copy).is_none() {
+                    frame::free_frame(copy);
+                    return false;
+                }
+
+                self.directory.unmap(vaddr);
+
+                if self.directory.map(vaddr, copy, flags).is_err() {
+                    frame::free_frame(copy);
+                    return false;
+                }
+
+                release_frame(paddr);
+            } else {
+                self.directory.protect(vaddr, flags);
+            }
+        } else {
+            return false;
+        }
+
+        self.flush(vaddr);
+        true
+    }
+
+    /// Switch CPU to this address space. Page faults are resolved only in
+    /// address space made current by `switch_to`.
+    pub fn activate(&self) {
+        // Kernel part is shared, so the kernel keeps running after switch.
+        unsafe { self.directory.activate() };
+    }
+}
+
+impl Drop for AddressSpace {
+    /// Unmap all regions & free private memory of the address space.
+    fn drop(&mut self) {
+        let regions = core::mem::take(&mut self.regions);
+
+        for region in regions.values() {
+            self.unmap_pages(region);
+        }
+    }
+}
+
+/// Current address space.
+static CURRENT: Mutex<Option<Arc<Mutex<AddressSpace>>>> = Mutex::new(None);
+
+/// Switch CPU to address space & make it current.
+///
+/// # Parameters
+/// - `space` - given address space.
+pub fn switch_to(space: Arc<Mutex<AddressSpace>>) {
+    space.lock().activate();
+
+    // Previous address space may be dropped only after the switch.
+    *CURRENT.lock() = Some(space);
+}
+
+/// Switch CPU to kernel address space.
+pub fn switch_to_kernel() {
+    mmu::activate_kernel();
+    CURRENT.lock().take();
+}
+
+/// Check whether current address space allows user mode to access range.
+///
+/// # Parameters
+/// - `start` - given range start address.
+/// - `size`  - given range size.
+/// - `prot`  - given required memory protection.
+///
+/// # Returns
+/// - `true`  - if range is accessible.
+/// - `false` - otherwise (or if there is no current address space).
+pub fn is_user_accessible(start: usize, size: usize, prot: Prot) -> bool {
+    let Some(current) = CURRENT.lock().clone() else {
+        return false;
+    };
+
+    current.lock().is_user_accessible(start, size, prot)
+}
+
+/// Resolve page fault in current address space.
+///
+/// # Parameters
+/// - `fault` - given page fault description.
+///
+/// # Returns
+/// - `true`  - if fault was resolved.
+/// - `false` - if fault is genuine access violation.
+pub fn handle_page_fault(fault: &PageFault) -> bool {
+    if fault.addr >= USER_END {
+        return false;
+    }
+
+    // Fault while address space is locked can not be resolved.
+    let Some(current) =
+        CURRENT.try_lock().and_then(|current| (*current).clone())
+    else {
+        return false;
+    };
+
+    current
+        .try_lock()
+        .is_some_and(|mut space| space.handle_fault(fault))
+}
+
+#[cfg(feature = "ktest")]
+pub mod tests {
+    use super::*;
+    use crate::ktest::TestCase;
+
+    /// Virtual memory unit tests.
+    pub const TESTS: &[TestCase] = &[
+        TestCase::new("map_anonymous", map_anonymous),
+        TestCase::new("overlap_is_rejected", overlap_is_rejected),
+        TestCase::new("unmap_splits_region", unmap_splits_region),
+        TestCase::new("protect_splits_region", protect_splits_region),
+        TestCase::new("shared_memory", shared_memory),
+        TestCase::new("kernel_part_is_shared", kernel_part_is_shared),
+        TestCase::new("demand_paging", demand_paging),
+        TestCase::new("copy_on_write", copy_on_write),
+    ];
+
+    /// Base address of test regions.
+    const BASE: usize = 0x10000000;
+
+    /// Read-write user protection & flags.
+    const RW: Prot = Prot(Prot::READ.0 | Prot::WRITE.0);
+
+    fn map_anonymous() {
+        let free = frame::stats().free;
+        let mut space = AddressSpace::new().unwrap();
+        space
+            .map(
+                BASE,
+                4 * PAGE,
+                RW,
+                RegionFlags::USER | RegionFlags::POPULATE,
+                Backing::Anonymous,
+            )
+            .unwrap();
+
+        let paddr = space.translate(BASE + PAGE + 8).unwrap();
+        let value =
+            with_direct(paddr, |vaddr| unsafe { *(vaddr as *const u64) });
+
+        assert_eq!(value, Some(0));
+        assert_eq!(space.find_region(BASE + 3 * PAGE).unwrap().start, BASE);
+        assert!(space.find_region(BASE + 4 * PAGE).is_none());
+
+        drop(space);
+        assert_eq!(frame::stats().free, free);
+    }
+
+    fn overlap_is_rejected() {
+        let mut space = AddressSpace::new().unwrap();
+        let flags = RegionFlags::USER;
+
+        space
+            .map(BASE, 2 * PAGE, RW, flags, Backing::Anonymous)
+            .unwrap();
+
+        assert_eq!(
+            space.map(BASE + PAGE, PAGE, RW, flags, Backing::Anonymous),
+            Err(VmError::Overlap)
+        );
+        assert_eq!(
+            space.map(BASE - PAGE, 2 * PAGE, RW, flags, Backing::Anonymous),
+            Err(VmError::Overlap)
+        );
+        assert_eq!(
+            space.map(USER_END, PAGE, RW, flags, Backing::Anonymous),
+            Err(VmError::InvalidRange)
+        );
+        assert_eq!(
+            space.map(BASE + 1, PAGE, RW, flags, Backing::Anonymous),
+            Err(VmError::Unaligned)
+        );
+    }
+
+    fn unmap_splits_region() {
+        let mut space = AddressSpace::new().unwrap();
+        let flags = RegionFlags::USER | RegionFlags::POPULATE;
+
+        space
+            .map(BASE, 4 * PAGE, RW, flags, Backing::Anonymous)
+            .unwrap();
+        space.unmap(BASE + PAGE, 2 * PAGE).unwrap();
+
+        let regions: Vec<(usize, usize)> = space
+            .regions()
+            .map(|region| (region.start, region.end))
+            .collect();
+
+        assert_eq!(
+            regions,
+            [(BASE, BASE + PAGE), (BASE + 3 * PAGE, BASE + 4 * PAGE)]
+        );
+        assert!(space.translate(BASE + PAGE).is_none());
+        assert!(space.translate(BASE + 3 * PAGE).is_some());
+    }
+
+    fn protect_splits_region() {
+        let mut space = AddressSpace::new().unwrap();
+        let paddr = frame::alloc_frames(2, 1).unwrap();
+
+        space
+            .map(
+                BASE,
+                2 * PAGE,
+                RW,
+                RegionFlags::USER,
+                Backing::Physical(paddr),
+            )
+            .unwrap();
+        space.protect(BASE + PAGE, PAGE, Prot::READ).unwrap();
+
+        let upper = space.find_region(BASE + PAGE).unwrap();
+        assert_eq!(upper.prot, Prot::READ);
+        assert!(
+            matches!(upper.backing, Backing::Physical(p) if p == paddr + PAGE as u64)
+        );
+        assert_eq!(space.find_region(BASE).unwrap().prot, RW);
+        assert_eq!(
+            space.protect(BASE, 3 * PAGE, Prot::READ),
+            Err(VmError::NotMapped)
+        );
+
+        // Physical backing is not freed with the address space.
+        drop(space);
+        frame::free_frames(paddr, 2);
+    }
+
+    fn shared_memory() {
+        let memory = SharedMemory::new(2 * PAGE).unwrap();
+        let backing = Backing::Shared {
+            memory: memory.clone(),
+            offset: 0,
+        };
+
+        let mut a = AddressSpace::new().unwrap();
+        let mut b = AddressSpace::new().unwrap();
+        a.map(BASE, 2 * PAGE, RW, RegionFlags::USER, backing.clone())
+            .unwrap();
+        b.map(BASE + 8 * PAGE, 2 * PAGE, RW, RegionFlags::USER, backing)
+            .unwrap();
+
+        assert_eq!(a.translate(BASE), b.translate(BASE + 8 * PAGE));
+        assert_eq!(Arc::strong_count(&memory), 3);
+
+        drop(a);
+        drop(b);
+        assert_eq!(Arc::strong_count(&memory), 1);
+    }
+
+    fn kernel_part_is_shared() {
+        let space = AddressSpace::new().unwrap();
+        let vaddr = kernel_part_is_shared as *const () as usize;
+
+        assert_eq!(space.translate(vaddr), mmu::translate(vaddr));
+        assert!(space.translate(vaddr).is_some());
+    }
+
+    fn demand_paging() {
+        let free = frame::stats().free;
+        let space = Arc::new(Mutex::new(AddressSpace::new().unwrap()));
+        let ptr = (BASE + 5 * PAGE + 8) as *mut u32;
+
+        space
+            .lock()
+            .map(BASE, 1024 * PAGE, RW, RegionFlags::NONE, Backing::Anonymous)
+            .unwrap();
+        assert!(space.lock().translate(ptr as usize).is_none());
+
+        switch_to(space.clone());
+
+        unsafe {
+            assert_eq!(ptr.read_volatile(), 0);
+            ptr.write_volatile(0xDEADBEEF);
+            assert_eq!(ptr.read_volatile(), 0xDEADBEEF);
+        }
+
+        switch_to_kernel();
+
+        assert!(space.lock().translate(ptr as usize).is_some());
+        assert!(space.lock().translate(BASE).is_none());
+
+        drop(space);
+        assert_eq!(frame::stats().free, free);
+    }
+
+    fn copy_on_write() {
+        let free = frame::stats().free;
+        let parent = Arc::new(Mutex::new(AddressSpace::new().unwrap()));
+        let ptr = BASE as *mut u32;
+
+        parent
+            .lock()
+            .map(BASE, 2 * PAGE, RW, RegionFlags::NONE, Backing::Anonymous)
+            .unwrap();
+
+        switch_to(parent.clone());
+        unsafe { ptr.write_volatile(1) };
+
+        let child = parent.lock().fork().unwrap();
+        let child = Arc::new(Mutex::new(child));
+        assert_eq!(parent.lock().translate(BASE), child.lock().translate(BASE));
+
+        // Parent gets its own copy, child becomes the only owner.
+        unsafe { ptr.write_volatile(2) };
+        assert_ne!(parent.lock().translate(BASE), child.lock().translate(BASE));
+
+        switch_to(child.clone());
+
+        unsafe {
+            assert_eq!(ptr.read_volatile(), 1);
+            ptr.write_volatile(3);
+        }
+
+        switch_to(parent.clone());
+        unsafe { assert_eq!(ptr.read_volatile(), 2) };
+        switch_to_kernel();
+
+        drop(parent);
+        drop(child);
+        assert_eq!(frame::stats().free, free);
+    }
+}
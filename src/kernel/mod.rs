@@ -4,11 +4,31 @@
 
 //! Main kernel module. Responsible for initializing kernel components.
 
+pub mod cmdline;
 pub mod gfx;
 mod memlayout;
-
-use crate::{config, hal, log, multiboot::MultibootInfo, printk};
-use core::str;
+pub mod mm;
+pub mod syscall;
+pub mod time;
+
+use crate::{bootinfo::BootInfo, config, hal, log, printk};
+use cmdline::{Param, ParamKind, kernel_param};
+use core::{
+    str,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+/// Whether to disable framebuffer terminal.
+static NOTERM: AtomicBool = AtomicBool::new(false);
+
+kernel_param! {
+    /// Kernel parameter disabling framebuffer terminal (`noterm`).
+    static NOTERM_PARAM = Param {
+        name: "noterm",
+        description: "Disable framebuffer terminal",
+        kind: ParamKind::Flag(&NOTERM),
+    };
+}
 
 /// Display CPU related info.
 fn display_cpu_info() {
@@ -69,6 +89,27 @@ fn display_memory_layout() {
     );
     log::info!("Kernel stack top address:    <{:#010X}>", stack_top_address);
     log::info!("Stack size: {} bytes", stack_size);
+
+    // Print physical memory usage.
+    let frames = mm::frame::stats();
+    let frame_size = mm::PAGE_SIZE as usize;
+
+    log::info!(
+        "Physical frames: {} used, {} free, {} total ({} KiB free)",
+        frames.used(),
+        frames.free,
+        frames.total,
+        frames.free * frame_size / 1024
+    );
+
+    // Print kernel heap usage.
+    let heap = mm::heap::stats();
+
+    log::info!(
+        "Kernel heap: {} KiB mapped, {} bytes used",
+        heap.size / 1024,
+        heap.used
+    );
 }
 
 /// Display OS related info.
@@ -85,28 +126,52 @@ fn display_os_info() {
     printk!("Repository: {}", repository);
     printk!("Created by {}", authors);
     printk!("Running under {} license.\n{}", license, license_details);
+
+    if let Some(date) = time::wall_clock() {
+        printk!("Current date: {} UTC\n", date);
+    }
 }
 
 /// Initialize kernel.
 ///
 /// # Parameters
-/// - `boot_info` - given multiboot info structure.
-pub fn init(boot_info: &MultibootInfo) {
+/// - `boot_info` - given bootloader independent boot information.
+pub fn init(boot_info: &BootInfo) {
     log::init_serial_writer();
     log::success!("Initialized kernel serial logger");
 
-    let fb = gfx::init(boot_info);
-    log::success!("Initialized kernel graphics");
-
-    log::init_terminal_writer(fb.clone());
-    log::success!("Initialized kernel terminal logger");
+    cmdline::parse(boot_info.cmdline.as_str());
+    log::success!("Parsed kernel command line: {:?}", boot_info.cmdline);
 
     display_cpu_info();
     hal::init();
     log::success!("Initialized architecture-specific part of the kernel");
 
+    if time::init() {
+        log::success!("Initialized wall clock");
+    } else {
+        log::fail!("Invalid real-time clock value, wall clock is disabled");
+    }
+
+    // Framebuffer is mapped after switching to kernel page directory.
+    mm::init(boot_info);
+
+    // Interrupt controller registers are memory mapped as well.
+    hal::init_late();
+
+    let fb = gfx::init(boot_info);
+    log::success!("Initialized kernel graphics");
+
+    if !NOTERM.load(Ordering::Relaxed) {
+        log::init_terminal_writer(fb.clone());
+        log::success!("Initialized kernel terminal logger");
+    }
+
     display_memory_layout();
     log::success!("Finished setting up OS");
 
     display_os_info();
+
+    #[cfg(feature = "ktest")]
+    crate::ktest::run_all();
 }
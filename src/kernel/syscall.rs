@@ -0,0 +1,367 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Date: 2025-06-13
+// Author: Alexander Kuzin <alkuzindev@gmail.com>.
+
+//! System call interface.
+//!
+//! # Description
+//! User mode passes system call number & up to `MAX_ARGS` arguments in
+//! registers (see architecture-specific entry code). System call number
+//! indexes `SYSCALLS` table. Result is returned in single register as
+//! signed value: non-negative value means success, negative one is negated
+//! `SyscallError` code.
+
+use super::{
+    cmdline::{Param, ParamKind, kernel_param},
+    mm::vm::{self, Prot},
+    time,
+};
+use crate::{hal, log};
+use alloc::vec::Vec;
+use core::{
+    fmt, str,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+/// Maximum number of system call arguments.
+pub const MAX_ARGS: usize = 4;
+
+/// Write string to kernel log (`ptr`, `len`).
+pub const SYS_DEBUG_WRITE: u32 = 0;
+
+/// Get time since boot in nanoseconds (`*mut u64`).
+pub const SYS_UPTIME: u32 = 1;
+
+/// Get wall-clock time as seconds since Unix epoch (`*mut u64`).
+pub const SYS_WALL_CLOCK: u32 = 2;
+
+/// Finish user code & return exit status to the kernel (`status`).
+pub const SYS_EXIT: u32 = 3;
+
+/// Maximum length of string written by `SYS_DEBUG_WRITE`.
+const MAX_DEBUG_WRITE: usize = 1024;
+
+/// System call errors enumeration. Values are part of the ABI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(i32)]
+pub enum SyscallError {
+    /// System call number is unknown.
+    NoSys = 1,
+    /// Argument value is invalid.
+    Invalid = 2,
+    /// Memory pointed by argument is not accessible.
+    Fault = 3,
+    /// Requested resource is not available.
+    Unavailable = 4,
+}
+
+impl fmt::Display for SyscallError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            Self::NoSys => "System call is not implemented",
+            Self::Invalid => "Invalid argument",
+            Self::Fault => "Bad address",
+            Self::Unavailable => "Resource is not available",
+        };
+
+        f.write_str(msg)
+    }
+}
+
+/// System call result (success value must not exceed `i32::MAX`).
+pub type SyscallResult = Result<u32, SyscallError>;
+
+/// System call table entry.
+pub struct Syscall {
+    /// System call name (for tracing).
+    pub name: &'static str,
+    /// Number of used arguments (for tracing).
+    pub args: usize,
+    /// System call handler.
+    pub handler: fn(&[u32; MAX_ARGS]) -> SyscallResult,
+}
+
+/// System calls indexed by their numbers.
+static SYSCALLS: [Syscall; 4] = [
+    Syscall {
+        name: "debug_write",
+        args: 2,
+        handler: sys_debug_write,
+    },
+    Syscall {
+        name: "uptime",
+        args: 1,
+        handler: sys_uptime,
+    },
+    Syscall {
+        name: "wall_clock",
+        args: 1,
+        handler: sys_wall_clock,
+    },
+    Syscall {
+        name: "exit",
+        args: 1,
+        handler: sys_exit,
+    },
+];
+
+/// Whether to log every system call.
+static TRACE: AtomicBool = AtomicBool::new(false);
+
+kernel_param! {
+    /// Kernel parameter enabling system call tracing (`syscall_trace`).
+    static SYSCALL_TRACE_PARAM = Param {
+        name: "syscall_trace",
+        description: "Log every system call with its arguments & result",
+        kind: ParamKind::Flag(&TRACE),
+    };
+}
+
+/// Encode system call result as register value.
+///
+/// # Parameters
+/// - `result` - given system call result.
+///
+/// # Returns
+/// - Success value or negated error code.
+pub fn encode(result: SyscallResult) -> u32 {
+    match result {
+        Ok(value) => value,
+        Err(err) => (err as i32).wrapping_neg() as u32,
+    }
+}
+
+/// Check whether user memory may be accessed by the kernel on behalf of
+/// current address space.
+///
+/// # Parameters
+/// - `addr` - given user virtual address.
+/// - `size` - given memory size.
+/// - `prot` - given required memory protection.
+///
+/// # Returns
+/// - `Ok`                  - if memory is accessible.
+/// - `SyscallError::Fault` - otherwise.
+fn check_user(addr: u32, size: usize, prot: Prot) -> Result<(), SyscallError> {
+    if vm::is_user_accessible(addr as usize, size, prot) {
+        Ok(())
+    } else {
+        Err(SyscallError::Fault)
+    }
+}
+
+/// Write value to user memory.
+///
+/// # Parameters
+/// - `addr`  - given user virtual address.
+/// - `value` - given value to write.
+///
+/// # Returns
+/// - `Ok`                  - in case of success.
+/// - `SyscallError::Fault` - if memory is not writable.
+fn write_user<T: Copy>(addr: u32, value: T) -> Result<(), SyscallError> {
+    check_user(addr, size_of::<T>(), Prot::WRITE)?;
+
+    // Missing pages are mapped by page fault handler.
+    unsafe { (addr as *mut T).write_unaligned(value) };
+    Ok(())
+}
+
+/// Write string to kernel log.
+///
+/// # Parameters
+/// - `args` - given string address & length.
+///
+/// # Returns
+/// - Number of bytes written - in case of success.
+/// - `SyscallError`          - otherwise.
+fn sys_debug_write(args: &[u32; MAX_ARGS]) -> SyscallResult {
+    let (addr, len) = (args[0], args[1] as usize);
+
+    if len > MAX_DEBUG_WRITE {
+        return Err(SyscallError::Invalid);
+    }
+
+    check_user(addr, len, Prot::READ)?;
+
+    // Copy string, so that user code can not change it after validation.
+    let bytes = unsafe { core::slice::from_raw_parts(addr as *const u8, len) };
+    let bytes = Vec::from(bytes);
+    let text = str::from_utf8(&bytes).map_err(|_| SyscallError::Invalid)?;
+
+    log::info!("User: {}", text);
+    Ok(len as u32)
+}
+
+/// Get time since boot.
+///
+/// # Parameters
+/// - `args` - given address of `u64` to store nanoseconds to.
+///
+/// # Returns
+/// - Zero           - in case of success.
+/// - `SyscallError` - otherwise.
+fn sys_uptime(args: &[u32; MAX_ARGS]) -> SyscallResult {
+    write_user(args[0], time::uptime().as_nanos() as u64)?;
+    Ok(0)
+}
+
+/// Get wall-clock time.
+///
+/// # Parameters
+/// - `args` - given address of `u64` to store Unix time to.
+///
+/// # Returns
+/// - Zero           - in case of success.
+/// - `SyscallError` - otherwise.
+fn sys_wall_clock(args: &[u32; MAX_ARGS]) -> SyscallResult {
+    let now = time::wall_clock().ok_or(SyscallError::Unavailable)?;
+
+    write_user(args[0], now.to_unix())?;
+    Ok(0)
+}
+
+/// Finish user code run by the kernel.
+///
+/// # Parameters
+/// - `args` - given exit status.
+///
+/// # Returns
+/// - `SyscallError::Invalid` - if no user code is run (otherwise does not
+///   return).
+fn sys_exit(args: &[u32; MAX_ARGS]) -> SyscallResult {
+    // Successful exit does not return to `dispatch`, so trace it here.
+    if TRACE.load(Ordering::Relaxed) {
+        log::debug!("syscall exit[{:X}]", args[0]);
+    }
+
+    hal::cpu::exit_user_mode(args[0]);
+    Err(SyscallError::Invalid)
+}
+
+/// Dispatch system call to its handler.
+///
+/// # Parameters
+/// - `number` - given system call number.
+/// - `args`   - given system call arguments.
+///
+/// # Returns
+/// - Encoded system call result (see `encode`).
+pub fn dispatch(number: u32, args: &[u32; MAX_ARGS]) -> u32 {
+    let Some(syscall) = SYSCALLS.get(number as usize) else {
+        if TRACE.load(Ordering::Relaxed) {
+            log::debug!(
+                "syscall {}{:X?} = {:?}",
+                number,
+                args,
+                SyscallError::NoSys
+            );
+        }
+
+        return encode(Err(SyscallError::NoSys));
+    };
+
+    let result = (syscall.handler)(args);
+
+    if TRACE.load(Ordering::Relaxed) {
+        log::debug!(
+            "syscall {}{:X?} = {:?}",
+            syscall.name,
+            &args[..syscall.args],
+            result
+        );
+    }
+
+    encode(result)
+}
+
+#[cfg(feature = "ktest")]
+pub mod tests {
+    use super::*;
+    use crate::{
+        kernel::mm::{
+            PAGE_SIZE,
+            vm::{AddressSpace, Backing, RegionFlags},
+        },
+        ktest::TestCase,
+    };
+    use alloc::sync::Arc;
+    use spin::Mutex;
+
+    /// System call unit tests.
+    pub const TESTS: &[TestCase] = &[
+        TestCase::new("result_encoding", result_encoding),
+        TestCase::new("unknown_number", unknown_number),
+        TestCase::new("kernel_memory_rejected", kernel_memory_rejected),
+        TestCase::new("user_memory", user_memory),
+        TestCase::new("exit_from_kernel", exit_from_kernel),
+    ];
+
+    /// Base address of test region.
+    const BASE: u32 = 0x10000000;
+
+    /// Page size in bytes.
+    const PAGE: u32 = PAGE_SIZE as u32;
+
+    fn result_encoding() {
+        assert_eq!(encode(Ok(0)), 0);
+        assert_eq!(encode(Ok(42)), 42);
+        assert_eq!(encode(Err(SyscallError::NoSys)) as i32, -1);
+        assert_eq!(encode(Err(SyscallError::Fault)) as i32, -3);
+    }
+
+    fn unknown_number() {
+        let result = dispatch(SYSCALLS.len() as u32, &[0; MAX_ARGS]);
+        assert_eq!(result, encode(Err(SyscallError::NoSys)));
+    }
+
+    fn kernel_memory_rejected() {
+        let value = 0u64;
+        let addr = &raw const value as u32;
+
+        let result = dispatch(SYS_UPTIME, &[addr, 0, 0, 0]);
+        assert_eq!(result, encode(Err(SyscallError::Fault)));
+
+        let result = dispatch(SYS_DEBUG_WRITE, &[addr, 8, 0, 0]);
+        assert_eq!(result, encode(Err(SyscallError::Fault)));
+    }
+
+    fn user_memory() {
+        let space = Arc::new(Mutex::new(AddressSpace::new().unwrap()));
+
+        space
+            .lock()
+            .map(
+                BASE as usize,
+                PAGE as usize,
+                Prot::READ | Prot::WRITE,
+                RegionFlags::USER,
+                Backing::Anonymous,
+            )
+            .unwrap();
+
+        vm::switch_to(space.clone());
+
+        let text = b"hello";
+        let ptr = BASE as *mut u8;
+        unsafe { ptr.copy_from_nonoverlapping(text.as_ptr(), text.len()) };
+
+        let result = dispatch(SYS_DEBUG_WRITE, &[BASE, 5, 0, 0]);
+        assert_eq!(result, 5);
+
+        let result = dispatch(SYS_DEBUG_WRITE, &[BASE + PAGE - 2, 5, 0, 0]);
+        assert_eq!(result, encode(Err(SyscallError::Fault)));
+
+        let result = dispatch(SYS_UPTIME, &[BASE + 8, 0, 0, 0]);
+        let uptime = unsafe { ((BASE + 8) as *const u64).read_unaligned() };
+        assert_eq!(result, 0);
+        assert!(uptime > 0);
+
+        vm::switch_to_kernel();
+    }
+
+    fn exit_from_kernel() {
+        let result = dispatch(SYS_EXIT, &[0; MAX_ARGS]);
+        assert_eq!(result, encode(Err(SyscallError::Invalid)));
+    }
+}
@@ -0,0 +1,431 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Date: 2025-06-13
+// Author: Alexander Kuzin <alkuzindev@gmail.com>.
+
+//! Kernel monotonic & wall clocks.
+//!
+//! # Description
+//! Monotonic time is measured since system timer initialization & never
+//! goes backwards. Busy-wait sleeps rely on timer interrupts, so they must
+//! not be used with interrupts disabled for longer than a single timer
+//! tick. Wall clock is real-time clock reading taken once during boot
+//! (with one second precision) advanced by monotonic time.
+
+use crate::hal;
+use core::{
+    fmt, hint,
+    ops::{Add, RangeInclusive, Sub},
+    time::Duration,
+};
+use spin::Once;
+
+/// Number of nanoseconds in second.
+const NANOS_PER_SEC: u64 = 1_000_000_000;
+
+/// Number of seconds in day.
+const SECS_PER_DAY: u64 = 86400;
+
+/// Number of days between 0000-03-01 & 1970-01-01 (proleptic Gregorian).
+const UNIX_EPOCH_DAYS: i64 = 719468;
+
+/// Number of days in 400 years.
+const DAYS_PER_ERA: i64 = 146097;
+
+/// Years accepted from real-time clock (garbage CMOS values are rejected).
+const RTC_YEARS: RangeInclusive<u16> = 1970..=2199;
+
+/// Unix time of boot (system timer initialization) in nanoseconds.
+static BOOT_TIME: Once<u64> = Once::new();
+
+/// Point of monotonic clock.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Instant {
+    /// Nanoseconds since boot.
+    nanos: u64,
+}
+
+impl Instant {
+    /// Get current point of monotonic clock.
+    ///
+    /// # Returns
+    /// - Current `Instant`.
+    pub fn now() -> Self {
+        Self {
+            nanos: hal::timer::uptime_ns(),
+        }
+    }
+
+    /// Get time passed since earlier point.
+    ///
+    /// # Parameters
+    /// - `earlier` - given earlier point of monotonic clock.
+    ///
+    /// # Returns
+    /// - Time passed (zero if `earlier` is later than `self`).
+    pub fn duration_since(&self, earlier: Self) -> Duration {
+        Duration::from_nanos(self.nanos.saturating_sub(earlier.nanos))
+    }
+
+    /// Get time passed since this point.
+    ///
+    /// # Returns
+    /// - Time passed since this point.
+    pub fn elapsed(&self) -> Duration {
+        Self::now().duration_since(*self)
+    }
+
+    /// Get time passed since boot.
+    ///
+    /// # Returns
+    /// - Time passed since boot till this point.
+    pub fn since_boot(&self) -> Duration {
+        Duration::from_nanos(self.nanos)
+    }
+}
+
+impl Add<Duration> for Instant {
+    type Output = Self;
+
+    /// Get point of monotonic clock after given time.
+    ///
+    /// # Parameters
+    /// - `duration` - given time to add.
+    ///
+    /// # Returns
+    /// - Later `Instant`.
+    fn add(self, duration: Duration) -> Self {
+        let nanos = u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX);
+
+        Self {
+            nanos: self.nanos.saturating_add(nanos),
+        }
+    }
+}
+
+impl Sub for Instant {
+    type Output = Duration;
+
+    /// Get time passed between two points.
+    ///
+    /// # Parameters
+    /// - `earlier` - given earlier point of monotonic clock.
+    ///
+    /// # Returns
+    /// - Time passed (zero if `earlier` is later than `self`).
+    fn sub(self, earlier: Self) -> Duration {
+        self.duration_since(earlier)
+    }
+}
+
+/// Get time passed since boot.
+///
+/// # Returns
+/// - Time passed since system timer initialization.
+#[inline(always)]
+pub fn uptime() -> Duration {
+    Instant::now().since_boot()
+}
+
+/// Busy-wait for given time.
+///
+/// # Parameters
+/// - `duration` - given time to wait.
+pub fn sleep(duration: Duration) {
+    let deadline = Instant::now() + duration;
+
+    while Instant::now() < deadline {
+        hint::spin_loop();
+    }
+}
+
+/// Busy-wait for given number of milliseconds.
+///
+/// # Parameters
+/// - `ms` - given number of milliseconds to wait.
+#[inline(always)]
+pub fn sleep_ms(ms: u64) {
+    sleep(Duration::from_millis(ms));
+}
+
+/// Busy-wait for given number of microseconds.
+///
+/// # Parameters
+/// - `us` - given number of microseconds to wait.
+#[inline(always)]
+pub fn sleep_us(us: u64) {
+    sleep(Duration::from_micros(us));
+}
+
+/// Calendar date & time (UTC).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct DateTime {
+    /// Year.
+    pub year: u16,
+    /// Month (1-12).
+    pub month: u8,
+    /// Day of month (1-31).
+    pub day: u8,
+    /// Hour (0-23).
+    pub hour: u8,
+    /// Minute (0-59).
+    pub minute: u8,
+    /// Second (0-59).
+    pub second: u8,
+}
+
+impl DateTime {
+    /// Unix epoch (1970-01-01 00:00:00).
+    pub const UNIX_EPOCH: Self = Self {
+        year: 1970,
+        month: 1,
+        day: 1,
+        hour: 0,
+        minute: 0,
+        second: 0,
+    };
+
+    /// Construct date & time from Unix time.
+    ///
+    /// # Parameters
+    /// - `secs` - given number of seconds since Unix epoch.
+    ///
+    /// # Returns
+    /// - New `DateTime` object.
+    pub fn from_unix(secs: u64) -> Self {
+        let days = (secs / SECS_PER_DAY) as i64 + UNIX_EPOCH_DAYS;
+        let time = secs % SECS_PER_DAY;
+
+        // Years start at March, so that leap day is the last one.
+        let era = days / DAYS_PER_ERA;
+        let day_of_era = days - era * DAYS_PER_ERA;
+        let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524
+            - day_of_era / 146096)
+            / 365;
+        let day_of_year = day_of_era
+            - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * month + 2) / 5 + 1;
+        let month = if month < 10 { month + 3 } else { month - 9 };
+        let year = year_of_era + era * 400 + (month <= 2) as i64;
+
+        Self {
+            year: year as u16,
+            month: month as u8,
+            day: day as u8,
+            hour: (time / 3600) as u8,
+            minute: (time / 60 % 60) as u8,
+            second: (time % 60) as u8,
+        }
+    }
+
+    /// Convert date & time to Unix time.
+    ///
+    /// # Returns
+    /// - Number of seconds since Unix epoch (zero for earlier dates).
+    pub fn to_unix(self) -> u64 {
+        let month = self.month as i64;
+        let year = self.year as i64 - (month <= 2) as i64;
+
+        // Years start at March, so that leap day is the last one.
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let month = if month > 2 { month - 3 } else { month + 9 };
+        let day_of_year = (153 * month + 2) / 5 + self.day as i64 - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4
+            - year_of_era / 100
+            + day_of_year;
+        let days = era * DAYS_PER_ERA + day_of_era - UNIX_EPOCH_DAYS;
+
+        if days < 0 {
+            return 0;
+        }
+
+        days as u64 * SECS_PER_DAY
+            + self.hour as u64 * 3600
+            + self.minute as u64 * 60
+            + self.second as u64
+    }
+
+    /// Check whether all fields are in range.
+    ///
+    /// # Returns
+    /// - `true`  - if date & time is valid.
+    /// - `false` - otherwise.
+    pub fn is_valid(&self) -> bool {
+        (1..=12).contains(&self.month)
+            && (1..=31).contains(&self.day)
+            && self.hour < 24
+            && self.minute < 60
+            && self.second < 60
+    }
+}
+
+impl fmt::Display for DateTime {
+    /// Format date & time as `YYYY-MM-DD hh:mm:ss`.
+    ///
+    /// # Parameters
+    /// - `f` - given formatter.
+    ///
+    /// # Returns
+    /// - `Ok`  - in case of success.
+    /// - `Err` - otherwise.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+            self.year,
+            self.month,
+            self.day,
+            self.hour,
+            self.minute,
+            self.second
+        )
+    }
+}
+
+/// Initialize wall clock from real-time clock.
+///
+/// # Returns
+/// - `true`  - in case of success.
+/// - `false` - if real-time clock value is invalid (wall clock is left
+///   uninitialized).
+pub fn init() -> bool {
+    let (date, uptime) =
+        hal::irq::without_interrupts(|| (hal::rtc::read(), uptime()));
+
+    if !date.is_valid() || !RTC_YEARS.contains(&date.year) {
+        return false;
+    }
+
+    let Some(now) = date.to_unix().checked_mul(NANOS_PER_SEC) else {
+        return false;
+    };
+
+    let uptime = uptime.as_nanos() as u64;
+
+    BOOT_TIME.call_once(|| now.saturating_sub(uptime));
+    true
+}
+
+/// Get current date & time.
+///
+/// # Returns
+/// - Current date & time (UTC) - in case of success.
+/// - `None`                    - if wall clock is not initialized.
+pub fn wall_clock() -> Option<DateTime> {
+    let boot_time = BOOT_TIME.get()?;
+    let now = boot_time + hal::timer::uptime_ns();
+
+    Some(DateTime::from_unix(now / NANOS_PER_SEC))
+}
+
+#[cfg(feature = "ktest")]
+pub mod tests {
+    use super::*;
+    use crate::ktest::TestCase;
+    use alloc::format;
+
+    /// Clocks unit tests.
+    pub const TESTS: &[TestCase] = &[
+        TestCase::new("clock_is_monotonic", clock_is_monotonic),
+        TestCase::new("sleep_waits", sleep_waits),
+        TestCase::new("ticks_advance", ticks_advance),
+        TestCase::new("instant_arithmetic", instant_arithmetic),
+        TestCase::new("unix_time_conversion", unix_time_conversion),
+        TestCase::new("date_time_format", date_time_format),
+        TestCase::new("wall_clock_advances", wall_clock_advances),
+    ];
+
+    /// Construct date & time.
+    const fn date(
+        year: u16,
+        month: u8,
+        day: u8,
+        hour: u8,
+        min: u8,
+    ) -> DateTime {
+        DateTime {
+            year,
+            month,
+            day,
+            hour,
+            minute: min,
+            second: 0,
+        }
+    }
+
+    fn clock_is_monotonic() {
+        let mut previous = Instant::now();
+
+        for _ in 0..10000 {
+            let now = Instant::now();
+            assert!(now >= previous);
+            previous = now;
+        }
+    }
+
+    fn sleep_waits() {
+        let start = Instant::now();
+        sleep_us(500);
+        assert!(start.elapsed() >= Duration::from_micros(500));
+
+        let start = Instant::now();
+        sleep_ms(20);
+        let elapsed = start.elapsed();
+
+        assert!(elapsed >= Duration::from_millis(20));
+        assert!(elapsed < Duration::from_millis(200));
+    }
+
+    fn ticks_advance() {
+        let ticks = hal::timer::ticks();
+        let rate = hal::timer::tick_rate() as u64;
+
+        // Wait for three tick periods, so that at least two ticks pass.
+        sleep(Duration::from_nanos(3_000_000_000 / rate));
+        assert!(hal::timer::ticks() >= ticks + 2);
+    }
+
+    fn instant_arithmetic() {
+        let start = Instant::now();
+        let later = start + Duration::from_millis(5);
+
+        assert_eq!(later - start, Duration::from_millis(5));
+        assert_eq!(start - later, Duration::ZERO);
+        assert_eq!(start + Duration::MAX, Instant { nanos: u64::MAX });
+    }
+
+    fn unix_time_conversion() {
+        let cases = [
+            (DateTime::UNIX_EPOCH, 0),
+            (date(2000, 2, 29, 12, 34), 951827640),
+            (date(2000, 3, 1, 0, 0), 951868800),
+            (date(2024, 12, 31, 23, 59), 1735689540),
+            (date(2100, 3, 1, 0, 0), 4107542400),
+        ];
+
+        for (expected, secs) in cases {
+            assert_eq!(expected.to_unix(), secs);
+            assert_eq!(DateTime::from_unix(secs), expected);
+        }
+
+        assert_eq!(date(1969, 12, 31, 23, 59).to_unix(), 0);
+    }
+
+    fn date_time_format() {
+        let text = format!("{}", date(2025, 6, 3, 7, 5));
+        assert_eq!(text, "2025-06-03 07:05:00");
+    }
+
+    fn wall_clock_advances() {
+        let Some(start) = wall_clock() else {
+            return;
+        };
+
+        assert!(start.is_valid());
+        assert!(start >= date(2025, 1, 1, 0, 0));
+
+        sleep_ms(1100);
+        assert!(wall_clock().unwrap() > start);
+    }
+}
@@ -0,0 +1,94 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Date: 2025-06-13
+// Author: Alexander Kuzin <alkuzindev@gmail.com>.
+
+//! Kernel custom testing framework.
+//!
+//! # Description
+//! Tests are plain functions grouped into suites. Failed assertion panics
+//! and the kernel panic handler reports the failed test location.
+
+use crate::{
+    arch, bootinfo, hal,
+    kernel::{cmdline, mm, syscall, time},
+    log, multiboot, multiboot2,
+};
+
+/// Kernel test case.
+pub struct TestCase {
+    /// Test name.
+    pub name: &'static str,
+    /// Test function.
+    pub func: fn(),
+}
+
+impl TestCase {
+    /// Construct new test case.
+    ///
+    /// # Parameters
+    /// - `name` - given test name.
+    /// - `func` - given test function.
+    ///
+    /// # Returns
+    /// - New `TestCase` object.
+    pub const fn new(name: &'static str, func: fn()) -> Self {
+        Self { name, func }
+    }
+}
+
+/// Run test suite.
+///
+/// # Parameters
+/// - `suite` - given test suite name.
+/// - `tests` - given test cases to run.
+pub fn run(suite: &str, tests: &[TestCase]) {
+    log::test!("Running {} tests of <{}>", tests.len(), suite);
+
+    for test in tests {
+        (test.func)();
+        log::test!("{}::{} ... ok", suite, test.name);
+    }
+}
+
+/// Run all kernel tests.
+pub fn run_all() {
+    run("multiboot", multiboot::tests::TESTS);
+    run("multiboot2", multiboot2::tests::TESTS);
+    run("bootinfo", bootinfo::tests::TESTS);
+    run("cmdline", cmdline::tests::TESTS);
+    run("memmap", mm::memmap::tests::TESTS);
+    run("frame", mm::frame::tests::TESTS);
+    run("buddy", mm::buddy::tests::TESTS);
+    run("heap", mm::heap::tests::TESTS);
+    run("slab", mm::slab::tests::TESTS);
+    run("kstack", mm::kstack::tests::TESTS);
+    run("vm", mm::vm::tests::TESTS);
+    run("mmio", mm::mmio::tests::TESTS);
+    run("irq", hal::irq::tests::TESTS);
+    run("time", time::tests::TESTS);
+    run("clocksource", hal::clocksource::tests::TESTS);
+    run("syscall", syscall::tests::TESTS);
+
+    #[cfg(target_arch = "x86")]
+    run("paging", arch::x86::paging::tests::TESTS);
+
+    #[cfg(target_arch = "x86")]
+    run("gdt", arch::x86::gdt::tests::TESTS);
+
+    #[cfg(target_arch = "x86")]
+    run("idt", arch::x86::idt::tests::TESTS);
+
+    #[cfg(target_arch = "x86")]
+    run("tss", arch::x86::tss::tests::TESTS);
+
+    #[cfg(target_arch = "x86")]
+    run("usermode", arch::x86::usermode::tests::TESTS);
+
+    #[cfg(target_arch = "x86")]
+    run("apic", arch::x86::apic::tests::TESTS);
+
+    #[cfg(target_arch = "x86")]
+    run("x86_syscall", arch::x86::syscall::tests::TESTS);
+
+    log::success!("All kernel tests passed");
+}
@@ -12,29 +12,50 @@
 #![allow(clippy::empty_loop)]
 // Allow unused values.
 #![allow(dead_code)]
+// Allow custom kernel heap allocation failure handler.
+#![feature(alloc_error_handler)]
+
+extern crate alloc;
 
 mod arch;
+mod bootinfo;
 mod config;
 mod drivers;
 mod hal;
 mod kernel;
+#[cfg(feature = "ktest")]
+mod ktest;
 mod log;
 mod multiboot;
+mod multiboot2;
 
+use bootinfo::BootInfo;
 use multiboot::{MULTIBOOT_BOOTLOADER_MAGIC, MultibootInfo};
+use multiboot2::{MULTIBOOT2_BOOTLOADER_MAGIC, Multiboot2Info};
 
 /// Kernel entry point.
 ///
 /// # Parameters
-/// - `magic`     - given multiboot magic number.
-/// - `boot_info` - given multiboot info structure.
+/// - `magic`     - given multiboot (or multiboot2) magic number.
+/// - `boot_info` - given physical address of boot info structure.
 #[unsafe(no_mangle)]
-extern "C" fn kmain(magic: u32, boot_info: &MultibootInfo) -> ! {
-    // Check that multiboot magic number is correct.
-    assert_eq!(magic, MULTIBOOT_BOOTLOADER_MAGIC);
+extern "C" fn kmain(magic: u32, boot_info: u32) -> ! {
+    // Convert boot protocol specific info to kernel boot info.
+    let boot_info = match magic {
+        MULTIBOOT_BOOTLOADER_MAGIC => {
+            let info =
+                unsafe { &*(boot_info as usize as *const MultibootInfo) };
+            BootInfo::from_multiboot(info)
+        }
+        MULTIBOOT2_BOOTLOADER_MAGIC => {
+            let info = unsafe { Multiboot2Info::from_addr(boot_info) };
+            BootInfo::from_multiboot2(&info.expect("Corrupted multiboot2 info"))
+        }
+        _ => unreachable!("Unknown bootloader magic number: {:#010X}", magic),
+    };
 
     // Initialize the kernel.
-    kernel::init(boot_info);
+    kernel::init(&boot_info);
 
     // Halt the kernel.
     loop {}
@@ -47,6 +68,12 @@ use core::panic::PanicInfo;
 /// # Parameters
 /// - `info` - given panic information struct.
 #[panic_handler]
-fn panic(_info: &PanicInfo) -> ! {
-    loop {}
+fn panic(info: &PanicInfo) -> ! {
+    // Interrupt handlers must not run on top of broken kernel state.
+    hal::irq::disable();
+    log::panic!("{}", info);
+
+    loop {
+        hal::cpu::halt();
+    }
 }
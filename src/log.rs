@@ -4,9 +4,10 @@
 
 //! Kernel logging related declarations.
 
-use crate::{drivers::vbe::Framebuffer, hal::uart::{Uart, UartInterface}, kernel::gfx::{Color, Rgb, terminal::Terminal}};
+use crate::{drivers::vbe::Framebuffer, hal::uart::{Uart, UartInterface}, kernel::{cmdline::{Choice, Param, ParamKind, kernel_param}, gfx::{Color, Rgb, terminal::Terminal}, time}};
 use core::fmt;
 use core::fmt::{Arguments, Write};
+use core::sync::atomic::{AtomicU32, Ordering};
 use lazy_static::lazy_static;
 use spin::Mutex;
 
@@ -64,13 +65,190 @@ pub fn init_terminal_writer(fb: Framebuffer) {
     TERMINAL_WRITER.lock().init(fb);
 }
 
+/// Log level enumeration. Less value means more important message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum LogLevel {
+    Panic = 0,
+    Fail = 1,
+    Success = 2,
+    Info = 3,
+    Debug = 4,
+}
+
+/// Maximum log level of messages to print.
+static LOGLEVEL: AtomicU32 = AtomicU32::new(LogLevel::Debug as u32);
+
+kernel_param! {
+    /// Kernel parameter setting maximum log level (`loglevel=0..4`).
+    static LOGLEVEL_PARAM = Param {
+        name: "loglevel",
+        description: "Maximum log level of messages to print",
+        kind: ParamKind::U32 {
+            value: &LOGLEVEL,
+            min: LogLevel::Panic as u32,
+            max: LogLevel::Debug as u32,
+        },
+    };
+}
+
+/// Check whether messages of specific log level should be printed.
+///
+/// # Parameters
+/// - `level` - given log level to check.
+///
+/// # Returns
+/// - `true`  - if messages of this log level should be printed.
+/// - `false` - otherwise.
+pub fn is_enabled(level: LogLevel) -> bool {
+    level as u32 <= LOGLEVEL.load(Ordering::Relaxed)
+}
+
+/// Kernel output console enumeration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Console {
+    /// Serial port only.
+    Serial,
+    /// Framebuffer terminal only.
+    Framebuffer,
+    /// Both serial port and framebuffer terminal.
+    Both,
+}
+
+/// Selected kernel output console.
+static CONSOLE: Choice<Console> = Choice::new(
+    Console::Both,
+    &[
+        ("serial", Console::Serial),
+        ("fb", Console::Framebuffer),
+        ("both", Console::Both),
+    ],
+);
+
+kernel_param! {
+    /// Kernel parameter selecting output console (`console=serial|fb|both`).
+    static CONSOLE_PARAM = Param {
+        name: "console",
+        description: "Kernel output console",
+        kind: ParamKind::Choice(&CONSOLE),
+    };
+}
+
+/// Log record timestamp format enumeration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Timestamp {
+    /// Seconds & milliseconds since boot.
+    Uptime,
+    /// Wall clock date & time.
+    Date,
+}
+
+/// Selected log record timestamp format.
+static TIMESTAMP: Choice<Timestamp> = Choice::new(
+    Timestamp::Uptime,
+    &[("uptime", Timestamp::Uptime), ("date", Timestamp::Date)],
+);
+
+kernel_param! {
+    /// Kernel parameter selecting log timestamp format (`logtime=uptime|date`).
+    static LOGTIME_PARAM = Param {
+        name: "logtime",
+        description: "Log record timestamp format",
+        kind: ParamKind::Choice(&TIMESTAMP),
+    };
+}
+
+/// Get selected log record timestamp format.
+///
+/// # Returns
+/// - Selected timestamp format.
+pub fn timestamp() -> Timestamp {
+    TIMESTAMP.get()
+}
+
+/// Print log record timestamp. Uptime is printed until wall clock is
+/// initialized.
+pub fn print_timestamp() {
+    let fg = TIMESTAMP_COLOR;
+    let bg = unsafe { BACKGROUND_COLOR };
+
+    let date = match timestamp() {
+        Timestamp::Date => time::wall_clock(),
+        Timestamp::Uptime => None,
+    };
+
+    if let Some(date) = date {
+        crate::cprint!(fg, bg, "|{}|", date);
+        return;
+    }
+
+    // Seconds since boot & milliseconds since the last full second.
+    let uptime = time::uptime();
+    crate::cprint!(
+        fg,
+        bg,
+        "|{:0>5}.{:0>3}|",
+        uptime.as_secs(),
+        uptime.subsec_millis()
+    );
+}
+
+/// Get selected kernel output console.
+///
+/// # Returns
+/// - Selected kernel output console.
+pub fn console() -> Console {
+    CONSOLE.get()
+}
+
 /// Prints format string and it's arguments.
 ///
 /// # Parameters
 /// - `args` - given precompiled version of a format string and it`s arguments.
 pub fn __print(args: Arguments) {
-    let _ = SERIAL_WRITER.lock().write_fmt(args);
-    let _ = TERMINAL_WRITER.lock().write_fmt(args);
+    let console = console();
+
+    if console != Console::Framebuffer {
+        let _ = SERIAL_WRITER.lock().write_fmt(args);
+    }
+
+    if console != Console::Serial {
+        let _ = TERMINAL_WRITER.lock().write_fmt(args);
+    }
+}
+
+/// Prints format string and it's arguments bypassing writer locks. Used
+/// on panic & fatal exception paths, where interrupted code may hold them.
+///
+/// # Parameters
+/// - `args` - given precompiled version of a format string and it`s arguments.
+pub fn __emergency_print(args: Arguments) {
+    let console = console();
+
+    if console != Console::Framebuffer {
+        // UART writer has no state, so separate instance is safe to use.
+        let _ = Uart {}.write_fmt(args);
+    }
+
+    if console != Console::Serial {
+        // Terminal is skipped if interrupted code holds it.
+        let _ = TERMINAL_WRITER.try_lock().map(|mut t| t.write_fmt(args));
+    }
+}
+
+/// Prints panic log record bypassing writer locks. Timestamp is not
+/// printed, since clock state may be locked as well.
+///
+/// # Parameters
+/// - `args` - given precompiled version of a format string and it`s arguments.
+pub fn __panic_print(args: Arguments) {
+    let (fg, bg) = unsafe { (FOREGROUND_COLOR, BACKGROUND_COLOR) };
+
+    __emergency_print(format_args!("["));
+    set_color(LOG_PANIC_COLOR, bg);
+    __emergency_print(format_args!("{LOG_PANIC}"));
+    set_color(fg, bg);
+    __emergency_print(format_args!("]: {args}\n"));
 }
 
 /// Formats and prints colored data.
@@ -145,21 +323,19 @@ pub const TIMESTAMP_COLOR: Rgb = Color::DarkGray as Rgb;
 /// Custom log output.
 ///
 /// # Parameters
+/// - `level` - given log level.
 /// - `title` - given custom log title.
 /// - `fb`    - given log title foreground color.
 /// - `bg`    - given log title background color.
 #[macro_export]
 macro_rules! custom {
-    // TODO: replace zeros in format string with timestamp:
-    // [<seconds since boot>.<milliseconds since the last full second>].
-    ($title:expr, $fg:expr, $bg:expr, $($arg:tt)*) => {{
-        let timestamp_fg = $crate::log::TIMESTAMP_COLOR;
-        let timestamp_bg = unsafe { $crate::log::BACKGROUND_COLOR };
-
-        $crate::cprint!(timestamp_fg, timestamp_bg, "|{:0>5}.{:0>3}|", 0, 0);
-        $crate::print!(" [");
-        $crate::cprint!($fg, $bg, "{}", $title);
-        $crate::print!("]: {}\n", format_args!($($arg)*));
+    ($level:expr, $title:expr, $fg:expr, $bg:expr, $($arg:tt)*) => {{
+        if $crate::log::is_enabled($level) {
+            $crate::log::print_timestamp();
+            $crate::print!(" [");
+            $crate::cprint!($fg, $bg, "{}", $title);
+            $crate::print!("]: {}\n", format_args!($($arg)*));
+        }
     }};
 }
 
@@ -168,6 +344,7 @@ macro_rules! custom {
 macro_rules! info {
     ($($arg:tt)*) => {{
         $crate::log::custom!(
+            $crate::log::LogLevel::Info,
             $crate::log::LOG_INFO,
             $crate::log::LOG_INFO_COLOR,
             unsafe { $crate::log::BACKGROUND_COLOR },
@@ -182,6 +359,7 @@ macro_rules! debug {
     ($($arg:tt)*) => {{
         #[cfg(debug_assertions)]
         $crate::log::custom!(
+            $crate::log::LogLevel::Debug,
             $crate::log::LOG_DEBUG,
             $crate::log::LOG_DEBUG_COLOR,
             unsafe { $crate::log::BACKGROUND_COLOR },
@@ -195,6 +373,7 @@ macro_rules! debug {
 macro_rules! success {
     ($($arg:tt)*) => {{
         $crate::log::custom!(
+            $crate::log::LogLevel::Success,
             $crate::log::LOG_SUCCESS,
             $crate::log::LOG_SUCCESS_COLOR,
             unsafe { $crate::log::BACKGROUND_COLOR },
@@ -208,6 +387,7 @@ macro_rules! success {
 macro_rules! fail {
     ($($arg:tt)*) => {{
         $crate::log::custom!(
+            $crate::log::LogLevel::Fail,
             $crate::log::LOG_FAIL,
             $crate::log::LOG_FAIL_COLOR,
             unsafe { $crate::log::BACKGROUND_COLOR },
@@ -216,16 +396,11 @@ macro_rules! fail {
     }};
 }
 
-/// Panic log output.
+/// Panic log output. Does not take writer locks (see `__panic_print`).
 #[macro_export]
 macro_rules! panic {
     ($($arg:tt)*) => {{
-        $crate::log::custom!(
-            $crate::log::LOG_PANIC,
-            $crate::log::LOG_PANIC_COLOR,
-            unsafe { $crate::log::BACKGROUND_COLOR },
-            $($arg)*
-        )
+        $crate::log::__panic_print(format_args!($($arg)*))
     }};
 }
 
@@ -234,6 +409,7 @@ macro_rules! panic {
 macro_rules! test {
     ($($arg:tt)*) => {{
         $crate::log::custom!(
+            $crate::log::LogLevel::Panic,
             $crate::log::LOG_TEST,
             $crate::log::LOG_TEST_COLOR,
             unsafe { $crate::log::BACKGROUND_COLOR },
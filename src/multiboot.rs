@@ -4,7 +4,12 @@
 
 //! Contains multiboot specification related declarations.
 
-use core::fmt;
+use core::{
+    ffi::{CStr, c_char},
+    fmt,
+    marker::PhantomData,
+    ptr,
+};
 
 /// Number of bytes from the start of the file
 /// to search for the header.
@@ -260,7 +265,7 @@ pub struct MultibootColor {
 }
 
 /// Multiboot memory map entry type.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(u32)]
 pub enum MultibootMemoryType {
     Available = 1,
@@ -270,6 +275,29 @@ pub enum MultibootMemoryType {
     BadRam = 5,
 }
 
+impl TryFrom<MultibootU32> for MultibootMemoryType {
+    type Error = MultibootU32;
+
+    /// Convert raw memory map entry type.
+    ///
+    /// # Parameters
+    /// - `value` - given raw memory map entry type.
+    ///
+    /// # Returns
+    /// - `MultibootMemoryType` member - in case of known type.
+    /// - `Err` with raw value         - otherwise.
+    fn try_from(value: MultibootU32) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(Self::Available),
+            2 => Ok(Self::Reserved),
+            3 => Ok(Self::AcpiReclaimable),
+            4 => Ok(Self::Nvs),
+            5 => Ok(Self::BadRam),
+            _ => Err(value),
+        }
+    }
+}
+
 /// Multiboot memory map info.
 #[derive(Debug, Clone, Copy)]
 #[repr(C, packed)]
@@ -277,7 +305,9 @@ pub struct MultibootMmapEntry {
     pub size: MultibootU32,
     pub addr: MultibootU64,
     pub len: MultibootU64,
-    pub mtype: MultibootMemoryType,
+    /// Raw memory type, firmware may report values unknown to the kernel.
+    /// Use `MultibootMemoryType::try_from` to convert it.
+    pub mtype: MultibootU32,
 }
 
 /// Multiboot module info.
@@ -307,3 +337,475 @@ pub struct MultibootApmInfo {
     pub cseg_16_len: MultibootU16,
     pub dseg_len: MultibootU16,
 }
+
+/// ELF32 section header (as passed by the bootloader).
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct MultibootElfSection {
+    /// Section name (index into the section header string table).
+    pub name: MultibootU32,
+    /// Section type.
+    pub stype: MultibootU32,
+    /// Section attributes.
+    pub flags: MultibootU32,
+    /// Virtual address of the section in memory.
+    pub addr: MultibootU32,
+    /// Offset of the section in the file image.
+    pub offset: MultibootU32,
+    /// Size of the section in bytes.
+    pub size: MultibootU32,
+    /// Section index of an associated section.
+    pub link: MultibootU32,
+    /// Extra information about the section.
+    pub info: MultibootU32,
+    /// Required alignment of the section.
+    pub addralign: MultibootU32,
+    /// Size of each entry for sections that contain fixed-size entries.
+    pub entsize: MultibootU32,
+}
+
+/// Read NUL-terminated string provided by the bootloader.
+///
+/// # Parameters
+/// - `addr` - given physical address of the string.
+///
+/// # Returns
+/// - String slice - in case of valid UTF-8 string.
+/// - `None`       - otherwise.
+pub fn read_c_str<'a>(addr: MultibootU32) -> Option<&'a str> {
+    if addr == 0 {
+        return None;
+    }
+
+    // Bootloader guarantees that the string is NUL-terminated.
+    let s = unsafe { CStr::from_ptr(addr as usize as *const c_char) };
+    s.to_str().ok()
+}
+
+impl MultibootInfo {
+    /// Check whether specific multiboot info flag is set.
+    ///
+    /// # Parameters
+    /// - `flag` - given `MULTIBOOT_INFO_*` flag to check.
+    ///
+    /// # Returns
+    /// - `true`  - if flag is set.
+    /// - `false` - otherwise.
+    #[inline(always)]
+    pub fn has_flag(&self, flag: MultibootU32) -> bool {
+        (self.flags & flag) != 0
+    }
+
+    /// Get memory map provided by the bootloader.
+    ///
+    /// # Returns
+    /// - Memory map entries iterator - if memory map is present.
+    /// - `None`                      - otherwise.
+    pub fn memory_map(&self) -> Option<MemoryMapIter<'_>> {
+        if !self.has_flag(MULTIBOOT_INFO_MEM_MAP) {
+            return None;
+        }
+
+        let current = self.mmap_addr as usize;
+
+        // Buffer wrapping around the address space is treated as empty.
+        let end = current
+            .checked_add(self.mmap_length as usize)
+            .unwrap_or(current);
+
+        Some(MemoryMapIter {
+            current,
+            end,
+            _info: PhantomData,
+        })
+    }
+
+    /// Get boot modules loaded by the bootloader.
+    ///
+    /// # Returns
+    /// - Boot modules iterator - if modules are present.
+    /// - `None`                - otherwise.
+    pub fn modules(&self) -> Option<ModuleIter<'_>> {
+        if !self.has_flag(MULTIBOOT_INFO_MODS) {
+            return None;
+        }
+
+        Some(ModuleIter {
+            addr: self.mods_addr as usize,
+            index: 0,
+            count: self.mods_count as usize,
+            _info: PhantomData,
+        })
+    }
+
+    /// Get kernel command line.
+    ///
+    /// # Returns
+    /// - Kernel command line - if command line is present.
+    /// - `None`              - otherwise.
+    pub fn cmdline(&self) -> Option<&str> {
+        if !self.has_flag(MULTIBOOT_INFO_CMDLINE) {
+            return None;
+        }
+
+        read_c_str(self.cmdline)
+    }
+
+    /// Get bootloader name.
+    ///
+    /// # Returns
+    /// - Bootloader name - if bootloader name is present.
+    /// - `None`          - otherwise.
+    pub fn boot_loader_name(&self) -> Option<&str> {
+        if !self.has_flag(MULTIBOOT_INFO_BOOT_LOADER_NAME) {
+            return None;
+        }
+
+        read_c_str(self.boot_loader_name)
+    }
+
+    /// Get kernel ELF section headers.
+    ///
+    /// # Returns
+    /// - ELF section headers iterator - if section headers are present.
+    /// - `None`                       - otherwise.
+    pub fn elf_sections(&self) -> Option<ElfSectionIter<'_>> {
+        if !self.has_flag(MULTIBOOT_INFO_ELF_SHDR) {
+            return None;
+        }
+
+        let table = unsafe { self.u.elf_sec };
+
+        // Entry size smaller than section header means corrupted table.
+        if (table.size as usize) < size_of::<MultibootElfSection>() {
+            return None;
+        }
+
+        Some(ElfSectionIter {
+            addr: table.addr as usize,
+            entry_size: table.size as usize,
+            index: 0,
+            count: table.num as usize,
+            _info: PhantomData,
+        })
+    }
+}
+
+/// Iterator over multiboot memory map entries.
+pub struct MemoryMapIter<'a> {
+    /// Address of the current memory map entry.
+    current: usize,
+    /// Address of the end of memory map buffer.
+    end: usize,
+    /// Bind iterator lifetime to multiboot info structure.
+    _info: PhantomData<&'a MultibootInfo>,
+}
+
+impl Iterator for MemoryMapIter<'_> {
+    type Item = MultibootMmapEntry;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Do not read entry that does not fit in memory map buffer.
+        let entry_end =
+            self.current.checked_add(size_of::<MultibootMmapEntry>())?;
+
+        if entry_end > self.end {
+            return None;
+        }
+
+        let ptr = self.current as *const MultibootMmapEntry;
+        let entry = unsafe { ptr::read_unaligned(ptr) };
+
+        // The `size` field does not include itself. Iteration ends if the
+        // next entry address overflows.
+        self.current = (entry.size as usize)
+            .checked_add(size_of::<MultibootU32>())
+            .and_then(|size| self.current.checked_add(size))
+            .unwrap_or(self.end);
+
+        Some(entry)
+    }
+}
+
+/// Iterator over multiboot boot modules.
+pub struct ModuleIter<'a> {
+    /// Address of the modules list.
+    addr: usize,
+    /// Index of the current module.
+    index: usize,
+    /// Number of modules.
+    count: usize,
+    /// Bind iterator lifetime to multiboot info structure.
+    _info: PhantomData<&'a MultibootInfo>,
+}
+
+impl Iterator for ModuleIter<'_> {
+    type Item = MultibootModList;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.count {
+            return None;
+        }
+
+        let ptr = self.addr as *const MultibootModList;
+        let module = unsafe { ptr::read_unaligned(ptr.add(self.index)) };
+        self.index += 1;
+
+        Some(module)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.count - self.index;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for ModuleIter<'_> {}
+
+/// Iterator over kernel ELF section headers.
+pub struct ElfSectionIter<'a> {
+    /// Address of the section header table.
+    addr: usize,
+    /// Size of a single section header table entry.
+    entry_size: usize,
+    /// Index of the current section header.
+    index: usize,
+    /// Number of section headers.
+    count: usize,
+    /// Bind iterator lifetime to multiboot info structure.
+    _info: PhantomData<&'a MultibootInfo>,
+}
+
+impl Iterator for ElfSectionIter<'_> {
+    type Item = MultibootElfSection;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.count {
+            return None;
+        }
+
+        let addr = self
+            .index
+            .checked_mul(self.entry_size)
+            .and_then(|offset| self.addr.checked_add(offset));
+
+        // Iteration ends if section header address overflows.
+        let Some(addr) = addr else {
+            self.index = self.count;
+            return None;
+        };
+
+        let ptr = addr as *const MultibootElfSection;
+        let section = unsafe { ptr::read_unaligned(ptr) };
+        self.index += 1;
+
+        Some(section)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.count - self.index;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for ElfSectionIter<'_> {}
+
+#[cfg(feature = "ktest")]
+pub mod tests {
+    use super::*;
+    use crate::ktest::TestCase;
+
+    /// Multiboot info unit tests.
+    pub const TESTS: &[TestCase] = &[
+        TestCase::new("missing_flags", missing_flags),
+        TestCase::new("memory_map", memory_map),
+        TestCase::new("memory_map_truncated", memory_map_truncated),
+        TestCase::new("memory_map_overflow", memory_map_overflow),
+        TestCase::new("modules", modules),
+        TestCase::new("strings", strings),
+        TestCase::new("elf_sections", elf_sections),
+    ];
+
+    /// Size of memory map entry without its `size` field.
+    const ENTRY_SIZE: u32 = size_of::<MultibootMmapEntry>() as u32 - 4;
+
+    /// Construct memory map entry.
+    ///
+    /// # Parameters
+    /// - `addr`  - given region address.
+    /// - `len`   - given region length.
+    /// - `mtype` - given raw region type.
+    ///
+    /// # Returns
+    /// - New `MultibootMmapEntry` object.
+    fn entry(addr: u64, len: u64, mtype: u32) -> MultibootMmapEntry {
+        MultibootMmapEntry {
+            size: ENTRY_SIZE,
+            addr,
+            len,
+            mtype,
+        }
+    }
+
+    /// Construct multiboot info with memory map.
+    ///
+    /// # Parameters
+    /// - `map` - given memory map entries.
+    /// - `len` - given memory map buffer length in bytes.
+    ///
+    /// # Returns
+    /// - New `MultibootInfo` object.
+    fn info_with_map(map: &[MultibootMmapEntry], len: u32) -> MultibootInfo {
+        MultibootInfo {
+            flags: MULTIBOOT_INFO_MEM_MAP,
+            mmap_addr: map.as_ptr() as u32,
+            mmap_length: len,
+            ..Default::default()
+        }
+    }
+
+    fn missing_flags() {
+        let info = MultibootInfo {
+            cmdline: c"ignored".as_ptr() as u32,
+            ..Default::default()
+        };
+
+        assert!(info.memory_map().is_none());
+        assert!(info.modules().is_none());
+        assert!(info.cmdline().is_none());
+        assert!(info.boot_loader_name().is_none());
+        assert!(info.elf_sections().is_none());
+    }
+
+    fn memory_map() {
+        let map = [entry(0, 0x9F000, 1), entry(0x100000, 0x1000, 42)];
+        let info = info_with_map(&map, size_of_val(&map) as u32);
+        let mut entries = info.memory_map().unwrap();
+
+        let first = entries.next().unwrap();
+        let (addr, len) = (first.addr, first.len);
+        assert_eq!((addr, len), (0, 0x9F000));
+        assert_eq!(
+            MultibootMemoryType::try_from(first.mtype),
+            Ok(MultibootMemoryType::Available)
+        );
+
+        // Unknown type is kept as raw value.
+        let second = entries.next().unwrap();
+        let addr = second.addr;
+        assert_eq!(addr, 0x100000);
+        assert_eq!(MultibootMemoryType::try_from(second.mtype), Err(42));
+
+        assert!(entries.next().is_none());
+    }
+
+    fn memory_map_truncated() {
+        let map = [entry(0, 0x1000, 1), entry(0x1000, 0x1000, 1)];
+        let len = size_of::<MultibootMmapEntry>() as u32 + 8;
+        let info = info_with_map(&map, len);
+
+        assert_eq!(info.memory_map().unwrap().count(), 1);
+    }
+
+    fn memory_map_overflow() {
+        let info = MultibootInfo {
+            flags: MULTIBOOT_INFO_MEM_MAP,
+            mmap_addr: u32::MAX - 8,
+            mmap_length: 64,
+            ..Default::default()
+        };
+
+        assert_eq!(info.memory_map().unwrap().count(), 0);
+
+        // Entry size moving past the end of address space ends iteration.
+        let mut map = [entry(0, 0x1000, 1), entry(0x1000, 0x1000, 1)];
+        map[0].size = u32::MAX;
+        let info = info_with_map(&map, size_of_val(&map) as u32);
+
+        assert_eq!(info.memory_map().unwrap().count(), 1);
+    }
+
+    fn modules() {
+        let mods = [
+            MultibootModList {
+                mod_start: 0x200000,
+                mod_end: 0x201000,
+                cmdline: 0,
+                pad: 0,
+            },
+            MultibootModList {
+                mod_start: 0x300000,
+                mod_end: 0x300800,
+                cmdline: 0,
+                pad: 0,
+            },
+        ];
+
+        let info = MultibootInfo {
+            flags: MULTIBOOT_INFO_MODS,
+            mods_addr: mods.as_ptr() as u32,
+            mods_count: mods.len() as u32,
+            ..Default::default()
+        };
+
+        let modules = info.modules().unwrap();
+        assert_eq!(modules.len(), 2);
+        assert!(modules.map(|m| m.mod_end).eq([0x201000, 0x300800]));
+    }
+
+    fn strings() {
+        let info = MultibootInfo {
+            flags: MULTIBOOT_INFO_CMDLINE | MULTIBOOT_INFO_BOOT_LOADER_NAME,
+            cmdline: c"noterm log_level=debug".as_ptr() as u32,
+            boot_loader_name: c"GRUB 2.12".as_ptr() as u32,
+            ..Default::default()
+        };
+
+        assert_eq!(info.cmdline(), Some("noterm log_level=debug"));
+        assert_eq!(info.boot_loader_name(), Some("GRUB 2.12"));
+
+        // Null string pointer is treated as missing string.
+        let info = MultibootInfo {
+            flags: MULTIBOOT_INFO_CMDLINE,
+            ..Default::default()
+        };
+
+        assert!(info.cmdline().is_none());
+    }
+
+    fn elf_sections() {
+        let sections = [MultibootElfSection {
+            name: 1,
+            stype: 1,
+            flags: 0,
+            addr: 0xC0100000,
+            offset: 0x1000,
+            size: 0x2000,
+            link: 0,
+            info: 0,
+            addralign: 16,
+            entsize: 0,
+        }; 3];
+
+        let mut info = MultibootInfo {
+            flags: MULTIBOOT_INFO_ELF_SHDR,
+            u: MultibootSymbolTableUnion {
+                elf_sec: MultibootELFSectionHeaderTable {
+                    num: sections.len() as u32,
+                    size: size_of::<MultibootElfSection>() as u32,
+                    addr: sections.as_ptr() as u32,
+                    shndx: 0,
+                },
+            },
+            ..Default::default()
+        };
+
+        let iter = info.elf_sections().unwrap();
+        assert_eq!(iter.len(), 3);
+        assert!(iter.map(|s| s.addr).all(|addr| addr == 0xC0100000));
+
+        // Entry size smaller than section header means corrupted table.
+        info.u.elf_sec.size = 4;
+        assert!(info.elf_sections().is_none());
+    }
+}
@@ -0,0 +1,752 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Date: 2025-06-13
+// Author: Alexander Kuzin <alkuzindev@gmail.com>.
+
+//! Contains multiboot2 specification related declarations.
+
+use crate::multiboot::MultibootElfSection;
+use core::{ffi::CStr, marker::PhantomData, ptr, slice};
+
+/// The magic field should contain this.
+pub const MULTIBOOT2_HEADER_MAGIC: u32 = 0xE85250D6;
+
+/// This should be in %eax.
+pub const MULTIBOOT2_BOOTLOADER_MAGIC: u32 = 0x36D76289;
+
+/// Alignment of multiboot2 header & tags.
+pub const MULTIBOOT2_TAG_ALIGN: usize = 8;
+
+/// 32-bit (protected) mode of i386 architecture.
+pub const MULTIBOOT2_ARCHITECTURE_I386: u32 = 0;
+
+// Types of the boot information tags.
+
+/// Terminating tag.
+pub const MULTIBOOT2_TAG_TYPE_END: u32 = 0;
+
+/// Kernel command line.
+pub const MULTIBOOT2_TAG_TYPE_CMDLINE: u32 = 1;
+
+/// Boot loader name.
+pub const MULTIBOOT2_TAG_TYPE_BOOT_LOADER_NAME: u32 = 2;
+
+/// Boot module.
+pub const MULTIBOOT2_TAG_TYPE_MODULE: u32 = 3;
+
+/// Basic lower/upper memory information.
+pub const MULTIBOOT2_TAG_TYPE_BASIC_MEMINFO: u32 = 4;
+
+/// BIOS boot device.
+pub const MULTIBOOT2_TAG_TYPE_BOOTDEV: u32 = 5;
+
+/// Memory map.
+pub const MULTIBOOT2_TAG_TYPE_MMAP: u32 = 6;
+
+/// VBE info.
+pub const MULTIBOOT2_TAG_TYPE_VBE: u32 = 7;
+
+/// Framebuffer info.
+pub const MULTIBOOT2_TAG_TYPE_FRAMEBUFFER: u32 = 8;
+
+/// ELF section headers.
+pub const MULTIBOOT2_TAG_TYPE_ELF_SECTIONS: u32 = 9;
+
+/// APM table.
+pub const MULTIBOOT2_TAG_TYPE_APM: u32 = 10;
+
+/// Copy of ACPI 1.0 RSDP.
+pub const MULTIBOOT2_TAG_TYPE_ACPI_OLD: u32 = 14;
+
+/// Copy of ACPI 2.0+ RSDP.
+pub const MULTIBOOT2_TAG_TYPE_ACPI_NEW: u32 = 15;
+
+/// Boot information structure fixed part.
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct Multiboot2InfoHeader {
+    /// Total size of boot information including this field.
+    pub total_size: u32,
+    /// Always zero.
+    pub reserved: u32,
+}
+
+/// Boot information tag header.
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct Multiboot2Tag {
+    /// Tag type.
+    pub typ: u32,
+    /// Tag size including header (without padding).
+    pub size: u32,
+}
+
+/// Memory map tag fixed part.
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct Multiboot2TagMmap {
+    pub typ: u32,
+    pub size: u32,
+    /// Size of one memory map entry.
+    pub entry_size: u32,
+    /// Version of memory map entry format.
+    pub entry_version: u32,
+}
+
+/// Multiboot2 memory map entry.
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct Multiboot2MmapEntry {
+    /// Starting physical address.
+    pub addr: u64,
+    /// Size of the memory region in bytes.
+    pub len: u64,
+    /// Memory region type (same values as in multiboot1).
+    pub mtype: u32,
+    /// Always zero.
+    pub reserved: u32,
+}
+
+/// Framebuffer tag fixed part.
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct Multiboot2TagFramebuffer {
+    pub typ: u32,
+    pub size: u32,
+    /// Framebuffer physical address.
+    pub framebuffer_addr: u64,
+    /// Number of bytes in a single row of the framebuffer.
+    pub framebuffer_pitch: u32,
+    pub framebuffer_width: u32,
+    pub framebuffer_height: u32,
+    /// Bits per pixel.
+    pub framebuffer_bpp: u8,
+    /// Framebuffer type (indexed, RGB or EGA text).
+    pub framebuffer_type: u8,
+    pub reserved: u16,
+}
+
+/// Module tag fixed part (followed by module command line).
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct Multiboot2TagModule {
+    pub typ: u32,
+    pub size: u32,
+    /// The memory used goes from bytes ’mod_start’ to ’mod_end-1’ inclusive.
+    pub mod_start: u32,
+    pub mod_end: u32,
+}
+
+/// ELF sections tag fixed part (followed by section headers).
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct Multiboot2TagElfSections {
+    pub typ: u32,
+    pub size: u32,
+    /// Number of section headers.
+    pub num: u32,
+    /// Size of a single section header.
+    pub entsize: u32,
+    /// Index of the section header string table.
+    pub shndx: u32,
+}
+
+/// Boot module info.
+#[derive(Debug, Clone, Copy)]
+pub struct Multiboot2Module<'a> {
+    /// Module physical start address.
+    pub start: u32,
+    /// Module physical end address (exclusive).
+    pub end: u32,
+    /// Module command line.
+    pub cmdline: Option<&'a str>,
+}
+
+/// Copy of the ACPI RSDP passed by the bootloader.
+#[derive(Debug, Clone, Copy)]
+pub enum AcpiRsdp<'a> {
+    /// ACPI 1.0 RSDP.
+    V1(&'a [u8]),
+    /// ACPI 2.0+ RSDP (XSDP).
+    V2(&'a [u8]),
+}
+
+/// Multiboot2 boot information.
+#[derive(Debug, Clone, Copy)]
+pub struct Multiboot2Info<'a> {
+    /// Address of the boot information structure.
+    addr: usize,
+    /// Total size of the boot information structure.
+    size: usize,
+    /// Bind lifetime to bootloader provided memory.
+    _info: PhantomData<&'a Multiboot2InfoHeader>,
+}
+
+/// Read NUL-terminated string stored inside of tag.
+///
+/// # Parameters
+/// - `addr` - given address of the string.
+/// - `end`  - given address of the end of the tag.
+///
+/// # Returns
+/// - String slice - in case of valid UTF-8 string.
+/// - `None`       - otherwise.
+fn read_tag_str<'a>(addr: usize, end: usize) -> Option<&'a str> {
+    if addr >= end {
+        return None;
+    }
+
+    let bytes = unsafe { slice::from_raw_parts(addr as *const u8, end - addr) };
+    let s = CStr::from_bytes_until_nul(bytes).ok()?;
+    s.to_str().ok()
+}
+
+impl<'a> Multiboot2Info<'a> {
+    /// Construct new multiboot2 boot information wrapper.
+    ///
+    /// # Parameters
+    /// - `addr` - given physical address of the boot information.
+    ///
+    /// # Returns
+    /// - Boot information wrapper - in case of valid structure.
+    /// - `None`                   - otherwise.
+    ///
+    /// # Safety
+    /// - `addr` must point to boot information provided by a multiboot2
+    ///   compliant bootloader which stays valid for lifetime `'a`.
+    pub unsafe fn from_addr(addr: u32) -> Option<Self> {
+        let addr = addr as usize;
+
+        if addr == 0 || (addr & (MULTIBOOT2_TAG_ALIGN - 1)) != 0 {
+            return None;
+        }
+
+        let header = unsafe { ptr::read(addr as *const Multiboot2InfoHeader) };
+        let size = header.total_size as usize;
+
+        // Structure must not wrap around the address space.
+        if size < size_of::<Multiboot2InfoHeader>()
+            || addr.checked_add(size).is_none()
+        {
+            return None;
+        }
+
+        Some(Self {
+            addr,
+            size,
+            _info: PhantomData,
+        })
+    }
+
+    /// Get boot information structure address.
+    ///
+    /// # Returns
+    /// - Physical address of boot information structure.
+    #[inline(always)]
+    pub fn addr(&self) -> usize {
+        self.addr
+    }
+
+    /// Get boot information structure size.
+    ///
+    /// # Returns
+    /// - Size of boot information structure in bytes.
+    #[inline(always)]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Get boot information tags.
+    ///
+    /// # Returns
+    /// - Boot information tags iterator.
+    pub fn tags(&self) -> TagIter<'a> {
+        TagIter {
+            current: self.addr + size_of::<Multiboot2InfoHeader>(),
+            end: self.addr + self.size,
+            _info: PhantomData,
+        }
+    }
+
+    /// Find first tag of specific type.
+    ///
+    /// # Parameters
+    /// - `typ` - given `MULTIBOOT2_TAG_TYPE_*` tag type.
+    ///
+    /// # Returns
+    /// - Found tag - in case of success.
+    /// - `None`    - otherwise.
+    pub fn find_tag(&self, typ: u32) -> Option<Tag<'a>> {
+        self.tags().find(|tag| tag.typ == typ)
+    }
+
+    /// Get memory map provided by the bootloader.
+    ///
+    /// # Returns
+    /// - Memory map entries iterator - if memory map is present.
+    /// - `None`                      - otherwise.
+    pub fn memory_map(&self) -> Option<MemoryMapIter<'a>> {
+        let tag = self.find_tag(MULTIBOOT2_TAG_TYPE_MMAP)?;
+        let mmap: Multiboot2TagMmap = tag.read()?;
+        let entry_size = mmap.entry_size as usize;
+
+        // Entry size smaller than entry struct means corrupted tag.
+        if entry_size < size_of::<Multiboot2MmapEntry>() {
+            return None;
+        }
+
+        Some(MemoryMapIter {
+            current: tag.addr + size_of::<Multiboot2TagMmap>(),
+            end: tag.end(),
+            entry_size,
+            _info: PhantomData,
+        })
+    }
+
+    /// Get framebuffer info.
+    ///
+    /// # Returns
+    /// - Framebuffer tag - if framebuffer info is present.
+    /// - `None`          - otherwise.
+    pub fn framebuffer(&self) -> Option<Multiboot2TagFramebuffer> {
+        self.find_tag(MULTIBOOT2_TAG_TYPE_FRAMEBUFFER)?.read()
+    }
+
+    /// Get boot modules loaded by the bootloader.
+    ///
+    /// # Returns
+    /// - Boot modules iterator.
+    pub fn modules(&self) -> impl Iterator<Item = Multiboot2Module<'a>> {
+        self.tags()
+            .filter(|tag| tag.typ == MULTIBOOT2_TAG_TYPE_MODULE)
+            .filter_map(|tag| {
+                let module: Multiboot2TagModule = tag.read()?;
+                let cmdline_addr = tag.addr + size_of::<Multiboot2TagModule>();
+
+                Some(Multiboot2Module {
+                    start: module.mod_start,
+                    end: module.mod_end,
+                    cmdline: read_tag_str(cmdline_addr, tag.end()),
+                })
+            })
+    }
+
+    /// Get kernel command line.
+    ///
+    /// # Returns
+    /// - Kernel command line - if command line is present.
+    /// - `None`              - otherwise.
+    pub fn cmdline(&self) -> Option<&'a str> {
+        let tag = self.find_tag(MULTIBOOT2_TAG_TYPE_CMDLINE)?;
+        read_tag_str(tag.payload_addr(), tag.end())
+    }
+
+    /// Get bootloader name.
+    ///
+    /// # Returns
+    /// - Bootloader name - if bootloader name is present.
+    /// - `None`          - otherwise.
+    pub fn boot_loader_name(&self) -> Option<&'a str> {
+        let tag = self.find_tag(MULTIBOOT2_TAG_TYPE_BOOT_LOADER_NAME)?;
+        read_tag_str(tag.payload_addr(), tag.end())
+    }
+
+    /// Get kernel ELF section headers.
+    ///
+    /// # Returns
+    /// - ELF section headers iterator - if section headers are present.
+    /// - `None`                       - otherwise.
+    pub fn elf_sections(&self) -> Option<ElfSectionIter<'a>> {
+        let tag = self.find_tag(MULTIBOOT2_TAG_TYPE_ELF_SECTIONS)?;
+        let elf: Multiboot2TagElfSections = tag.read()?;
+        let entry_size = elf.entsize as usize;
+
+        // Entry size smaller than section header means corrupted tag.
+        if entry_size < size_of::<MultibootElfSection>() {
+            return None;
+        }
+
+        Some(ElfSectionIter {
+            current: tag.addr + size_of::<Multiboot2TagElfSections>(),
+            end: tag.end(),
+            entry_size,
+            remaining: elf.num as usize,
+            _info: PhantomData,
+        })
+    }
+
+    /// Get copy of ACPI RSDP. ACPI 2.0+ RSDP is preferred if both present.
+    ///
+    /// # Returns
+    /// - ACPI RSDP - if it is present.
+    /// - `None`    - otherwise.
+    pub fn acpi_rsdp(&self) -> Option<AcpiRsdp<'a>> {
+        if let Some(tag) = self.find_tag(MULTIBOOT2_TAG_TYPE_ACPI_NEW) {
+            return Some(AcpiRsdp::V2(tag.payload()));
+        }
+
+        let tag = self.find_tag(MULTIBOOT2_TAG_TYPE_ACPI_OLD)?;
+        Some(AcpiRsdp::V1(tag.payload()))
+    }
+}
+
+/// Boot information tag.
+#[derive(Debug, Clone, Copy)]
+pub struct Tag<'a> {
+    /// Tag type.
+    pub typ: u32,
+    /// Tag size including header.
+    pub size: u32,
+    /// Tag address.
+    addr: usize,
+    /// Bind lifetime to bootloader provided memory.
+    _info: PhantomData<&'a Multiboot2InfoHeader>,
+}
+
+impl<'a> Tag<'a> {
+    /// Get address of the end of the tag.
+    ///
+    /// # Returns
+    /// - Address right after the last byte of the tag.
+    #[inline(always)]
+    fn end(&self) -> usize {
+        self.addr + self.size as usize
+    }
+
+    /// Get address of the tag data after the tag header.
+    ///
+    /// # Returns
+    /// - Address of the tag payload.
+    #[inline(always)]
+    fn payload_addr(&self) -> usize {
+        self.addr + size_of::<Multiboot2Tag>()
+    }
+
+    /// Get tag data after the tag header.
+    ///
+    /// # Returns
+    /// - Tag payload bytes.
+    pub fn payload(&self) -> &'a [u8] {
+        let len = self.end() - self.payload_addr();
+        unsafe { slice::from_raw_parts(self.payload_addr() as *const u8, len) }
+    }
+
+    /// Read tag as specific tag struct.
+    ///
+    /// # Returns
+    /// - Tag struct - if tag is big enough to contain it.
+    /// - `None`     - otherwise.
+    fn read<T: Copy>(&self) -> Option<T> {
+        if (self.size as usize) < size_of::<T>() {
+            return None;
+        }
+
+        Some(unsafe { ptr::read_unaligned(self.addr as *const T) })
+    }
+}
+
+/// Iterator over multiboot2 boot information tags.
+pub struct TagIter<'a> {
+    /// Address of the current tag.
+    current: usize,
+    /// Address of the end of boot information structure.
+    end: usize,
+    /// Bind lifetime to bootloader provided memory.
+    _info: PhantomData<&'a Multiboot2InfoHeader>,
+}
+
+impl<'a> Iterator for TagIter<'a> {
+    type Item = Tag<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Do not read tag header that does not fit in boot information.
+        let header_end = self.current.checked_add(size_of::<Multiboot2Tag>())?;
+
+        if header_end > self.end {
+            return None;
+        }
+
+        let header = unsafe { ptr::read(self.current as *const Multiboot2Tag) };
+        let size = header.size as usize;
+
+        // Tag must contain its header and fit in boot information.
+        let tag_end = self.current.checked_add(size);
+        let is_corrupted = size < size_of::<Multiboot2Tag>()
+            || tag_end.is_none_or(|end| end > self.end);
+
+        if header.typ == MULTIBOOT2_TAG_TYPE_END || is_corrupted {
+            self.current = self.end;
+            return None;
+        }
+
+        let tag = Tag {
+            typ: header.typ,
+            size: header.size,
+            addr: self.current,
+            _info: PhantomData,
+        };
+
+        // Tags are padded in order to be 8-byte aligned. Iteration ends if
+        // the next tag address overflows.
+        self.current = self
+            .current
+            .checked_add(size.next_multiple_of(MULTIBOOT2_TAG_ALIGN))
+            .unwrap_or(self.end);
+
+        Some(tag)
+    }
+}
+
+/// Iterator over multiboot2 memory map entries.
+pub struct MemoryMapIter<'a> {
+    /// Address of the current memory map entry.
+    current: usize,
+    /// Address of the end of memory map tag.
+    end: usize,
+    /// Size of one memory map entry.
+    entry_size: usize,
+    /// Bind lifetime to bootloader provided memory.
+    _info: PhantomData<&'a Multiboot2InfoHeader>,
+}
+
+impl Iterator for MemoryMapIter<'_> {
+    type Item = Multiboot2MmapEntry;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.current.checked_add(self.entry_size)? > self.end {
+            return None;
+        }
+
+        let ptr = self.current as *const Multiboot2MmapEntry;
+        let entry = unsafe { ptr::read_unaligned(ptr) };
+        self.current += self.entry_size;
+
+        Some(entry)
+    }
+}
+
+/// Iterator over kernel ELF section headers.
+pub struct ElfSectionIter<'a> {
+    /// Address of the current section header.
+    current: usize,
+    /// Address of the end of ELF sections tag.
+    end: usize,
+    /// Size of a single section header.
+    entry_size: usize,
+    /// Number of remaining section headers.
+    remaining: usize,
+    /// Bind lifetime to bootloader provided memory.
+    _info: PhantomData<&'a Multiboot2InfoHeader>,
+}
+
+impl Iterator for ElfSectionIter<'_> {
+    type Item = MultibootElfSection;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0
+            || self.current.checked_add(self.entry_size)? > self.end
+        {
+            return None;
+        }
+
+        let ptr = self.current as *const MultibootElfSection;
+        let section = unsafe { ptr::read_unaligned(ptr) };
+        self.current += self.entry_size;
+        self.remaining -= 1;
+
+        Some(section)
+    }
+}
+
+#[cfg(feature = "ktest")]
+pub mod tests {
+    use super::*;
+    use crate::ktest::TestCase;
+
+    /// Multiboot2 boot information unit tests.
+    pub const TESTS: &[TestCase] = &[
+        TestCase::new("invalid_header", invalid_header),
+        TestCase::new("tag_walk", tag_walk),
+        TestCase::new("corrupted_tag", corrupted_tag),
+        TestCase::new("memory_map", memory_map),
+        TestCase::new("modules", modules),
+    ];
+
+    /// Synthetic 8-byte aligned multiboot2 boot information buffer.
+    #[repr(C, align(8))]
+    struct Multiboot2Buffer([u8; 256]);
+
+    /// Write little endian 32-bit value into buffer.
+    fn put_u32(buf: &mut [u8], offset: usize, value: u32) {
+        buf[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+    }
+
+    /// Write little endian 64-bit value into buffer.
+    fn put_u64(buf: &mut [u8], offset: usize, value: u64) {
+        buf[offset..offset + 8].copy_from_slice(&value.to_le_bytes());
+    }
+
+    /// Append tag with payload to buffer.
+    ///
+    /// # Parameters
+    /// - `buf`     - given boot information buffer.
+    /// - `offset`  - given tag offset (updated to the next tag offset).
+    /// - `typ`     - given tag type.
+    /// - `payload` - given tag data after the tag header.
+    fn put_tag(buf: &mut [u8], offset: &mut usize, typ: u32, payload: &[u8]) {
+        let size = size_of::<Multiboot2Tag>() + payload.len();
+
+        put_u32(buf, *offset, typ);
+        put_u32(buf, *offset + 4, size as u32);
+        buf[*offset + 8..*offset + size].copy_from_slice(payload);
+        *offset += size.next_multiple_of(MULTIBOOT2_TAG_ALIGN);
+    }
+
+    /// Append terminating tag & set total size of boot information.
+    ///
+    /// # Parameters
+    /// - `buf`    - given boot information buffer.
+    /// - `offset` - given terminating tag offset.
+    fn finish(buf: &mut [u8], mut offset: usize) {
+        put_tag(buf, &mut offset, MULTIBOOT2_TAG_TYPE_END, &[]);
+        put_u32(buf, 0, offset as u32);
+    }
+
+    /// Wrap synthetic boot information.
+    ///
+    /// # Parameters
+    /// - `buffer` - given boot information buffer.
+    ///
+    /// # Returns
+    /// - Boot information wrapper - in case of valid structure.
+    /// - `None`                   - otherwise.
+    fn parse(buffer: &Multiboot2Buffer) -> Option<Multiboot2Info<'_>> {
+        unsafe { Multiboot2Info::from_addr(buffer.0.as_ptr() as u32) }
+    }
+
+    fn invalid_header() {
+        let mut buffer = Multiboot2Buffer([0; 256]);
+        assert!(unsafe { Multiboot2Info::from_addr(0) }.is_none());
+
+        // Total size smaller than the fixed part.
+        put_u32(&mut buffer.0, 0, 4);
+        assert!(parse(&buffer).is_none());
+
+        // Unaligned structure.
+        finish(&mut buffer.0, 8);
+        let addr = buffer.0.as_ptr() as u32 + 4;
+        assert!(unsafe { Multiboot2Info::from_addr(addr) }.is_none());
+        assert_eq!(parse(&buffer).unwrap().size(), 16);
+    }
+
+    fn tag_walk() {
+        let mut buffer = Multiboot2Buffer([0; 256]);
+        let buf = &mut buffer.0;
+        let mut offset = 8;
+
+        put_tag(buf, &mut offset, MULTIBOOT2_TAG_TYPE_CMDLINE, b"noterm\0");
+        put_tag(buf, &mut offset, 0x1234, &[1, 2, 3]);
+        put_tag(
+            buf,
+            &mut offset,
+            MULTIBOOT2_TAG_TYPE_BOOT_LOADER_NAME,
+            b"GRUB 2.12\0",
+        );
+        finish(buf, offset);
+
+        // Tags after the terminating one are ignored.
+        offset += size_of::<Multiboot2Tag>();
+        put_tag(buf, &mut offset, MULTIBOOT2_TAG_TYPE_APM, &[0; 8]);
+        put_u32(buf, 0, offset as u32);
+
+        let info = parse(&buffer).unwrap();
+        let types = [
+            MULTIBOOT2_TAG_TYPE_CMDLINE,
+            0x1234,
+            MULTIBOOT2_TAG_TYPE_BOOT_LOADER_NAME,
+        ];
+
+        assert!(info.tags().map(|tag| tag.typ).eq(types));
+        assert_eq!(info.find_tag(0x1234).unwrap().payload(), &[1, 2, 3]);
+        assert_eq!(info.cmdline(), Some("noterm"));
+        assert_eq!(info.boot_loader_name(), Some("GRUB 2.12"));
+        assert!(info.find_tag(MULTIBOOT2_TAG_TYPE_APM).is_none());
+        assert!(info.framebuffer().is_none());
+    }
+
+    fn corrupted_tag() {
+        let mut buffer = Multiboot2Buffer([0; 256]);
+        let buf = &mut buffer.0;
+        let mut offset = 8;
+
+        put_tag(buf, &mut offset, MULTIBOOT2_TAG_TYPE_CMDLINE, b"a\0");
+
+        // Tag size exceeding boot information ends the walk.
+        put_u32(buf, offset, MULTIBOOT2_TAG_TYPE_BOOT_LOADER_NAME);
+        put_u32(buf, offset + 4, u32::MAX);
+        finish(buf, offset + 8);
+
+        assert_eq!(parse(&buffer).unwrap().tags().count(), 1);
+
+        // Tag smaller than its header ends the walk.
+        put_u32(&mut buffer.0, offset + 4, 4);
+        assert_eq!(parse(&buffer).unwrap().tags().count(), 1);
+    }
+
+    fn memory_map() {
+        let mut buffer = Multiboot2Buffer([0; 256]);
+        let buf = &mut buffer.0;
+        let mut payload = [0; 8 + 2 * 24];
+
+        put_u32(&mut payload, 0, 24);
+        put_u64(&mut payload, 8, 0x100000);
+        put_u64(&mut payload, 16, 0x1000000);
+        put_u32(&mut payload, 24, 1);
+        put_u64(&mut payload, 32, 0xE0000);
+        put_u64(&mut payload, 40, 0x20000);
+        put_u32(&mut payload, 48, 2);
+
+        let mut offset = 8;
+        put_tag(buf, &mut offset, MULTIBOOT2_TAG_TYPE_MMAP, &payload);
+        finish(buf, offset);
+
+        let info = parse(&buffer).unwrap();
+        let entries = info.memory_map().unwrap();
+        let expected = [(0x100000, 1), (0xE0000, 2)];
+
+        assert!(entries.map(|e| (e.addr, e.mtype)).eq(expected));
+
+        // Entry size smaller than entry struct means corrupted tag.
+        put_u32(&mut buffer.0, 16, 8);
+        assert!(parse(&buffer).unwrap().memory_map().is_none());
+    }
+
+    fn modules() {
+        let mut buffer = Multiboot2Buffer([0; 256]);
+        let buf = &mut buffer.0;
+        let mut offset = 8;
+
+        let mut payload = [0; 8 + 5];
+        put_u32(&mut payload, 0, 0x200000);
+        put_u32(&mut payload, 4, 0x201000);
+        payload[8..].copy_from_slice(b"init\0");
+        put_tag(buf, &mut offset, MULTIBOOT2_TAG_TYPE_MODULE, &payload);
+
+        put_u32(&mut payload, 0, 0x300000);
+        put_u32(&mut payload, 4, 0x300800);
+        payload[8..].copy_from_slice(b"\0\0\0\0\0");
+        put_tag(buf, &mut offset, MULTIBOOT2_TAG_TYPE_MODULE, &payload);
+        finish(buf, offset);
+
+        let info = parse(&buffer).unwrap();
+        let mut modules = info.modules();
+
+        let first = modules.next().unwrap();
+        assert_eq!((first.start, first.end), (0x200000, 0x201000));
+        assert_eq!(first.cmdline, Some("init"));
+
+        let second = modules.next().unwrap();
+        assert_eq!(second.end, 0x300800);
+        assert_eq!(second.cmdline, Some(""));
+        assert!(modules.next().is_none());
+    }
+}